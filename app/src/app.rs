@@ -7,7 +7,7 @@ use cirrus_theming::theme::Theme;
 use eframe::egui::{self, Color32, Context, CornerRadius, Frame, Key, Margin, Vec2, ViewportCommand};
 use egui_notify::ToastLevel;
 
-use crate::{about_window::AboutWindow, config::config::Config, context_menu::ContextMenu, home_menu::HomeMenu, image_loader::ImageLoader, image_selector::ImageSelector, monitor_size::MonitorSize, settings::SettingsMenu, tutorial::Tutorial, ui_controls::UIControlsManager, viewport::Viewport, windows::WindowsManager};
+use crate::{about_window::AboutWindow, config::config::Config, context_menu::ContextMenu, crop_tool::CropTool, home_menu::HomeMenu, image_loader::{ImageLoader, transform::TransformKeyBinds}, image_selector::ImageSelector, monitor_size::MonitorSize, settings::SettingsMenu, tutorial::Tutorial, ui_controls::UIControlsManager, viewport::{TransparencyBackground, Viewport}, windows::WindowsManager};
 
 pub struct Roseate {
     theme: Theme,
@@ -25,6 +25,7 @@ pub struct Roseate {
     windows_manager: WindowsManager,
     ui_controls_manager: UIControlsManager,
     context_menu: ContextMenu,
+    crop_tool: CropTool,
     tutorial: Tutorial,
 
     open_image_input_reader: Option<BoxedEguiInputReaderFunc>,
@@ -51,6 +52,7 @@ impl Roseate {
         let home_menu = HomeMenu::new();
         let ui_controls_manager = UIControlsManager::new();
         let context_menu = ContextMenu::new();
+        let crop_tool = CropTool::new();
         let tutorial = Tutorial::new();
 
         Self {
@@ -69,6 +71,7 @@ impl Roseate {
             ui_controls_manager,
             config_manager,
             context_menu,
+            crop_tool,
             tutorial,
 
             open_image_input_reader: None,
@@ -82,11 +85,19 @@ impl Roseate {
     fn handle_inputs(&mut self, ctx: &Context) {
         let config = &self.config_manager.config;
 
+        // the crop tool goes first so it gets to escape before fullscreen does.
+        self.crop_tool.handle_input(
+            &ctx,
+            &mut self.notifier,
+            &config.key_binds.crop_image
+        );
+
         self.windows_manager.handle_input(
             &ctx,
             &mut self.notifier,
             &config.key_binds.show_image_info,
-            &config.key_binds.show_extra_image_info
+            &config.key_binds.show_extra_image_info,
+            &config.key_binds.show_adjustments
         );
         self.ui_controls_manager.handle_input(
             &ctx,
@@ -235,6 +246,61 @@ impl eframe::App for Roseate {
                     open_image_input_reader
                 );
 
+                self.image_loader.handle_page_input(
+                    ui,
+                    &mut self.image_selector,
+                    &self.monitor_size,
+                    config.image.backend.get_decoding_backend(),
+                    &mut self.notifier,
+                    &config.key_binds.next_page,
+                    &config.key_binds.previous_page,
+                );
+
+                // crops and slider changes wait until the image is done loading so they're never dropped.
+                if !self.image_loader.state.is_loading() {
+                    if let Some(crop) = self.crop_tool.take_crop() {
+                        self.image_loader.crop(
+                            crop,
+                            &mut self.image_selector,
+                            &self.monitor_size,
+                            config.image.backend.get_decoding_backend(),
+                            &mut self.notifier,
+                        );
+                    }
+                }
+
+                // checked again as the crop may have just started loading the image.
+                if !self.image_loader.state.is_loading() {
+                    if let Some(adjustments) = self.windows_manager.take_adjustments() {
+                        self.image_loader.adjust(
+                            adjustments,
+                            &mut self.image_selector,
+                            &self.monitor_size,
+                            config.image.backend.get_decoding_backend(),
+                            &mut self.notifier,
+                        );
+                    }
+                }
+
+                // the context menu only gets the chance to rotate and flip the image a frame later.
+                if let Some(transform) = self.context_menu.take_transform() {
+                    self.image_loader.queue_transform(transform);
+                }
+
+                self.image_loader.handle_transform_input(
+                    ui,
+                    &mut self.image_selector,
+                    &self.monitor_size,
+                    config.image.backend.get_decoding_backend(),
+                    &mut self.notifier,
+                    TransformKeyBinds {
+                        rotate_clockwise: &config.key_binds.rotate_clockwise,
+                        rotate_anticlockwise: &config.key_binds.rotate_anticlockwise,
+                        flip_horizontally: &config.key_binds.flip_horizontally,
+                        flip_vertically: &config.key_binds.flip_vertically,
+                    },
+                );
+
                 self.image_loader.dynamic_sampling_update(
                     &self.viewport.zoom,
                     self.viewport.is_busy,
@@ -263,7 +329,7 @@ impl eframe::App for Roseate {
                                     config.ui.image_info.show_location,
                                 );
 
-                                self.context_menu.show(ui, &mut self.windows_manager, &mut self.ui_controls_manager);
+                                self.context_menu.show(ui, &mut self.windows_manager, &mut self.ui_controls_manager, &mut self.crop_tool);
                                 self.ui_controls_manager.show(
                                     ui,
                                     &mut self.viewport,
@@ -273,6 +339,8 @@ impl eframe::App for Roseate {
                                     &mut self.show_settings,
                                 );
 
+                                let page_count = uploaded_image.image.pages.lock().unwrap().len();
+
                                 let config_padding = config.ui.viewport.padding;
                                 let proper_padding_percentage = ((100.0 - config_padding) / 100.0).clamp(0.0, 1.0);
 
@@ -280,14 +348,27 @@ impl eframe::App for Roseate {
                                     ui,
                                     &uploaded_image.image.size,
                                     uploaded_image.resource.clone(), // ImageResource is safe to clone without expensive dup
+                                    (uploaded_image.image.page, page_count),
                                     &mut self.notifier,
                                     proper_padding_percentage,
                                     config.ui.viewport.zoom_into_cursor,
                                     config.ui.viewport.fit_to_window,
                                     config.ui.viewport.animate_fit_to_window,
                                     config.ui.viewport.animate_reset,
+                                    // opaque images have nothing to show through.
+                                    match uploaded_image.image_info.colour_type.has_alpha() {
+                                        true => config.ui.viewport.get_transparency_background(),
+                                        false => TransparencyBackground::Theme,
+                                    },
                                     &config.key_binds.reset_viewport
                                 );
+
+                                self.crop_tool.show(
+                                    ui,
+                                    self.viewport.image_rect,
+                                    uploaded_image.image.size,
+                                    uploaded_image.image.is_cropped(),
+                                );
                             });
 
                         ctx.request_repaint_after_secs(0.5); // We need to request repaints just in
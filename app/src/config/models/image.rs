@@ -1,3 +1,4 @@
+use roseate_core::{animation::AnimationStreaming, limits::DecodeLimits, processing::{quantization::Dithering, tone_mapping::ToneMapper}};
 use serde::{Deserialize, Serialize};
 use crate::{config::models::image_optimizations::ImageOptimizations, image::backend::DefaultDecodingBackend};
 use std::hash::Hash;
@@ -10,12 +11,37 @@ pub struct Image {
     pub optimizations: ImageOptimizations,
     #[serde(default)]
     pub backend: Backend,
+    #[serde(default)]
+    pub hdr: Hdr,
+    #[serde(default)]
+    pub quantization: Quantization,
+    #[serde(default)]
+    pub orientation: Orientation,
+    #[serde(default)]
+    pub animation: Animation,
 }
 
-#[derive(Serialize, Deserialize, Default, Hash, Clone)]
+#[derive(Serialize, Deserialize, Hash, Clone)]
 pub struct Backend {
     #[serde(default = "decoder_default")]
     pub decoder: String,
+    #[serde(default = "max_dimension_default")]
+    pub max_width: u32,
+    #[serde(default = "max_dimension_default")]
+    pub max_height: u32,
+    #[serde(default = "max_memory_default")]
+    pub max_memory: u64,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self {
+            decoder: decoder_default(),
+            max_width: max_dimension_default(),
+            max_height: max_dimension_default(),
+            max_memory: max_memory_default(),
+        }
+    }
 }
 
 impl Backend {
@@ -26,12 +52,143 @@ impl Backend {
             _ => DefaultDecodingBackend::ImageRS
         }
     }
+
+    /// A limit of zero means no limit.
+    pub fn get_decode_limits(&self) -> DecodeLimits {
+        DecodeLimits::new(
+            (self.max_width != 0).then_some(self.max_width),
+            (self.max_height != 0).then_some(self.max_height),
+            // the config has it in megabytes.
+            (self.max_memory != 0).then_some(self.max_memory.saturating_mul(1024 * 1024)),
+        )
+    }
 }
 
 fn decoder_default() -> String {
     String::from("image-rs")
 }
 
+fn max_dimension_default() -> u32 {
+    0
+}
+
+fn max_memory_default() -> u64 {
+    4096
+}
+
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct Hdr {
+    #[serde(default = "tone_mapper_default")]
+    pub tone_mapper: String,
+}
+
+impl Default for Hdr {
+    fn default() -> Self {
+        Self {
+            tone_mapper: tone_mapper_default()
+        }
+    }
+}
+
+impl Hdr {
+    pub fn get_tone_mapper(&self) -> ToneMapper {
+        match self.tone_mapper.to_lowercase().as_str() {
+            "reinhard" => ToneMapper::Reinhard,
+            "hable" => ToneMapper::Hable,
+            "aces" | &_ => ToneMapper::AcesFilmic,
+        }
+    }
+}
+
+fn tone_mapper_default() -> String {
+    String::from("aces")
+}
+
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct Quantization {
+    #[serde(default = "dithering_default")]
+    pub dithering: String,
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Self {
+            dithering: dithering_default()
+        }
+    }
+}
+
+impl Quantization {
+    pub fn get_dithering(&self) -> Dithering {
+        match self.dithering.to_lowercase().as_str() {
+            "none" => Dithering::None,
+            "ordered" => Dithering::Ordered,
+            "floyd-steinberg" => Dithering::FloydSteinberg,
+            "blue-noise" | &_ => Dithering::BlueNoise,
+        }
+    }
+}
+
+fn dithering_default() -> String {
+    String::from("blue-noise")
+}
+
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct Orientation {
+    #[serde(default = "super::true_default")]
+    pub auto: bool,
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self {
+            auto: true
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct Animation {
+    #[serde(default = "stream_over_default")]
+    pub stream_over: u64,
+    #[serde(default = "frames_ahead_default")]
+    pub frames_ahead: usize,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            stream_over: stream_over_default(),
+            frames_ahead: frames_ahead_default(),
+        }
+    }
+}
+
+impl Animation {
+    /// Zero means animations are never streamed.
+    pub fn get_animation_streaming(&self) -> Option<AnimationStreaming> {
+        (self.stream_over != 0).then_some(
+            AnimationStreaming {
+                // the config has it in megabytes.
+                max_cached_bytes: self.stream_over.saturating_mul(1024 * 1024),
+                frames_ahead: self.frames_ahead.max(1),
+            }
+        )
+    }
+}
+
+fn stream_over_default() -> u64 {
+    512
+}
+
+fn frames_ahead_default() -> usize {
+    8
+}
+
 
 #[derive(Serialize, Deserialize, Default, Hash, Clone)]
 pub struct ImageLoading {
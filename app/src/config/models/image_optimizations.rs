@@ -1,4 +1,5 @@
 use std::{hash::Hash};
+use roseate_core::modifications::ResizeFilter;
 use serde::{Deserialize, Deserializer, Serialize};
 use crate::{image_loader::optimization};
 
@@ -14,6 +15,12 @@ pub struct ImageOptimizations {
     pub experimental_dynamic_sampling: DynamicSampling,
     #[serde(default, deserialize_with = "deserialize_image_optimization_field_value")]
     pub experimental_multi_threaded_sampling: MultiThreadedSampling,
+    #[serde(default, deserialize_with = "deserialize_image_optimization_field_value")]
+    pub raw_embedded_preview: RawEmbeddedPreview,
+    #[serde(default, deserialize_with = "deserialize_image_optimization_field_value")]
+    pub progressive_decoding: ProgressiveDecoding,
+    #[serde(default)]
+    pub resize_filter: ResizeFilter,
 }
 
 impl Default for ImageOptimizations {
@@ -24,6 +31,9 @@ impl Default for ImageOptimizations {
             experimental_consume_pixels_during_gpu_upload: ConsumePixelsDuringGPUUpload::default(),
             experimental_dynamic_sampling: DynamicSampling::default(),
             experimental_multi_threaded_sampling: MultiThreadedSampling::default(),
+            raw_embedded_preview: RawEmbeddedPreview::default(),
+            progressive_decoding: ProgressiveDecoding::default(),
+            resize_filter: ResizeFilter::default(),
         }
     }
 }
@@ -67,7 +77,10 @@ impl ImageOptimizations {
                             }
                         ),
                         false => None,
-                    }
+                    },
+                    raw_embedded_preview: self.raw_embedded_preview.enabled,
+                    progressive_decoding: self.progressive_decoding.enabled,
+                    resize_filter: self.resize_filter,
                 }
             }
         }
@@ -189,6 +202,44 @@ impl DefaultWithEnabled for DynamicSampling {
 }
 
 
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct RawEmbeddedPreview {
+    #[serde(default = "super::false_default")]
+    pub enabled: bool,
+}
+
+impl Default for RawEmbeddedPreview {
+    fn default() -> Self {
+        Self::default_with_enabled(false)
+    }
+}
+
+impl DefaultWithEnabled for RawEmbeddedPreview {
+    fn default_with_enabled(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
+pub struct ProgressiveDecoding {
+    #[serde(default = "super::true_default")]
+    pub enabled: bool,
+}
+
+impl Default for ProgressiveDecoding {
+    fn default() -> Self {
+        Self::default_with_enabled(true)
+    }
+}
+
+impl DefaultWithEnabled for ProgressiveDecoding {
+    fn default_with_enabled(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+
 trait DefaultWithEnabled: Default {
     fn default_with_enabled(enabled: bool) -> Self;
 }
@@ -12,6 +12,22 @@ pub struct KeyBinds {
     pub show_ui_controls: String,
     #[serde(default = "open_image")]
     pub open_image: String,
+    #[serde(default = "next_page")]
+    pub next_page: String,
+    #[serde(default = "previous_page")]
+    pub previous_page: String,
+    #[serde(default = "rotate_clockwise")]
+    pub rotate_clockwise: String,
+    #[serde(default = "rotate_anticlockwise")]
+    pub rotate_anticlockwise: String,
+    #[serde(default = "flip_horizontally")]
+    pub flip_horizontally: String,
+    #[serde(default = "flip_vertically")]
+    pub flip_vertically: String,
+    #[serde(default = "crop_image")]
+    pub crop_image: String,
+    #[serde(default = "show_adjustments")]
+    pub show_adjustments: String,
 }
 
 fn show_image_info() -> String { "I".into() }
@@ -19,6 +35,14 @@ fn show_extra_image_info() -> String { "CTRL+I".into() }
 fn reset_viewport() -> String { "R".into() }
 fn show_ui_controls() -> String { "C".into() }
 fn open_image() -> String { "CTRL+O".into() }
+fn next_page() -> String { "N".into() }
+fn previous_page() -> String { "P".into() }
+fn rotate_clockwise() -> String { "E".into() }
+fn rotate_anticlockwise() -> String { "Q".into() }
+fn flip_horizontally() -> String { "H".into() }
+fn flip_vertically() -> String { "V".into() }
+fn crop_image() -> String { "X".into() }
+fn show_adjustments() -> String { "B".into() }
 
 impl Default for KeyBinds {
     fn default() -> Self {
@@ -28,6 +52,14 @@ impl Default for KeyBinds {
             reset_viewport: reset_viewport(),
             show_ui_controls: show_ui_controls(),
             open_image: open_image(),
+            next_page: next_page(),
+            previous_page: previous_page(),
+            rotate_clockwise: rotate_clockwise(),
+            rotate_anticlockwise: rotate_anticlockwise(),
+            flip_horizontally: flip_horizontally(),
+            flip_vertically: flip_vertically(),
+            crop_image: crop_image(),
+            show_adjustments: show_adjustments(),
         }
     }
 }
\ No newline at end of file
@@ -2,7 +2,9 @@ use std::hash::Hash;
 
 use serde::{Serialize, Deserialize};
 
-use crate::config::models::ui::controls::Controls;
+use eframe::egui::Color32;
+
+use crate::{config::models::ui::controls::Controls, viewport::TransparencyBackground};
 
 pub mod controls;
 pub(self) use super::{true_default, false_default};
@@ -32,6 +34,8 @@ pub struct Viewport {
     pub animate_fit_to_window: bool,
     #[serde(default = "super::true_default")]
     pub animate_reset: bool,
+    #[serde(default = "transparency_background_default")]
+    pub transparency_background: String,
 }
 
 impl Hash for Viewport {
@@ -41,6 +45,7 @@ impl Hash for Viewport {
         self.fit_to_window.hash(state);
         self.animate_fit_to_window.hash(state);
         self.animate_reset.hash(state);
+        self.transparency_background.hash(state);
     }
 }
 
@@ -51,7 +56,21 @@ impl Default for Viewport {
             zoom_into_cursor: true,
             fit_to_window: true,
             animate_fit_to_window: true,
-            animate_reset: true
+            animate_reset: true,
+            transparency_background: transparency_background_default(),
+        }
+    }
+}
+
+impl Viewport {
+    pub fn get_transparency_background(&self) -> TransparencyBackground {
+        match self.transparency_background.to_lowercase().as_str() {
+            "theme" => TransparencyBackground::Theme,
+            colour if colour.starts_with('#') => match Color32::from_hex(colour) {
+                Ok(colour) => TransparencyBackground::Solid(colour),
+                Err(_) => TransparencyBackground::Checkerboard,
+            },
+            "checkerboard" | &_ => TransparencyBackground::Checkerboard,
         }
     }
 }
@@ -60,6 +79,10 @@ fn ui_padding() -> f32 {
     2.0
 }
 
+fn transparency_background_default() -> String {
+    String::from("checkerboard")
+}
+
 
 #[derive(Serialize, Deserialize, Hash, Clone)]
 pub struct HomeMenu {
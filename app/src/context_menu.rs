@@ -1,15 +1,20 @@
 use eframe::egui::{self, Align, Context, CornerRadius, FontId, Id, LayerId, Layout, Popup, PopupAnchor, PopupCloseBehavior, PopupKind, Pos2, Style, Ui};
 
-use crate::{ui_controls::UIControlsManager, windows::WindowsManager};
+use roseate_core::orientation::ImageOrientation;
+
+use crate::{crop_tool::CropTool, ui_controls::UIControlsManager, windows::WindowsManager};
 
 pub struct ContextMenu {
-    show_menu: Option<Pos2>
+    show_menu: Option<Pos2>,
+    /// The rotation or flip picked from the menu, waiting for the image loader to pick it up.
+    transform: Option<ImageOrientation>,
 }
 
 impl ContextMenu {
     pub fn new() -> Self {
         Self {
             show_menu: None,
+            transform: None,
         }
     }
 
@@ -31,7 +36,13 @@ impl ContextMenu {
         }
     }
 
-    pub fn show(&mut self, ui: &mut Ui, windows_manager: &mut WindowsManager, ui_controls_manager: &mut UIControlsManager) {
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        windows_manager: &mut WindowsManager,
+        ui_controls_manager: &mut UIControlsManager,
+        crop_tool: &mut CropTool
+    ) {
         if let Some(mouse_position) = self.show_menu {
             let id = Id::new("context_menu");
 
@@ -95,6 +106,33 @@ impl ContextMenu {
                             }
                         });
 
+                        ui.menu_button("Rotate / Flip", |ui| {
+                            for (label, transform) in [
+                                ("Rotate Clockwise", ImageOrientation::Rotate90),
+                                ("Rotate Anticlockwise", ImageOrientation::Rotate270),
+                                ("Flip Horizontally", ImageOrientation::FlipHorizontal),
+                                ("Flip Vertically", ImageOrientation::FlipVertical),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    self.transform = Some(transform);
+
+                                    self.show_menu = None;
+                                }
+                            }
+                        });
+
+                        if ui.button("Crop").clicked() {
+                            crop_tool.toggle();
+
+                            self.show_menu = None;
+                        }
+
+                        if ui.button("Adjustments").clicked() {
+                            windows_manager.show_adjustments = !windows_manager.show_adjustments;
+
+                            self.show_menu = None;
+                        }
+
                         if ui.button("Toggle Controls").clicked() {
                             ui_controls_manager.show_controls = match ui_controls_manager.show_controls {
                                 Some(show) => Some(!show),
@@ -117,4 +155,8 @@ impl ContextMenu {
             }
         }
     }
+
+    pub fn take_transform(&mut self) -> Option<ImageOrientation> {
+        self.transform.take()
+    }
 }
@@ -0,0 +1,484 @@
+use std::time::Duration;
+
+use cirrus_egui::notifier::{Notifier, banner::BannerPlacement};
+use cirrus_soft_binds::egui::{BoxedEguiInputReaderFunc, parse_and_get_egui_input_reader_from_string};
+use eframe::egui::{self, Align2, Color32, Context, CornerRadius, CursorIcon, Id, Key, Modifiers, Pos2, Rect, Sense, Stroke, StrokeKind, Ui, Vec2};
+use egui_notify::ToastLevel;
+use log::debug;
+use roseate_core::decoded_image::ImageSize;
+
+/// How big the drag handles on the corners and edges of the selection are (in points).
+const HANDLE_SIZE: f32 = 10.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CropAspectRatio {
+    Free,
+    Square,
+    FourByThree,
+    SixteenByNine,
+    /// The aspect ratio of the image itself.
+    Original,
+}
+
+impl CropAspectRatio {
+    const ALL: [Self; 5] = [Self::Free, Self::Square, Self::FourByThree, Self::SixteenByNine, Self::Original];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Free => "Free",
+            Self::Square => "1:1",
+            Self::FourByThree => "4:3",
+            Self::SixteenByNine => "16:9",
+            Self::Original => "Original",
+        }
+    }
+
+    /// Width divided by height, `None` if the selection can be any shape.
+    fn ratio(&self, image_size: ImageSize) -> Option<f32> {
+        match self {
+            Self::Free => None,
+            Self::Square => Some(1.0),
+            Self::FourByThree => Some(4.0 / 3.0),
+            Self::SixteenByNine => Some(16.0 / 9.0),
+            Self::Original => Some(image_size.0 as f32 / image_size.1 as f32),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CropHandle {
+    Move,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl CropHandle {
+    fn moves_left(&self) -> bool {
+        matches!(self, Self::Left | Self::TopLeft | Self::BottomLeft)
+    }
+
+    fn moves_right(&self) -> bool {
+        matches!(self, Self::Right | Self::TopRight | Self::BottomRight)
+    }
+
+    fn moves_top(&self) -> bool {
+        matches!(self, Self::Top | Self::TopLeft | Self::TopRight)
+    }
+
+    fn moves_bottom(&self) -> bool {
+        matches!(self, Self::Bottom | Self::BottomLeft | Self::BottomRight)
+    }
+
+    fn cursor_icon(&self, dragging: bool) -> CursorIcon {
+        match self {
+            Self::Move => match dragging {
+                true => CursorIcon::Grabbing,
+                false => CursorIcon::Grab,
+            },
+            Self::Left | Self::Right => CursorIcon::ResizeHorizontal,
+            Self::Top | Self::Bottom => CursorIcon::ResizeVertical,
+            Self::TopLeft | Self::BottomRight => CursorIcon::ResizeNwSe,
+            Self::TopRight | Self::BottomLeft => CursorIcon::ResizeNeSw,
+        }
+    }
+}
+
+/// Lets the user drag out the part of the image they want to crop it down to.
+pub struct CropTool {
+    pub active: bool,
+
+    /// The selection in pixels of the image as it's shown, `None` selects the whole image.
+    selection: Option<Rect>,
+    aspect_ratio: CropAspectRatio,
+    handle: Option<CropHandle>,
+    image_size: ImageSize,
+
+    crop_input_reader: Option<BoxedEguiInputReaderFunc>,
+
+    /// The crop the user confirmed (or `Some(None)` if they took the
+    /// crop back off), waiting for the image loader to pick it up.
+    confirmed_crop: Option<Option<(u32, u32, u32, u32)>>,
+}
+
+impl CropTool {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+
+            selection: None,
+            aspect_ratio: CropAspectRatio::Free,
+            handle: None,
+            image_size: (0, 0),
+
+            crop_input_reader: None,
+
+            confirmed_crop: None,
+        }
+    }
+
+    pub fn handle_input(&mut self, ctx: &Context, notifier: &mut Notifier, crop_image_key: &String) {
+        let crop_input_reader = self.crop_input_reader.get_or_insert_with(|| {
+            match parse_and_get_egui_input_reader_from_string(crop_image_key, |i, key| i.key_pressed(key)) {
+                Ok(reader) => Box::new(reader),
+                Err(error) => {
+                    notifier.toast(
+                        error.to_string(),
+                        ToastLevel::Error,
+                        |_| {}
+                    );
+
+                    Box::new(|i| i.key_pressed(Key::X))
+                },
+            }
+        });
+
+        if ctx.input(crop_input_reader) {
+            self.toggle();
+
+            notifier.show_banner(
+                match self.active {
+                    true => format!("Crop Mode ({crop_image_key})"),
+                    false => String::from("Exited Crop Mode"),
+                },
+                BannerPlacement::BOTTOM,
+                Duration::from_secs(2)
+            );
+        }
+
+        if !self.active {
+            return;
+        }
+
+        // we consume escape so it doesn't take us out of fullscreen too.
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+            self.active = false;
+        }
+
+        if ctx.input(|i| i.key_pressed(Key::Enter)) {
+            self.confirm();
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+
+        // every time crop mode is entered we start with the whole image selected.
+        self.selection = None;
+        self.handle = None;
+    }
+
+    /// Draws the selection over the image (`image_rect` being where the viewport painted
+    /// it) along with the aspect ratio presets and the size of the selection in pixels.
+    pub fn show(&mut self, ui: &mut Ui, image_rect: Rect, image_size: ImageSize, is_cropped: bool) {
+        if !self.active {
+            return;
+        }
+
+        // a different image (or page) has a different size so the old selection is no good.
+        if image_size != self.image_size {
+            self.image_size = image_size;
+            self.selection = None;
+        }
+
+        let image_bounds = Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(image_size.0 as f32, image_size.1 as f32)
+        );
+
+        let mut selection = self.selection.unwrap_or(image_bounds);
+
+        // points on screen per pixel of the image.
+        let scale = image_rect.width() / image_size.0 as f32;
+
+        let to_screen = |rect: Rect| Rect::from_min_max(
+            image_rect.min + rect.min.to_vec2() * scale,
+            image_rect.min + rect.max.to_vec2() * scale,
+        );
+        let to_pixels = |position: Pos2| (((position - image_rect.min) / scale).to_pos2())
+            .clamp(image_bounds.min, image_bounds.max);
+
+        let response = ui.interact(
+            image_rect.expand(HANDLE_SIZE),
+            Id::new("crop_tool"),
+            Sense::drag()
+        );
+
+        if response.drag_started() {
+            if let Some(pointer_position) = response.interact_pointer_pos() {
+                self.handle = Self::handle_at(to_screen(selection), pointer_position);
+
+                // dragging outside of the selection starts a new one.
+                if self.handle.is_none() {
+                    let position = to_pixels(pointer_position);
+
+                    selection = Rect::from_min_size(position, Vec2::ZERO);
+                    self.handle = Some(CropHandle::BottomRight);
+                }
+            }
+        }
+
+        if response.dragged() {
+            match (self.handle, response.interact_pointer_pos()) {
+                (Some(CropHandle::Move), _) => {
+                    let moved_selection = selection.translate(response.drag_delta() / scale);
+
+                    // the selection is pushed back inside the image if it's moved past the edge.
+                    let push_back = Vec2::new(
+                        (image_bounds.min.x - moved_selection.min.x).max(0.0)
+                            - (moved_selection.max.x - image_bounds.max.x).max(0.0),
+                        (image_bounds.min.y - moved_selection.min.y).max(0.0)
+                            - (moved_selection.max.y - image_bounds.max.y).max(0.0),
+                    );
+
+                    selection = moved_selection.translate(push_back);
+                },
+                (Some(handle), Some(pointer_position)) => {
+                    selection = Self::resize_selection(
+                        selection,
+                        handle,
+                        to_pixels(pointer_position),
+                        self.aspect_ratio.ratio(image_size),
+                        image_bounds
+                    );
+                },
+                _ => {},
+            }
+        }
+
+        if response.drag_stopped() {
+            self.handle = None;
+        }
+
+        match self.handle {
+            Some(handle) => ui.ctx().set_cursor_icon(handle.cursor_icon(true)),
+            None => if let Some(hover_position) = response.hover_pos()
+                && let Some(handle) = Self::handle_at(to_screen(selection), hover_position) {
+                ui.ctx().set_cursor_icon(handle.cursor_icon(false));
+            },
+        }
+
+        self.selection = Some(selection);
+
+        Self::paint_selection(ui, image_rect, to_screen(selection));
+
+        let (x, y, width, height) = Self::selection_to_pixels(selection);
+
+        egui::Window::new("crop_tool_window")
+            .anchor(Align2::CENTER_BOTTOM, Vec2::new(0.0, -48.0))
+            .title_bar(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    for aspect_ratio in CropAspectRatio::ALL {
+                        if ui.selectable_label(self.aspect_ratio == aspect_ratio, aspect_ratio.label()).clicked() {
+                            self.aspect_ratio = aspect_ratio;
+                            self.fit_selection_to_aspect_ratio(aspect_ratio.ratio(image_size));
+                        }
+                    }
+
+                    ui.separator();
+
+                    ui.label(format!("{} x {} px at ({}, {})", width, height, x, y));
+
+                    ui.separator();
+
+                    if ui.button("Crop").clicked() {
+                        self.confirm();
+                    }
+
+                    if is_cropped && ui.button("Uncrop").clicked() {
+                        self.confirmed_crop = Some(None);
+                        self.active = false;
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        self.active = false;
+                    }
+                });
+            });
+    }
+
+    /// The crop the user confirmed, `Some(None)` if they took the crop off.
+    pub fn take_crop(&mut self) -> Option<Option<(u32, u32, u32, u32)>> {
+        self.confirmed_crop.take()
+    }
+
+    fn confirm(&mut self) {
+        if let Some(selection) = self.selection {
+            let crop = Self::selection_to_pixels(selection);
+
+            debug!("Crop of {:?} confirmed...", crop);
+
+            self.confirmed_crop = Some(Some(crop));
+        }
+
+        self.active = false;
+    }
+
+    fn selection_to_pixels(selection: Rect) -> (u32, u32, u32, u32) {
+        (
+            selection.min.x.round() as u32,
+            selection.min.y.round() as u32,
+            selection.width().round().max(1.0) as u32,
+            selection.height().round().max(1.0) as u32,
+        )
+    }
+
+    fn handle_at(selection_on_screen: Rect, position: Pos2) -> Option<CropHandle> {
+        let handles = [
+            (CropHandle::TopLeft, selection_on_screen.left_top()),
+            (CropHandle::TopRight, selection_on_screen.right_top()),
+            (CropHandle::BottomLeft, selection_on_screen.left_bottom()),
+            (CropHandle::BottomRight, selection_on_screen.right_bottom()),
+            (CropHandle::Left, selection_on_screen.left_center()),
+            (CropHandle::Right, selection_on_screen.right_center()),
+            (CropHandle::Top, selection_on_screen.center_top()),
+            (CropHandle::Bottom, selection_on_screen.center_bottom()),
+        ];
+
+        for (handle, handle_position) in handles {
+            if handle_position.distance(position) <= HANDLE_SIZE {
+                return Some(handle);
+            }
+        }
+
+        match selection_on_screen.contains(position) {
+            true => Some(CropHandle::Move),
+            false => None,
+        }
+    }
+
+    /// Moves the edges the handle is on to the pointer, the edges across from
+    /// it stay put. With an aspect ratio the other edges follow along with it.
+    fn resize_selection(
+        selection: Rect,
+        handle: CropHandle,
+        pointer_position: Pos2,
+        ratio: Option<f32>,
+        image_bounds: Rect,
+    ) -> Rect {
+        let anchor_x = match handle.moves_left() {
+            true => selection.max.x,
+            false => selection.min.x,
+        };
+        let anchor_y = match handle.moves_top() {
+            true => selection.max.y,
+            false => selection.min.y,
+        };
+
+        let moves_horizontally = handle.moves_left() || handle.moves_right();
+        let moves_vertically = handle.moves_top() || handle.moves_bottom();
+
+        let mut width = match moves_horizontally {
+            true => (pointer_position.x - anchor_x).abs().max(1.0),
+            false => selection.width(),
+        };
+        let mut height = match moves_vertically {
+            true => (pointer_position.y - anchor_y).abs().max(1.0),
+            false => selection.height(),
+        };
+
+        if let Some(ratio) = ratio {
+            match (moves_horizontally, moves_vertically) {
+                (false, true) => width = height * ratio,
+                // corners follow the width.
+                _ => height = width / ratio,
+            }
+        }
+
+        let max_width = match handle.moves_left() {
+            true => anchor_x - image_bounds.min.x,
+            false => image_bounds.max.x - anchor_x,
+        };
+        let max_height = match handle.moves_top() {
+            true => anchor_y - image_bounds.min.y,
+            false => image_bounds.max.y - anchor_y,
+        };
+
+        if width > max_width {
+            width = max_width;
+            height = ratio.map_or(height, |ratio| width / ratio);
+        }
+
+        if height > max_height {
+            height = max_height;
+            width = ratio.map_or(width, |ratio| height * ratio);
+        }
+
+        let min_x = match handle.moves_left() {
+            true => anchor_x - width,
+            false => anchor_x,
+        };
+        let min_y = match handle.moves_top() {
+            true => anchor_y - height,
+            false => anchor_y,
+        };
+
+        Rect::from_min_size(Pos2::new(min_x, min_y), Vec2::new(width, height))
+    }
+
+    /// Shrinks the selection around it's center to the aspect ratio.
+    fn fit_selection_to_aspect_ratio(&mut self, ratio: Option<f32>) {
+        if let (Some(selection), Some(ratio)) = (self.selection, ratio) {
+            let (mut width, mut height) = (selection.width(), selection.height());
+
+            match width / height > ratio {
+                true => width = height * ratio,
+                false => height = width / ratio,
+            }
+
+            self.selection = Some(Rect::from_center_size(selection.center(), Vec2::new(width, height)));
+        }
+    }
+
+    fn paint_selection(ui: &Ui, image_rect: Rect, selection_on_screen: Rect) {
+        let painter = ui.painter();
+        let dim_colour = Color32::from_black_alpha(150);
+
+        // everything outside of the selection is dimmed.
+        for dimmed_rect in [
+            Rect::from_min_max(image_rect.min, Pos2::new(image_rect.max.x, selection_on_screen.min.y)),
+            Rect::from_min_max(Pos2::new(image_rect.min.x, selection_on_screen.max.y), image_rect.max),
+            Rect::from_min_max(
+                Pos2::new(image_rect.min.x, selection_on_screen.min.y),
+                Pos2::new(selection_on_screen.min.x, selection_on_screen.max.y)
+            ),
+            Rect::from_min_max(
+                Pos2::new(selection_on_screen.max.x, selection_on_screen.min.y),
+                Pos2::new(image_rect.max.x, selection_on_screen.max.y)
+            ),
+        ] {
+            painter.rect_filled(dimmed_rect, CornerRadius::ZERO, dim_colour);
+        }
+
+        painter.rect_stroke(
+            selection_on_screen,
+            CornerRadius::ZERO,
+            Stroke::new(1.5, Color32::WHITE),
+            StrokeKind::Outside
+        );
+
+        for handle_position in [
+            selection_on_screen.left_top(),
+            selection_on_screen.right_top(),
+            selection_on_screen.left_bottom(),
+            selection_on_screen.right_bottom(),
+            selection_on_screen.left_center(),
+            selection_on_screen.right_center(),
+            selection_on_screen.center_top(),
+            selection_on_screen.center_bottom(),
+        ] {
+            painter.rect_filled(
+                Rect::from_center_size(handle_position, Vec2::splat(HANDLE_SIZE)),
+                CornerRadius::same(2),
+                Color32::WHITE
+            );
+        }
+    }
+}
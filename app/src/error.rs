@@ -38,10 +38,6 @@ pub enum Error {
     the file until we can save our monitor size state to it.")]
     CachedMonitorSizeAlreadyLocked { error: String },
 
-    #[display("Experimental SVG support has been temporary removed from \
-    Roseate! We're still working on SVG support, it will be back when it's ready.")]
-    SvgNotSupportedYet,
-
     #[display("There is no backend available that supports the \
     '{image_format}' image format in this version of Roseate!")]
     BackendForImageFormatNotAvailable { image_format: ImageFormat },
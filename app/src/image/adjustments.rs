@@ -0,0 +1,66 @@
+use roseate_core::modifications::ImageModification;
+
+/// The colour adjustments the user has dialed into the adjustments window, 
+/// neutral by default. They're never made to the image file itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColourAdjustments {
+    /// In stops, so 1.0 doubles the light in the image.
+    pub exposure: f32,
+    pub contrast: f32,
+    pub gamma: f32,
+    pub saturation: f32,
+    /// Negative is cooler (bluer) and positive is warmer (more orange).
+    pub temperature: f32,
+    /// Negative is greener and positive is more magenta.
+    pub tint: f32,
+}
+
+impl Default for ColourAdjustments {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            temperature: 0.0,
+            tint: 0.0,
+        }
+    }
+}
+
+impl ColourAdjustments {
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The modifications to make to the image for these adjustments, 
+    /// adjustments left at neutral are left out as they'd do nothing.
+    pub fn to_modifications(&self) -> Vec<ImageModification> {
+        let neutral = Self::default();
+        let mut modifications = Vec::new();
+
+        if self.temperature != neutral.temperature || self.tint != neutral.tint {
+            modifications.push(
+                ImageModification::WhiteBalance { temperature: self.temperature, tint: self.tint }
+            );
+        }
+
+        if self.exposure != neutral.exposure {
+            modifications.push(ImageModification::Exposure(self.exposure));
+        }
+
+        if self.contrast != neutral.contrast {
+            modifications.push(ImageModification::Contrast(self.contrast));
+        }
+
+        if self.gamma != neutral.gamma {
+            modifications.push(ImageModification::Gamma(self.gamma));
+        }
+
+        if self.saturation != neutral.saturation {
+            modifications.push(ImageModification::Saturation(self.saturation));
+        }
+
+        modifications
+    }
+}
@@ -2,8 +2,8 @@ use std::{fmt::Display, time::{Duration}};
 
 use cirrus_egui::notifier::Notifier;
 use egui_notify::ToastLevel;
-use log::{debug};
-use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend}, format::ImageFormat, reader::ImageReader};
+use log::{debug, warn};
+use roseate_core::{animation::AnimationStreaming, backends::{backend::{DecodeBackend, MultiPageDecodeBackend}, image_rs::ImageRSBackend, psd::PsdBackend, rawloader::RawloaderBackend, resvg::ResvgBackend, zune_image::ZuneImageBackend}, decoded_image::{DecodedImage, ImageSize, PageInfo}, error::{Error as CoreError, Result as CoreResult}, format::ImageFormat, modifications::ImageModification, reader::ImageReader};
 
 use crate::error::{Error, Result};
 
@@ -13,10 +13,99 @@ pub enum DefaultDecodingBackend {
     /// modifications. This is by far the most stable backend.
     ImageRS,
     /// Uses the zune-image rust crate for image decoding and modifications, 
-    /// it's fast but it's implementation in roseate is currently experimental.
+    /// it's fast and light on memory but supports less image formats than image-rs.
     ZuneImage,
 }
 
+/// A decoder backend that has been initialized by [`DefaultDecodingBackend`].
+pub enum DecodingBackend {
+    ImageRS(ImageRSBackend),
+    ZuneImage(ZuneImageBackend),
+    Resvg(ResvgBackend),
+    Rawloader(RawloaderBackend),
+    Psd(PsdBackend),
+}
+
+impl DecodingBackend {
+    pub fn modify<I>(&mut self, modifications: I)
+    where
+        I: IntoIterator<Item = ImageModification>,
+    {
+        match self {
+            Self::ImageRS(backend) => backend.modify(modifications),
+            Self::ZuneImage(backend) => backend.modify(modifications),
+            Self::Resvg(backend) => backend.modify(modifications),
+            Self::Rawloader(backend) => backend.modify(modifications),
+            Self::Psd(backend) => backend.modify(modifications),
+        }
+    }
+
+    /// Camera raws can be displayed from the jpeg preview embedded into them 
+    /// instead of being developed, the other backends have nothing like that.
+    pub fn use_embedded_preview(&mut self, use_embedded_preview: bool) {
+        if let Self::Rawloader(backend) = self {
+            backend.use_embedded_preview(use_embedded_preview);
+        }
+    }
+
+    /// Only image-rs can stream animations that are too big to hold in memory, 
+    /// the other backends always decode every frame up front.
+    pub fn stream_animations(&mut self, animation_streaming: Option<AnimationStreaming>) {
+        if let Self::ImageRS(backend) = self {
+            backend.stream_animations(animation_streaming);
+        }
+    }
+
+    /// Only image-rs decodes multi-page images (tiffs and icos) so 
+    /// every other backend has no pages to speak of.
+    pub fn pages(&self) -> Vec<PageInfo> {
+        match self {
+            Self::ImageRS(backend) => backend.pages(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn select_page(&mut self, index: usize) -> CoreResult<()> {
+        match self {
+            Self::ImageRS(backend) => backend.select_page(index),
+            _ => Ok(()),
+        }
+    }
+
+    /// Backends that can't decode progressively (everything but image-rs with pngs 
+    /// and progressive jpegs for now) just decode as normal without handing out any snapshots.
+    pub fn decode_progressively<F>(self, on_snapshot: F) -> CoreResult<DecodedImage>
+    where
+        F: FnMut(DecodedImage),
+    {
+        match self {
+            Self::ImageRS(backend) => backend.decode_progressively(on_snapshot),
+            Self::ZuneImage(backend) => backend.decode_progressively(on_snapshot),
+            Self::Resvg(backend) => backend.decode_progressively(on_snapshot),
+            Self::Rawloader(backend) => backend.decode_progressively(on_snapshot),
+            Self::Psd(backend) => backend.decode_progressively(on_snapshot),
+        }
+    }
+
+    /// Decodes progressively if we're given somewhere to hand the snapshots to.
+    pub fn decode_with(self, on_snapshot: Option<&mut dyn FnMut(DecodedImage)>) -> CoreResult<DecodedImage> {
+        match on_snapshot {
+            Some(on_snapshot) => self.decode_progressively(on_snapshot),
+            None => self.decode(),
+        }
+    }
+
+    pub fn decode(self) -> CoreResult<DecodedImage> {
+        match self {
+            Self::ImageRS(backend) => backend.decode(),
+            Self::ZuneImage(backend) => backend.decode(),
+            Self::Resvg(backend) => backend.decode(),
+            Self::Rawloader(backend) => backend.decode(),
+            Self::Psd(backend) => backend.decode(),
+        }
+    }
+}
+
 impl Display for DefaultDecodingBackend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -29,12 +118,32 @@ impl Display for DefaultDecodingBackend {
 impl DefaultDecodingBackend {
     const PRIORITIZED_BACKENDS: [Self; 2] = [Self::ImageRS, Self::ZuneImage];
 
-    pub fn init_default_backend_or_fallback_if_not_supported(
+    /// Initializes the default backend (falling back to the next backend if it doesn't support the 
+    /// image format), has `prepare_backend` set it up and then decodes the image. If a backend fails 
+    /// to initialize or decode the image we retry with the next backend that supports the format.
+    pub fn init_and_decode_with_fallback<F>(
         &self,
         image_reader: ImageReader,
         notifier: &mut Notifier,
         fallback_on_unsupported_image_format: bool,
-    ) -> Result<impl DecodeBackend + use<>> {
+        mut prepare_backend: F,
+        mut on_snapshot: Option<&mut dyn FnMut(DecodedImage)>,
+    ) -> Result<DecodedImage>
+    where
+        F: FnMut(&mut DecodingBackend) -> Result<()>,
+    {
+        // vector images, camera raws and photoshop documents only have the 
+        // one backend that can decode them so there's nothing to fallback to.
+        if Self::is_image_format_backend_specific(&image_reader.image_format) {
+            let mut backend = Self::init_format_specific_backend(image_reader)?;
+            prepare_backend(&mut backend)?;
+
+            notifier.set_loading(Some("Decoding image..."));
+            return Ok(backend.decode_with(on_snapshot)?);
+        }
+
+        let image_format = image_reader.image_format.clone();
+
         let mut prioritized_backends = vec![self.clone()];
 
         if fallback_on_unsupported_image_format {
@@ -45,10 +154,12 @@ impl DefaultDecodingBackend {
             }
         }
 
+        let mut supported_backends = Vec::new();
+
         for backend in prioritized_backends {
-            if backend.is_image_format_supported(&image_reader.image_format) {
-                // TODO: if decoder fails to init, also fallback to the next decoder
-                return backend.init_backend_decoder(image_reader);
+            if backend.is_image_format_supported(&image_format) {
+                supported_backends.push(backend);
+                continue;
             }
 
             notifier.toast(
@@ -63,6 +174,104 @@ impl DefaultDecodingBackend {
             );
         }
 
+        let mut image_reader = Some(image_reader);
+        let backend_count = supported_backends.len();
+
+        for (index, backend) in supported_backends.into_iter().enumerate() {
+            let current_image_reader = match image_reader.take() {
+                Some(image_reader) => image_reader,
+                None => break,
+            };
+
+            // we hold onto a copy of the encoded image in case this backend fails and the 
+            // next one needs it, there's no point in doing that for the last backend though.
+            if index + 1 < backend_count {
+                image_reader = current_image_reader.try_clone();
+            }
+
+            let result = backend.clone().init_backend_decoder(current_image_reader)
+                .and_then(|mut decoding_backend| {
+                    prepare_backend(&mut decoding_backend)?;
+
+                    // reborrowed for each backend we try as the snapshots could be handed to more than one.
+                    let on_snapshot = on_snapshot.as_mut()
+                        .map(|on_snapshot| &mut **on_snapshot as &mut dyn FnMut(DecodedImage));
+
+                    notifier.set_loading(Some("Decoding image..."));
+                    Ok(decoding_backend.decode_with(on_snapshot)?)
+                });
+
+            match result {
+                Ok(decoded_image) => {
+                    if index > 0 {
+                        notifier.toast(
+                            format!("The image was decoded with the '{backend}' decoder backend."),
+                            ToastLevel::Success,
+                            |toast| {
+                                toast.duration(Duration::from_secs(3));
+                            }
+                        );
+                    }
+
+                    return Ok(decoded_image);
+                },
+                Err(
+                    Error::Core(
+                        error @ (CoreError::DecoderInitFailure { .. } | CoreError::DecodingFailure { .. })
+                    )
+                ) if image_reader.is_some() => {
+                    warn!("The '{backend}' backend failed to decode the image, falling back to the next backend... Error: {error}");
+
+                    notifier.toast(
+                        format!(
+                            "The decoder backend '{backend}' failed to \
+                            decode this image, falling back to another backend..."
+                        ),
+                        ToastLevel::Warning,
+                        |toast| {
+                            toast.duration(Duration::from_secs(2));
+                        }
+                    );
+                },
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(
+            Error::BackendForImageFormatNotAvailable {
+                image_format
+            }
+        )
+    }
+
+    fn is_image_format_backend_specific(image_format: &ImageFormat) -> bool {
+        ResvgBackend::SUPPORTED_FORMATS.contains(image_format)
+            || RawloaderBackend::SUPPORTED_FORMATS.contains(image_format)
+            || PsdBackend::SUPPORTED_FORMATS.contains(image_format)
+    }
+
+    fn init_format_specific_backend(image_reader: ImageReader) -> Result<DecodingBackend> {
+        // vector images are never decoded by the raster backends.
+        if ResvgBackend::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            debug!("Initializing 'resvg' backend for vector image...");
+
+            return Ok(DecodingBackend::Resvg(ResvgBackend::from_reader(image_reader)?));
+        }
+
+        // same goes for camera raws, only rawloader can develop them.
+        if RawloaderBackend::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            debug!("Initializing 'rawloader' backend for camera raw image...");
+
+            return Ok(DecodingBackend::Rawloader(RawloaderBackend::from_reader(image_reader)?));
+        }
+
+        // and photoshop documents.
+        if PsdBackend::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            debug!("Initializing 'psd' backend for photoshop document...");
+
+            return Ok(DecodingBackend::Psd(PsdBackend::from_reader(image_reader)?));
+        }
+
         Err(
             Error::BackendForImageFormatNotAvailable {
                 image_format: image_reader.image_format
@@ -70,21 +279,30 @@ impl DefaultDecodingBackend {
         )
     }
 
-    fn init_backend_decoder(self, image_reader: ImageReader) -> Result<impl DecodeBackend> {
+    fn init_backend_decoder(self, image_reader: ImageReader) -> Result<DecodingBackend> {
         debug!("Initializing '{self}' backend decoder...");
 
         let decoder = match self {
-            Self::ImageRS => ImageRSBackend::from_reader(image_reader)?,
-            Self::ZuneImage => todo!("The ZuneImage backend is not yet implemented in roseate-core!"),
+            Self::ImageRS => DecodingBackend::ImageRS(ImageRSBackend::from_reader(image_reader)?),
+            Self::ZuneImage => DecodingBackend::ZuneImage(ZuneImageBackend::from_reader(image_reader)?),
         };
 
         Ok(decoder)
     }
 
+    /// The scale (as a denominator) this backend would decode the image at to get it down to 
+    /// `target_size`, `1` if it can only ever decode it at full size (zune-image can't scale at all).
+    pub fn decode_scale(&self, image_format: &ImageFormat, image_size: ImageSize, target_size: ImageSize) -> u32 {
+        match self {
+            Self::ImageRS => ImageRSBackend::decode_scale(image_format, image_size, target_size),
+            Self::ZuneImage => 1,
+        }
+    }
+
     fn is_image_format_supported(&self, image_format: &ImageFormat) -> bool {
         match self {
             Self::ImageRS => ImageRSBackend::SUPPORTED_FORMATS.contains(&image_format),
-            Self::ZuneImage => false,
+            Self::ZuneImage => ZuneImageBackend::SUPPORTED_FORMATS.contains(&image_format),
         }
     }
 }
\ No newline at end of file
@@ -1,28 +1,56 @@
-use std::{collections::HashSet, fmt::Debug, fs::File, hash::Hash, io::{Cursor, Read}, path::PathBuf, sync::{Arc, Mutex}, time::Instant};
+use std::{fmt::Debug, fs::File, hash::Hash, path::PathBuf, sync::{Arc, Mutex}, time::{Duration, Instant}};
 
 use log::{debug, info};
+use egui_notify::ToastLevel;
 use cirrus_egui::notifier::Notifier;
-use roseate_core::{backends::backend::DecodeBackend, decoded_image::{DecodedImage, ImageSize}, format::{ImageFormat, determine_image_format_and_size_from_header}, modifications::{ImageModification, ImageModifications}, reader::{EncodedImageReader, ImageReader, ImageReaderData}};
+use roseate_core::{animation::AnimationStreaming, decoded_image::{DecodedImage, DecodedImageContent, ImageSize, PageInfo}, format::{ImageFormat, determine_image_format_and_size, determine_svg_size}, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, orientation::{ImageOrientation, determine_image_orientation}, reader::{EncodedImageBytes, EncodedImageReader, ImageReader, ImageReaderData}, thumbnail::decode_embedded_thumbnail};
 
-use crate::{error::{Error, Result}, image::backend::DefaultDecodingBackend};
+use crate::{error::{Error, Result}, image::{adjustments::ColourAdjustments, backend::DefaultDecodingBackend}};
 
 #[derive(Clone)]
 pub struct Image {
     pub path: Arc<PathBuf>,
+    /// The size of the image once it's oriented and edited.
     pub size: ImageSize,
     pub format: ImageFormat,
     pub decoded: Arc<Mutex<Option<DecodedImage>>>,
-
-    encoded_image_reader: Arc<Mutex<Option<EncodedImageReader>>>,
-
-    last_modifications: ImageModifications,
+    /// The page of a multi-page image (tiffs and icos) that we are showing.
+    pub page: usize,
+    /// Empty until the image is loaded, and stays empty if the image only has the one page.
+    pub pages: Arc<Mutex<Vec<PageInfo>>>,
+    /// How the image gets rotated and flipped the right way up, always 
+    /// normal if the user doesn't want images oriented automatically.
+    pub orientation: ImageOrientation,
+    /// The flips, rotations and crops the user has asked for in the order they asked 
+    /// for them, made to the image once it's oriented the right way up.
+    pub edits: ModificationPipeline,
+    /// The exposure, contrast, gamma, saturation and white balance the user has dialed in.
+    pub adjustments: ColourAdjustments,
+
+    /// The decoded image before it's colour adjustments, kept while the image is adjusted so 
+    /// moving the sliders around only has to adjust the image again rather than decode it again.
+    unadjusted: Arc<Mutex<Option<DecodedImage>>>,
+    /// The image's file, memory-mapped so it costs us nothing on the heap and 
+    /// can be handed to the decoders again every time we load from disk.
+    encoded_image_bytes: EncodedImageBytes,
+    /// The size of the image (or page) the way it's stored, before anything is done to it.
+    stored_size: ImageSize,
+    /// The format the file extension claimed the image was, if it's contents disagreed.
+    extension_mismatch: Option<ImageFormat>,
+    /// Whether the image is an animation too big to hold in memory that's being streamed instead, 
+    /// there's no decoded image in memory to go back to so it's always loaded from disk again.
+    streamed: Arc<Mutex<bool>>,
+
+    last_modifications: ModificationPipeline,
 }
 
 impl Hash for Image {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.path.hash(state);
-        self.size.hash(state);
+        // rotating or cropping the image doesn't make it a different image.
+        self.stored_size.hash(state);
         self.format.hash(state);
+        self.page.hash(state);
     }
 }
 
@@ -33,75 +61,96 @@ impl Debug for Image {
             .field("path", &self.path)
             .field("size", &self.size)
             .field("format", &self.format)
+            .field("page", &self.page)
+            .field("edits", &self.edits)
             .field("last_modifications", &self.last_modifications)
             .finish()
     }
 }
 
 impl Image {
-    pub fn new(path: PathBuf) -> Result<Self> {
+    pub fn new(path: PathBuf, auto_orientation: bool) -> Result<Self> {
         if !path.exists() {
             return Err(
                 Error::FileNotFound { path: path.to_string_lossy().to_string() }
             );
         }
 
-        if path.extension().unwrap_or_default() == "svg" {
-            // NOTE: this is experimental stuff, not final.
-            // let size = determine_svg_size(&path);
-
-            // return Ok(
-            //     Self {
-            //         path,
-            //         size,
-            //         format: ImageFormat::Svg,
-            //         decoded: None
-            //     }
-            // );
-
-            // We can't support SVG at all now as we no longer work with 
-            // egui's image loader. https://github.com/cloudy-org/roseate/issues/89
-            // 
-            // I'm planning to create our own SVG renderer in the viewport by 
-            // reading into egui's implementation of it in their loader.
-            return Err(Error::SvgNotSupportedYet);
-        }
-
-        let mut image_buffer = Vec::new();
-
-        debug!("Reading image file into buffer...");
+        debug!("Memory-mapping image file...");
 
         let now = Instant::now();
 
-        File::open(&path)
+        let file = File::open(&path)
             .map_err(|error| Error::ImageFileOpenFailure {
                 error: error.to_string(),
-            })?
-            .read_to_end(&mut image_buffer)
+            })?;
+
+        let encoded_image_bytes = EncodedImageBytes::from_file(file)
             .map_err(|error| Error::ImageFileReadFailure {
                 error: error.to_string(),
             })?;
 
-        let mut image_reader = EncodedImageReader::new(image_buffer);
+        let mut image_reader = EncodedImageReader::new(encoded_image_bytes.clone());
+
+        info!("Image file mapped in '{}' seconds.", now.elapsed().as_secs_f32());
+
+        let extension = path.extension().and_then(|extension| extension.to_str());
+
+        let (format, size, extension_mismatch) = match extension == Some("svg") {
+            true => (ImageFormat::Svg, determine_svg_size(&path)?, None),
+            false => {
+                let detected_image_format = determine_image_format_and_size(&mut image_reader, extension)?;
+
+                (
+                    detected_image_format.image_format,
+                    detected_image_format.image_size,
+                    detected_image_format.extension_mismatch
+                )
+            },
+        };
 
-        info!("Image file read in '{}' seconds.", now.elapsed().as_secs_f32());
+        let orientation = match auto_orientation {
+            true => determine_image_orientation(&encoded_image_bytes),
+            false => ImageOrientation::Normal,
+        };
 
-        let (format, size) = determine_image_format_and_size_from_header(&mut image_reader)?;
+        if orientation != ImageOrientation::Normal {
+            debug!("Image will be oriented ({:?})...", orientation);
+        }
 
         Ok(
             Self {
                 path: Arc::new(path),
-                size,
+                size: orientation.orient_size(size),
                 format,
                 decoded: Arc::new(Mutex::new(None)),
-
-                encoded_image_reader: Arc::new(Mutex::new(Some(image_reader))),
-                last_modifications: HashSet::default(),
+                page: 0,
+                pages: Arc::new(Mutex::new(Vec::new())),
+                orientation,
+                edits: ModificationPipeline::new(),
+                adjustments: ColourAdjustments::default(),
+
+                unadjusted: Arc::new(Mutex::new(None)),
+                encoded_image_bytes,
+                stored_size: size,
+                extension_mismatch,
+                streamed: Arc::new(Mutex::new(false)),
+                last_modifications: ModificationPipeline::new(),
             }
         )
     }
 
-    pub fn load(&mut self, modifications: ImageModifications, backend: &DefaultDecodingBackend, reload: bool, notifier: &mut Notifier) -> Result<()> {
+    pub fn load(
+        &mut self,
+        modifications: ModificationPipeline,
+        backend: &DefaultDecodingBackend,
+        reload: bool,
+        use_embedded_preview: bool,
+        animation_streaming: Option<AnimationStreaming>,
+        decode_limits: DecodeLimits,
+        on_snapshot: Option<&mut dyn FnMut(DecodedImage)>,
+        notifier: &mut Notifier
+    ) -> Result<()> {
         notifier.set_loading(
             Some(
                 format!(
@@ -111,6 +160,23 @@ impl Image {
             )
         );
 
+        // we only want to warn about this the first time the image is loaded.
+        if let Some(extension_image_format) = self.extension_mismatch.take() {
+            notifier.toast(
+                format!(
+                    "This image's file extension says it's a {} but it's actually a {}, \
+                    it'll be loaded as a {}.",
+                    extension_image_format,
+                    self.format,
+                    self.format
+                ),
+                ToastLevel::Warning,
+                |toast| {
+                    toast.duration(Duration::from_secs(5));
+                }
+            );
+        }
+
         if reload && self.are_mods_the_same(&modifications) {
             debug!(
                 "Image modifications were the same, rejecting reload..."
@@ -119,28 +185,92 @@ impl Image {
             return Ok(());
         }
 
+        let streamed = *self.streamed.lock().unwrap();
+
+        // svgs get their colours adjusted as they're rasterized (and streamed animations as each frame 
+        // is decoded), everything else is adjusted once it's decoded so we can hold onto the 
+        // image without it's adjustments and just adjust that again next time.
+        let adjustments = match self.format == ImageFormat::Svg || streamed {
+            true => Vec::new(),
+            false => modifications.colour_adjustments(),
+        };
+
+        let mut decode_modifications = modifications.clone();
+
+        if !adjustments.is_empty() {
+            decode_modifications.retain(|modification| !modification.is_colour_adjustment());
+        }
+
+        // svgs are always rasterized again from the source, never from what's in memory.
         let load_fresh_from_disk = match reload {
-            true => self.are_mods_out_of_mem_bounds(&modifications),
+            true => self.format == ImageFormat::Svg || streamed || self.are_mods_out_of_mem_bounds(&decode_modifications),
             false => true,
         };
 
         // if we have already loaded this image and we can use the image in memory, the image reader will contain decoded image.
-        let image_reader_data = self.get_image_reader_data(load_fresh_from_disk, notifier)?;
-        let image_reader = ImageReader::new(image_reader_data, self.format.clone());
+        let image_reader_data = self.get_image_reader_data(load_fresh_from_disk);
+
+        // the image in memory was already edited the last time it 
+        // was loaded so the decoder only needs whatever has changed since.
+        let backend_modifications = match &image_reader_data {
+            ImageReaderData::DecodedImage(_) => self.get_mods_relative_to_mem(&decode_modifications),
+            ImageReaderData::EncodedImage(_) => decode_modifications,
+        };
+
+        // we only let the backends orient the image if we did too, otherwise the 
+        // image could be decoded a different size to what we've been working with.
+        let image_reader = ImageReader::new(image_reader_data, self.format.clone())
+            .with_limits(decode_limits)
+            .with_auto_orientation(self.orientation != ImageOrientation::Normal);
 
         notifier.set_loading(Some("Initializing decoder to use for decoding..."));
-        let mut backend = backend.init_default_backend_or_fallback_if_not_supported(
+        self.last_modifications = modifications.clone();
+
+        let page = self.page;
+        let pages = self.pages.clone();
+
+        // partially decoded snapshots of the image get handed 
+        // to "on_snapshot" as they come in if we're given it.
+        let decoded_image = backend.init_and_decode_with_fallback(
             image_reader,
             notifier,
-            true
+            true,
+            |backend| {
+                backend.use_embedded_preview(use_embedded_preview);
+                backend.stream_animations(animation_streaming);
+
+                // multi-page images get decoded a page at a time.
+                let backend_pages = backend.pages();
+
+                if !backend_pages.is_empty() {
+                    backend.select_page(page)?;
+                    *pages.lock().unwrap() = backend_pages;
+                }
+
+                backend.modify(backend_modifications.clone());
+
+                Ok(())
+            },
+            on_snapshot
         )?;
 
-        notifier.set_loading(Some("Passing image modifications to decoder..."));
-        self.last_modifications = modifications.clone();
-        backend.modify(modifications);
+        let streamed = matches!(decoded_image.content, DecodedImageContent::Streamed(_));
+        *self.streamed.lock().unwrap() = streamed;
 
-        notifier.set_loading(Some("Decoding image..."));
-        *self.decoded.lock().unwrap() = Some(backend.decode()?);
+        // there's no frames in memory to adjust if the animation turned out too big to hold, 
+        // they'll be handed to the backend with everything else the next time it's loaded.
+        let decoded_image = match adjustments.is_empty() || streamed {
+            true => {
+                *self.unadjusted.lock().unwrap() = None;
+                decoded_image
+            },
+            false => {
+                *self.unadjusted.lock().unwrap() = Some(decoded_image.clone());
+                self.adjust_decoded_image(decoded_image, adjustments, backend, decode_limits, notifier)?
+            },
+        };
+
+        *self.decoded.lock().unwrap() = Some(decoded_image);
 
         debug!("Done decoding image!");
 
@@ -149,91 +279,185 @@ impl Image {
         Ok(())
     }
 
+    /// Runs the image's colour adjustments over the already decoded image, the backends 
+    /// take a decoded image like any other so this is just another (very quick) decode.
+    fn adjust_decoded_image(
+        &self,
+        decoded_image: DecodedImage,
+        adjustments: Vec<ImageModification>,
+        backend: &DefaultDecodingBackend,
+        decode_limits: DecodeLimits,
+        notifier: &mut Notifier
+    ) -> Result<DecodedImage> {
+        debug!("Adjusting the colours of the decoded image ({:?})...", adjustments);
+        notifier.set_loading(Some("Adjusting image colours..."));
+
+        let image_reader = ImageReader::new(decoded_image, self.format.clone())
+            .with_limits(decode_limits);
+
+        backend.init_and_decode_with_fallback(
+            image_reader,
+            notifier,
+            true,
+            |backend| {
+                backend.modify(adjustments.clone());
+
+                Ok(())
+            },
+            None
+        )
+    }
+
+    /// Decodes the thumbnail embedded into the image (camera jpegs and raws usually have one) 
+    /// without decoding the image itself, it's only good for a placeholder as it's tiny.
+    pub fn decode_embedded_thumbnail(&self) -> Option<DecodedImage> {
+        // the thumbnail is only ever of the first page.
+        if self.page != 0 {
+            return None;
+        }
+
+        decode_embedded_thumbnail(&self.encoded_image_bytes, &self.format)
+            .map(|mut thumbnail| {
+                thumbnail.orient(self.orientation);
+                thumbnail
+            })
+    }
+
+    /// Switches to another page of a multi-page image. The page is decoded 
+    /// on it's own so the image has to be loaded again after this.
+    pub fn select_page(&mut self, page: usize) {
+        if let Some(page_info) = self.pages.lock().unwrap().get(page) {
+            self.page = page;
+            self.stored_size = page_info.size;
+            // pages can be all different sizes so a crop of one is no good for another.
+            self.edits.retain(|modification| !matches!(modification, ImageModification::Crop { .. }));
+            self.update_size();
+
+            *self.decoded.lock().unwrap() = None;
+            *self.unadjusted.lock().unwrap() = None;
+        }
+    }
+
+    /// Flips or rotates the image (on top of whatever it's been edited by 
+    /// already), the image has to be loaded again after this for it to show.
+    pub fn apply_transform(&mut self, transform: ImageOrientation) {
+        self.edits.extend(transform.to_modifications());
+        self.update_size();
+    }
+
+    /// Crops the image down to the `(x, y, width, height)` rectangle of the image as it's 
+    /// shown right now (so crops can be cropped further), `None` takes every crop back off. 
+    /// The image has to be loaded again after this for it to show.
+    pub fn apply_crop(&mut self, rect: Option<(u32, u32, u32, u32)>) {
+        match rect {
+            Some((x, y, width, height)) => self.edits.push(ImageModification::Crop { x, y, width, height }),
+            None => self.edits.retain(|modification| !matches!(modification, ImageModification::Crop { .. })),
+        }
+
+        self.update_size();
+    }
+
+    pub fn is_cropped(&self) -> bool {
+        self.edits.iter().any(|modification| matches!(modification, ImageModification::Crop { .. }))
+    }
+
+    fn update_size(&mut self) {
+        self.size = self.edits.output_size(self.orientation.orient_size(self.stored_size));
+    }
+
     /// Returns already decoded image from memory if it exists and if a fresh 
     /// image from disk is not required. Otherwise, in the case `fresh_from_disk` 
-    /// is true or decoded image doesn't exist, a buf reader to the fresh image 
-    /// on the disk is returned for us to decode later.
-    fn get_image_reader_data(&mut self, fresh_from_disk: bool, notifier: &mut Notifier) -> Result<ImageReaderData> {
+    /// is true or decoded image doesn't exist, a reader over the memory-mapped 
+    /// image file is returned for us to decode later. The image from before 
+    /// it's colour adjustments is always preferred as they get made again anyway.
+    fn get_image_reader_data(&mut self, fresh_from_disk: bool) -> ImageReaderData {
+        let unadjusted = self.unadjusted.lock().unwrap().take();
+
         if !fresh_from_disk {
+            if let Some(unadjusted) = unadjusted {
+                *self.decoded.lock().unwrap() = None;
+                return ImageReaderData::from(unadjusted);
+            }
+
             if let Some(decoded_image) = self.decoded.lock().unwrap().take() {
-                return Ok(ImageReaderData::DecodedImage(decoded_image));
+                return ImageReaderData::from(decoded_image);
             }
 
             debug!("Decoded image is not currently loaded in memory, falling back to loading from disk...");
         }
 
-        let cursor = self.get_encoded_image_reader(notifier)?;
-        notifier.unset_loading();
-
-        Ok(ImageReaderData::EncodedImage(cursor))
+        ImageReaderData::from(self.encoded_image_bytes.clone())
     }
 
-    fn get_encoded_image_reader(&mut self, notifier: &mut Notifier) -> Result<EncodedImageReader> {
-        match self.encoded_image_reader.lock().unwrap().take() {
-            Some(cursor) => Ok(cursor),
+    fn are_mods_out_of_mem_bounds(&mut self, modifications: &ModificationPipeline) -> bool {
+        // what's in memory has already been cropped, we don't have what's outside of the crop.
+        let transform = match self.get_transform_from_mem(modifications) {
+            Some(transform) => transform,
             None => {
-                notifier.set_loading(Some("Opening image's file for reading..."));
-
-                let mut image_buffer = Vec::new();
+                debug!("Image edits other than flips and rotations have changed, modifications are out of memory bounds...");
+                return true;
+            },
+        };
 
-                File::open(&*self.path)
-                    .map_err(
-                        |error| Error::ImageFileOpenFailure { error: error.to_string() }
-                    )?
-                    .read_to_end(&mut image_buffer)
-                    .map_err(|error| Error::ImageFileReadFailure {
-                        error: error.to_string(),
-                    })?;
+        let is_out_of_bounds = match (modifications.last_resize(), self.last_modifications.last_resize()) {
+            (Some((new_width, new_height)), Some(last_resize)) => {
+                // the last resize was of the image before it's flipped and rotated again.
+                let (width, height) = transform.orient_size(last_resize);
 
-                Ok(Cursor::new(image_buffer))
+                // If this statement evaluates to true this 
+                // means we are being asked to resize the image upwards 
+                // to data we do not have in memory, hence we cannot use what's 
+                // in memory (these modifications are outside the memory bounds).
+                new_width > width || new_height > height
             },
-        }
-    }
-
-    fn are_mods_out_of_mem_bounds(&mut self, modifications: &ImageModifications) -> bool {
-        let require_resize = modifications.iter().find_map(|modification| {
-            #[allow(irrefutable_let_patterns)]
-            if let ImageModification::Resize(width, height) = modification {
-                Some((*width, *height))
-            } else {
-                None
-            }
-        });
-
-        let is_out_of_bounds = self.last_modifications.iter().any(|last_modification| {
-            match last_modification {
-                ImageModification::Resize(width, height) => {
-                    match require_resize {
-                        Some((new_width, new_height)) => {
-                            // If this statement evaluates to true this 
-                            // means we are being asked to resize the image upwards 
-                            // to data we do not have in memory, hence we cannot use what's 
-                            // in memory (these modifications are outside the memory bounds).
-                            new_width > *width || new_height > *height
-                        }
-                        None => false,
-                    }
-                }
-            }
-        });
+            _ => false,
+        };
 
         debug!("Are any modifications out of memory bounds: {}", is_out_of_bounds);
 
         is_out_of_bounds
     }
 
-    /// Check if modifications in both hash sets are the same.
-    fn are_mods_the_same(&self, modifications: &ImageModifications) -> bool {
-        if modifications.len() != self.last_modifications.len() {
-            return false;
-        }
+    /// The flip and rotation that gets the image in memory to `modifications`, `None` if anything else 
+    /// about the edits has changed as the image in memory can only be flipped, rotated and resized further.
+    fn get_transform_from_mem(&self, modifications: &ModificationPipeline) -> Option<ImageOrientation> {
+        let mut edits = modifications.clone();
+        let mut last_edits = self.last_modifications.clone();
 
-        for modification in modifications {
-            if !self.last_modifications.iter().any(|last_mod| modification == last_mod) {
-                return false;
-            }
+        // the image in memory we go from is always the one without it's colour adjustments.
+        edits.retain(|modification| !modification.is_colour_adjustment());
+        last_edits.retain(|modification| !modification.is_colour_adjustment());
+
+        edits.pop_resize();
+        last_edits.pop_resize();
+
+        let transform = edits.pop_orientation();
+        let last_transform = last_edits.pop_orientation();
+
+        match edits == last_edits {
+            true => Some(last_transform.inverse().then(transform)),
+            false => None,
         }
+    }
 
-        true
+    /// The image in memory has already been edited by the last modifications, so this 
+    /// swaps `modifications` for the flip, rotation and resize that get it from there.
+    fn get_mods_relative_to_mem(&self, modifications: &ModificationPipeline) -> ModificationPipeline {
+        let transform = self.get_transform_from_mem(modifications).unwrap_or_default();
+
+        transform.to_modifications()
+            .into_iter()
+            .chain(
+                modifications.iter()
+                    .last()
+                    .filter(|modification| matches!(modification, ImageModification::Resize(..)))
+                    .cloned()
+            )
+            .collect()
     }
-}
\ No newline at end of file
+
+    /// Pipelines are ordered so rotating then cropping isn't the same as cropping then rotating.
+    fn are_mods_the_same(&self, modifications: &ModificationPipeline) -> bool {
+        *modifications == self.last_modifications
+    }
+}
@@ -1,4 +1,5 @@
 pub mod image;
 pub mod backend;
+pub mod adjustments;
 
-pub use image::Image;
\ No newline at end of file
+pub use image::Image;
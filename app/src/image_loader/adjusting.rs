@@ -0,0 +1,40 @@
+use cirrus_egui::notifier::Notifier;
+use log::debug;
+
+use crate::{image::{adjustments::ColourAdjustments, backend::DefaultDecodingBackend}, image_selector::ImageSelector, monitor_size::MonitorSize};
+
+use super::ImageLoader;
+
+impl ImageLoader {
+    /// Adjusts the colours of the image. The image in memory is adjusted again from 
+    /// how it was before it's adjustments so this doesn't go back to the image file.
+    pub fn adjust(
+        &mut self,
+        adjustments: ColourAdjustments,
+        image_selector: &mut ImageSelector,
+        monitor_size: &MonitorSize,
+        backend: DefaultDecodingBackend,
+        notifier: &mut Notifier,
+    ) {
+        if self.state.is_loading() {
+            debug!("Not adjusting the image's colours as it's still being loaded...");
+            return;
+        }
+
+        if let Some(image) = image_selector.get_mutable_image() {
+            debug!("Adjusting image colours ({:?})...", adjustments);
+
+            image.adjustments = adjustments;
+
+            self.reset();
+
+            self.load(
+                image,
+                true,
+                backend,
+                monitor_size,
+                notifier,
+            );
+        }
+    }
+}
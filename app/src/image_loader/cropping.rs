@@ -0,0 +1,40 @@
+use cirrus_egui::notifier::Notifier;
+use log::debug;
+
+use crate::{image::backend::DefaultDecodingBackend, image_selector::ImageSelector, monitor_size::MonitorSize};
+
+use super::ImageLoader;
+
+impl ImageLoader {
+    /// Crops the image to the `(x, y, width, height)` rectangle of the image as it's shown 
+    /// (or takes the crop off with `None`). Like rotating, the image file itself is never touched.
+    pub fn crop(
+        &mut self,
+        rect: Option<(u32, u32, u32, u32)>,
+        image_selector: &mut ImageSelector,
+        monitor_size: &MonitorSize,
+        backend: DefaultDecodingBackend,
+        notifier: &mut Notifier,
+    ) {
+        if self.state.is_loading() {
+            debug!("Not cropping the image as it's still being loaded...");
+            return;
+        }
+
+        if let Some(image) = image_selector.get_mutable_image() {
+            debug!("Cropping image ({:?})...", rect);
+
+            image.apply_crop(rect);
+
+            self.reset();
+
+            self.load(
+                image,
+                true,
+                backend,
+                monitor_size,
+                notifier,
+            );
+        }
+    }
+}
@@ -3,12 +3,16 @@ use std::time::Duration;
 use cirrus_egui::{notifier::Notifier, scheduler::Scheduler};
 use eframe::egui::Vec2;
 use log::debug;
-use roseate_core::decoded_image::ImageSize;
+use roseate_core::{decoded_image::ImageSize, format::ImageFormat};
 
 use crate::{image::{Image, backend::DefaultDecodingBackend}, image_selector::ImageSelector, monitor_size::MonitorSize};
 
 use super::ImageLoader;
 
+/// The largest size (on either side) we'll ever rasterize an svg 
+/// to when zooming so we stay within the GPU's texture size limits.
+const MAX_SVG_RASTER_SIZE: u32 = 8192;
+
 impl ImageLoader {
     pub fn dynamic_sampling_update(
         &mut self,
@@ -53,8 +57,10 @@ impl ImageLoader {
 
     fn schedule_dynamic_sampling(&mut self, image: &Image, zoom_factor: &f32) {
         let is_enabled = self.image_optimizations.dynamic_sampling.is_some();
+        let is_svg = image.format == ImageFormat::Svg;
 
-        if !is_enabled || !self.monitor_downsampling_required {
+        // svgs can always be rasterized sharper, raster images can't go above their original resolution.
+        if !is_svg && (!is_enabled || !self.monitor_downsampling_required) {
             return;
         }
 
@@ -79,8 +85,18 @@ impl ImageLoader {
             return;
         }
 
-        let max_image_size = &image.size;
-        let mut image_size = &image.size;
+        let max_image_size = match is_svg {
+            true => {
+                let scale = MAX_SVG_RASTER_SIZE as f32 / image.size.0.max(image.size.1) as f32;
+
+                (
+                    (image.size.0 as f32 * scale) as u32,
+                    (image.size.1 as f32 * scale) as u32
+                )
+            },
+            false => image.size,
+        };
+        let image_size = &image.size;
 
         // TODO: (28/03/2025) check if we even need this now
         // if let Some(ImageOptimizations::MonitorDownsampling(marginal_allowance)) = self.image_optimizations.get(
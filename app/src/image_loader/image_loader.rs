@@ -1,18 +1,22 @@
-use std::{collections::HashSet, hash::{DefaultHasher, Hash, Hasher}, thread, time::{Duration, Instant}};
+use std::{hash::{DefaultHasher, Hash, Hasher}, thread, time::{Duration, Instant}};
 
 use cirrus_egui::{notifier::{Notifier, toast::ToastText}, scheduler::Scheduler};
 use cirrus_soft_binds::egui::BoxedEguiInputReaderFunc;
 use eframe::egui::Ui;
 use egui_notify::ToastLevel;
 use log::{debug, info, warn};
-use roseate_core::{decoded_image::ImageSize, format::ImageFormat, modifications::{ImageModification, ImageModifications}};
+use roseate_core::{animation::AnimationStreaming, decoded_image::{DecodedImage, ImageSize}, format::ImageFormat, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline, ResizeFilter}, orientation::ImageOrientation, processing::{quantization::Dithering, tone_mapping::ToneMapper}};
 
-use crate::{image::{Image, backend::DefaultDecodingBackend}, image_loader::{optimization::ImageOptimizations, state::{ImageLoaderState, InnerState}, uploading::UploadedImage}, image_selector::ImageSelector, monitor_size::MonitorSize};
+use crate::{image::{Image, adjustments::ColourAdjustments, backend::DefaultDecodingBackend}, image_loader::{optimization::ImageOptimizations, state::{ImageLoaderState, InnerState}, uploading::UploadedImage}, image_selector::ImageSelector, monitor_size::MonitorSize};
 
 pub struct ImageLoader {
     pub state: ImageLoaderState,
 
     pub image_optimizations: ImageOptimizations,
+    pub tone_mapper: ToneMapper,
+    pub dithering: Dithering,
+    pub decode_limits: DecodeLimits,
+    pub animation_streaming: Option<AnimationStreaming>,
 
     pub(super) dynamic_sample_schedule: Option<Scheduler>,
     pub(super) last_zoom_factor: f32,
@@ -23,15 +27,33 @@ pub struct ImageLoader {
 
     pub(super) uploaded_image: Option<UploadedImage>,
 
+    pub(super) next_page_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) previous_page_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) rotate_clockwise_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) rotate_anticlockwise_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) flip_horizontally_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) flip_vertically_input_reader: Option<BoxedEguiInputReaderFunc>,
+    pub(super) queued_transform: Option<ImageOrientation>,
+
     new_image_experimental_warning_shown: bool,
 }
 
 impl ImageLoader {
-    pub fn new(image_optimizations: ImageOptimizations) -> Self {
+    pub fn new(
+        image_optimizations: ImageOptimizations,
+        tone_mapper: ToneMapper,
+        dithering: Dithering,
+        decode_limits: DecodeLimits,
+        animation_streaming: Option<AnimationStreaming>
+    ) -> Self {
         Self {
             state: ImageLoaderState::default(),
 
             image_optimizations,
+            tone_mapper,
+            dithering,
+            decode_limits,
+            animation_streaming,
 
             dynamic_sample_schedule: None,
             last_zoom_factor: 1.0,
@@ -42,6 +64,14 @@ impl ImageLoader {
 
             uploaded_image: None,
 
+            next_page_input_reader: None,
+            previous_page_input_reader: None,
+            rotate_clockwise_input_reader: None,
+            rotate_anticlockwise_input_reader: None,
+            flip_horizontally_input_reader: None,
+            flip_vertically_input_reader: None,
+            queued_transform: None,
+
             new_image_experimental_warning_shown: false
         }
     }
@@ -82,15 +112,7 @@ impl ImageLoader {
             }
 
             if let Some(image) = image_selector.get_mutable_image() {
-                // reset the image loader (set all values back to default)
-                self.dynamic_sample_schedule = None;
-                self.last_zoom_factor = 1.0;
-                self.dynamic_sampling_new_resolution = ImageSize::default();
-                self.dynamic_sampling_old_resolution = ImageSize::default();
-                self.accumulated_zoom_factor_change = 0.0;
-                self.monitor_downsampling_required = false;
-                // self.uploaded_image = None;
-                // self.load_image_to_gpu = Arc::new(Mutex::new(false));
+                self.reset();
 
                 self.load(
                     image,
@@ -103,6 +125,19 @@ impl ImageLoader {
         }
     }
 
+    /// Resets the image loader (sets all values back to default) 
+    /// for when a different image (or page) is about to be loaded.
+    pub(super) fn reset(&mut self) {
+        self.dynamic_sample_schedule = None;
+        self.last_zoom_factor = 1.0;
+        self.dynamic_sampling_new_resolution = ImageSize::default();
+        self.dynamic_sampling_old_resolution = ImageSize::default();
+        self.accumulated_zoom_factor_change = 0.0;
+        self.monitor_downsampling_required = false;
+        // self.uploaded_image = None;
+        // self.load_image_to_gpu = Arc::new(Mutex::new(false));
+    }
+
     pub fn load(
         &mut self,
         image: &mut Image,
@@ -118,45 +153,59 @@ impl ImageLoader {
 
         let mut image_modifications = self.get_image_modifications(
             &image.size,
+            &image.format,
+            &image.edits,
+            &image.adjustments,
             monitor_size,
         );
 
         let image_modifications_debug = format!("{:?}", image_modifications);
 
         let use_experimental_multi_threaded_downsampling = match &self.image_optimizations.multi_threaded_sampling {
-            Some(multi_threaded_sampling) => {
-                Self::snatch_resize_modification_and_get_size(&mut image_modifications)
-                    .and_then(|target_size| Some((target_size, multi_threaded_sampling.number_of_threads)))
+            // svgs are rasterized straight at the size we want so there's no point downsampling 
+            // them after and the multi-threaded downsample is lanczos only so any other filter is left to the decoder.
+            Some(multi_threaded_sampling) if image.format != ImageFormat::Svg && self.image_optimizations.resize_filter == ResizeFilter::Lanczos3 => {
+                // backends that can decode the image straight at a fraction of it's size (jpegs with image-rs) 
+                // beat decoding it at full size and downsampling after, they only have a little bit left to resize.
+                let decode_scale = image_modifications.last_resize()
+                    .map(|target_size| backend.decode_scale(&image.format, image.size, target_size))
+                    .unwrap_or(1);
+
+                match decode_scale > 1 {
+                    true => {
+                        debug!(
+                            "Leaving the resize to the '{}' backend as it can decode this image at 1/{} scale...",
+                            backend,
+                            decode_scale
+                        );
+
+                        None
+                    },
+                    false => Self::snatch_resize_modification_and_get_size(&mut image_modifications)
+                        .and_then(|target_size| Some((target_size, multi_threaded_sampling.number_of_threads))),
+                }
             },
-            None => None,
+            _ => None,
         };
 
+        let use_raw_embedded_preview = self.image_optimizations.raw_embedded_preview;
+        let use_progressive_decoding = self.image_optimizations.progressive_decoding;
+        let decode_limits = self.decode_limits;
+        let animation_streaming = self.animation_streaming;
+
         *self.state.inner_state.lock().unwrap() = InnerState::Decoding;
 
         notifier.set_loading(
             Some("Gathering necessary image modifications...")
         );
 
-        // Our svg implementation is very experimental. 
-        // Also broken! https://github.com/cloudy-org/roseate/issues/66 
-        // Let's warn the user.
-        if ImageFormat::Svg == image.format {
-            notifier.toast(
-                "SVG files are experimental and broken! \
-                Expect many bugs, inconstancies and performance / memory issues.",
-                egui_notify::ToastLevel::Warning,
-                |toast| {
-                    toast.duration(Some(Duration::from_secs(8)));
-                }
-            );
-        }
-
         // let image_loaded_arc = self.image_loaded_arc.clone();
         let mut image_clone = image.clone();
         let mut notifier_clone = notifier.clone();
 
         let image_loader_state_arc = self.state.inner_state.clone();
         let load_image_to_gpu_arc = self.state.load_image_to_gpu.clone();
+        let snapshot_arc = self.state.snapshot.clone();
 
         let reload_image = match &self.uploaded_image {
             Some(uploaded_image) => {
@@ -176,10 +225,15 @@ impl ImageLoader {
                 true => {
                     notifier_clone.set_loading(Some("Reloading image..."));
 
+                    // there's already an image on screen when reloading so we don't bother with snapshots.
                     let result = image_clone.load(
                         image_modifications,
                         &backend,
                         true,
+                        use_raw_embedded_preview,
+                        animation_streaming,
+                        decode_limits,
+                        None,
                         &mut notifier_clone
                     );
 
@@ -194,10 +248,38 @@ impl ImageLoader {
                 false => {
                     notifier_clone.set_loading(Some("Loading image..."));
 
+                    // the thumbnail embedded into camera jpegs and raws gets shown (stretched 
+                    // out and blurry) in the meantime so big photos don't leave us with a blank screen.
+                    if let Some(thumbnail) = image_clone.decode_embedded_thumbnail() {
+                        debug!("Showing image's embedded thumbnail as a placeholder...");
+                        *snapshot_arc.lock().unwrap() = Some(thumbnail);
+                    }
+
+                    let mut on_snapshot = |mut snapshot: DecodedImage| {
+                        // snapshots skip the resize modification just like the 
+                        // final image does so we have to downsample them ourselves.
+                        if let Some((target_size, number_of_threads)) = use_experimental_multi_threaded_downsampling {
+                            Self::multi_threaded_downsample_decoded_image(
+                                target_size,
+                                &mut snapshot,
+                                number_of_threads
+                            );
+                        }
+
+                        *snapshot_arc.lock().unwrap() = Some(snapshot);
+                    };
+
                     let result = image_clone.load(
                         image_modifications,
                         &backend,
                         false,
+                        use_raw_embedded_preview,
+                        animation_streaming,
+                        decode_limits,
+                        match use_progressive_decoding {
+                            true => Some(&mut on_snapshot),
+                            false => None,
+                        },
                         &mut notifier_clone,
                     );
 
@@ -210,6 +292,9 @@ impl ImageLoader {
                 }
             };
 
+            // a snapshot we haven't got round to uploading yet is of no use to us any more.
+            *snapshot_arc.lock().unwrap() = None;
+
             match result {
                 Ok(()) => {
                     if let Some((target_size, number_of_threads)) = use_experimental_multi_threaded_downsampling {
@@ -257,8 +342,18 @@ impl ImageLoader {
     /// Method that handles choosing which type of modifications 
     /// should be done to the image at this time. It decides that on a number of various factors, 
     /// like image optimizations applied by the user, monitor size, zoom factor and etc.
-    fn get_image_modifications(&mut self, image_size: &ImageSize, monitor_size: &MonitorSize) -> ImageModifications {
-        let mut image_modifications = HashSet::new();
+    fn get_image_modifications(
+        &mut self,
+        image_size: &ImageSize,
+        image_format: &ImageFormat,
+        image_edits: &ModificationPipeline,
+        image_adjustments: &ColourAdjustments,
+        monitor_size: &MonitorSize
+    ) -> ModificationPipeline {
+        // the flips, rotations and crops the user asked for always come along 
+        // followed by their colour adjustments, any resize goes on the end.
+        let mut image_modifications = image_edits.clone();
+        image_modifications.extend(image_adjustments.to_modifications());
 
         if let Some(monitor_downsampling) = &self.image_optimizations.monitor_downsampling {
             let (max_width, max_height) = monitor_downsampling.get_size_relative_to_monitor(&monitor_size);
@@ -291,17 +386,21 @@ impl ImageLoader {
                     monitor_downsampling.marginal_allowance, width, height
                 );
 
-                image_modifications.replace(
-                    ImageModification::Resize(width.round() as u32, height.round() as u32)
+                image_modifications.push(
+                    ImageModification::Resize(
+                        width.round() as u32,
+                        height.round() as u32,
+                        self.image_optimizations.resize_filter
+                    )
                 );
             }
         }
 
-        if let Some(dynamic_sampling) = &self.image_optimizations.dynamic_sampling {
+        // svgs always get dynamically sampled (re-rasterized) so they stay sharp when zoomed into.
+        if self.image_optimizations.dynamic_sampling.is_some() || *image_format == ImageFormat::Svg {
             // TODO: handle up and down dyn sampling options.
             // NOTE: I think I might just add "down" as a bool tbh, you'll never want 
             // upsampling to be disabled if you choose to enable dyn sampling in the first place.
-            let (up, down) = (dynamic_sampling.up, dynamic_sampling.down);
 
             let new_resolution = self.dynamic_sampling_new_resolution;
             let old_resolution = self.dynamic_sampling_old_resolution;
@@ -314,8 +413,12 @@ impl ImageLoader {
                 );
 
                 if !(new_resolution.0 == image_size.0 && new_resolution.1 == image_size.1) {
-                    image_modifications.replace(
-                        ImageModification::Resize(new_resolution.0, new_resolution.1)
+                    image_modifications.push(
+                        ImageModification::Resize(
+                            new_resolution.0,
+                            new_resolution.1,
+                            self.image_optimizations.resize_filter
+                        )
                     );
 
                     self.dynamic_sampling_old_resolution = new_resolution;
@@ -325,9 +428,7 @@ impl ImageLoader {
                         dynamic sampling is requesting the full resolution already!"
                     );
 
-                    image_modifications.remove(
-                        &ImageModification::Resize(new_resolution.0, new_resolution.1)
-                    );
+                    image_modifications.pop_resize();
 
                     self.dynamic_sampling_old_resolution = (
                         image_size.0,
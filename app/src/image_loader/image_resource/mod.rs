@@ -1,4 +1,7 @@
 mod resource;
 pub use resource::*;
 
-mod pixels;
\ No newline at end of file
+mod pixels;
+
+mod streamed_animation;
+pub use streamed_animation::*;
\ No newline at end of file
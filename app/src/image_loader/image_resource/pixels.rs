@@ -1,10 +1,6 @@
-use std::time::Duration;
-
-use cirrus_egui::notifier::Notifier;
 use eframe::egui::{self, Color32, Context, TextureHandle, TextureOptions};
-use egui_notify::ToastLevel;
 use log::debug;
-use roseate_core::{colour_type::ImageColourType, decoded_image::DecodedImage, pixels::Pixels, processing::quantization::squish_pixels_to_u8};
+use roseate_core::{colour_type::ImageColourType, decoded_image::{DecodedImage, ImageSize}, pixels::Pixels, processing::{alpha::premultiply_alpha, quantization::{Dithering, quantize_pixels_to_u8}, tone_mapping::{ToneMapper, tone_map_pixels_to_u8}}};
 
 use crate::image_loader::image_resource::ImageResource;
 
@@ -14,7 +10,8 @@ impl ImageResource {
         decoded_image: &DecodedImage,
         pixels: &Pixels,
         texture_options: TextureOptions,
-        notifier: &mut Notifier,
+        tone_mapper: ToneMapper,
+        dithering: Dithering,
     ) -> TextureHandle {
         let image_size = [decoded_image.size.0 as usize, decoded_image.size.1 as usize];
 
@@ -26,25 +23,34 @@ impl ImageResource {
                     image_size,
                     decoded_image.info.colour_type
                 ),
-                // NOTE: U16 and F32 images will display completely washed out.
-                // 
-                // Currently we do not handle any kind of HDR or scientific 
-                // data and u16 and f32 images just get linearly converted to 
-                // u8 or clamped. This happens in the 'squish_pixels_to_u8' method.
-                // 
-                // Also i'm currently still learning about bit depth and everything else around it. ~ Goldy
-                higher_bit_depth_pixels => {
-                    notifier.show_toast(
-                        "U16 and F32 bit depth images are experimental, they may display incorrectly or washed out!",
-                        ToastLevel::Warning,
-                        |toast| {
-                            toast.duration(Duration::from_secs(30));
-                        }
+                // HDR images are scene-linear and go way past 1.0 so 
+                // clamping them would blow out all the highlights.
+                Pixels::F32(pixels) if decoded_image.info.format.is_high_dynamic_range() => {
+                    let raw_vec_u8_pixels = tone_map_pixels_to_u8(
+                        pixels,
+                        decoded_image.info.colour_type,
+                        tone_mapper
                     );
 
-                    let raw_vec_u8_pixels = squish_pixels_to_u8(higher_bit_depth_pixels);
+                    debug!("Done tone-mapping to u8 pixels, transforming to egui colour image now...");
 
-                    debug!("Done squishing to u8 pixels, transforming to egui colour image now...");
+                    Self::u8_pixels_into_egui_color_image(
+                        &raw_vec_u8_pixels,
+                        image_size,
+                        decoded_image.info.colour_type
+                    )
+                },
+                // our monitors (and egui textures) are only 8 bit so 16 bit and float 
+                // images get dithered down to 8 bit, otherwise smooth gradients would band.
+                higher_bit_depth_pixels => {
+                    let raw_vec_u8_pixels = quantize_pixels_to_u8(
+                        higher_bit_depth_pixels,
+                        decoded_image.size,
+                        decoded_image.info.colour_type,
+                        dithering
+                    );
+
+                    debug!("Done quantizing to u8 pixels, transforming to egui colour image now...");
 
                     Self::u8_pixels_into_egui_color_image(
                         &raw_vec_u8_pixels,
@@ -62,9 +68,18 @@ impl ImageResource {
     pub(super) fn rgba8_pixels_direct_consume_into_egui_texture(
         ctx: &Context,
         decoded_image: &DecodedImage,
-        mut pixels: Pixels,
+        pixels: Pixels,
         texture_options: TextureOptions
     ) -> TextureHandle {
+        ctx.load_texture(
+            "static_image",
+            Self::rgba8_pixels_into_egui_color_image(pixels, decoded_image.size),
+            texture_options
+        )
+    }
+
+    /// Consumes the RGBA8 pixels into an egui colour image without copying them.
+    pub(super) fn rgba8_pixels_into_egui_color_image(mut pixels: Pixels, image_size: ImageSize) -> egui::ColorImage {
         assert!(pixels.len() % 4 == 0);
 
         let length = pixels.len() / 4;
@@ -74,6 +89,10 @@ impl ImageResource {
             Pixels::F32(_) => panic!("f32 pixels are not allowed in `rgba8_pixels_direct_consume_into_color32`!"),
         };
 
+        // "Color32" wants premultiplied RGBA only and zero copying copies in 
+        // unmultiplied RGBA so we premultiply the pixels in place (and in parallel) first.
+        premultiply_alpha(&mut pixels, ImageColourType::Rgba8);
+
         let pointer = pixels.as_mut_ptr() as *mut Color32;
 
         match pixels {
@@ -84,31 +103,11 @@ impl ImageResource {
 
         let mut colour_32_pixels: Vec<Color32> = unsafe { Vec::from_raw_parts(pointer, length, capacity) };
 
-        // "Color32" wants premultiplied RGBA only and zero copying copies in 
-        // unmultiplied RGBA so we need to convert it to a premultiplied RGBA colour.
-        // 
-        // Despite what you think, this will not lead to a duplication of memory 
-        // (a new vec being assigned), the Rust compiler is smart enough here to 
-        // perform an "in-place" optimization while iterating through the vec. :)
-        // 
-        // https://doc.rust-lang.org/stable/std/iter/trait.FromIterator.html#impl-FromIterator%3CT%3E-for-Vec%3CT%3E
-        // 
-        // I fucking love compilers! https://stackoverflow.com/a/78682795
-        colour_32_pixels = colour_32_pixels.iter()
-            .map(|colour| Color32::from_rgba_unmultiplied(
-                colour.r(), colour.g(), colour.b(), colour.a()
-            ))
-            .collect();
-
         colour_32_pixels.shrink_to_fit();
 
-        let image_size = [decoded_image.size.0 as usize, decoded_image.size.1 as usize];
+        let image_size = [image_size.0 as usize, image_size.1 as usize];
 
-        ctx.load_texture(
-            "static_image",
-            egui::ColorImage::new(image_size, colour_32_pixels),
-            texture_options
-        )
+        egui::ColorImage::new(image_size, colour_32_pixels)
     }
 
     fn u8_pixels_into_egui_color_image(raw_vec_pixels: &Vec<u8>, image_size: [usize; 2], colour_type: ImageColourType) -> egui::ColorImage {
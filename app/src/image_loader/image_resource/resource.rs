@@ -1,12 +1,17 @@
-use cirrus_egui::notifier::Notifier;
+use std::sync::{Arc, Mutex};
+
 use eframe::egui::{Context, TextureHandle, TextureOptions};
 use log::debug;
-use roseate_core::{colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, pixels::Pixels};
+use roseate_core::{colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, pixels::Pixels, processing::{quantization::Dithering, tone_mapping::ToneMapper}};
+
+use crate::image_loader::image_resource::StreamedAnimation;
 
 #[derive(Clone)]
 pub enum ImageResource {
     Texture(TextureHandle),
     AnimatedTexture(Vec<(TextureHandle, f32)>),
+    /// Animations too big to hold every frame of, played back a frame at a time as they're decoded.
+    StreamedAnimation(Arc<Mutex<StreamedAnimation>>),
     // Vector(egui::Image<'static>)
 }
 
@@ -15,7 +20,8 @@ impl ImageResource {
         ctx: &Context,
         decoded_image: &DecodedImage,
         texture_options: TextureOptions,
-        notifier: &mut Notifier,
+        tone_mapper: ToneMapper,
+        dithering: Dithering,
     ) -> Self {
         debug!("Copying image's '{}' pixels into RGBA egui texture...", decoded_image.info.colour_type);
 
@@ -28,7 +34,8 @@ impl ImageResource {
                     decoded_image,
                     pixels,
                     texture_options,
-                    notifier,
+                    tone_mapper,
+                    dithering,
                 );
 
                 Self::Texture(texture)
@@ -46,7 +53,8 @@ impl ImageResource {
                                 decoded_image,
                                 pixels,
                                 texture_options,
-                                notifier,
+                                tone_mapper,
+                                dithering,
                             ),
                             *delay
                         )
@@ -55,6 +63,32 @@ impl ImageResource {
 
                 Self::AnimatedTexture(textures)
             },
+            // streamed frames are always rgba8 so there's nothing to tone-map or dither.
+            DecodedImageContent::Streamed(stream) => {
+                debug!("Uploading the first frame of streamed animation to egui image texture...");
+
+                Self::StreamedAnimation(
+                    Arc::new(Mutex::new(
+                        StreamedAnimation::new(ctx, stream.clone(), decoded_image.size, texture_options)
+                    ))
+                )
+            },
+            // we select the page we want before decoding so only 
+            // the first page would be shown if we were ever handed them all.
+            DecodedImageContent::Pages(pages) => {
+                debug!("Transforming first page of multi-page image to egui image texture for uploading to the GPU...");
+
+                let texture = Self::decoded_image_pixels_to_egui_texture(
+                    ctx,
+                    decoded_image,
+                    &pages[0].pixels,
+                    texture_options,
+                    tone_mapper,
+                    dithering,
+                );
+
+                Self::Texture(texture)
+            },
         }
     }
 
@@ -109,6 +143,30 @@ impl ImageResource {
 
                 ImageResource::AnimatedTexture(textures)
             },
+            DecodedImageContent::Streamed(stream) => {
+                debug!("Uploading the first frame of streamed animation to egui image texture...");
+
+                ImageResource::StreamedAnimation(
+                    Arc::new(Mutex::new(
+                        StreamedAnimation::new(ctx, stream, decoded_image.size, texture_options)
+                    ))
+                )
+            },
+            DecodedImageContent::Pages(pages) => {
+                debug!("Handing first page of multi-page image to egui's backend to upload to the GPU...");
+
+                let first_page = pages.into_iter().next()
+                    .expect("what! this multi-page image has no pages!?!?");
+
+                let texture = Self::rgba8_pixels_direct_consume_into_egui_texture(
+                    ctx,
+                    decoded_image,
+                    first_page.pixels,
+                    texture_options
+                );
+
+                ImageResource::Texture(texture)
+            },
         }
     }
 }
\ No newline at end of file
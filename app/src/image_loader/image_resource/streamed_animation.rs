@@ -0,0 +1,77 @@
+use eframe::egui::{Color32, ColorImage, Context, TextureHandle, TextureOptions};
+use log::{debug, warn};
+use roseate_core::{animation::AnimationStream, decoded_image::ImageSize};
+
+use crate::image_loader::image_resource::ImageResource;
+
+/// An animation too big to upload every frame of, so one texture 
+/// gets each frame written over it as the frames are streamed in.
+pub struct StreamedAnimation {
+    stream: AnimationStream,
+    size: ImageSize,
+    texture: TextureHandle,
+    /// How long (in seconds) the frame in the texture is shown for.
+    delay: f32,
+    texture_options: TextureOptions,
+}
+
+impl StreamedAnimation {
+    /// Waits on the first frame so there's something to show straight away.
+    pub(super) fn new(
+        ctx: &Context,
+        stream: AnimationStream,
+        size: ImageSize,
+        texture_options: TextureOptions,
+    ) -> Self {
+        debug!("Waiting on the first frame of the streamed animation...");
+
+        let (colour_image, delay) = match stream.next_frame() {
+            Some(Ok((pixels, delay))) => (ImageResource::rgba8_pixels_into_egui_color_image(pixels, size), delay),
+            Some(Err(error)) => {
+                warn!("Failed to decode the first frame of the streamed animation: {}", error);
+                (ColorImage::new([1, 1], vec![Color32::TRANSPARENT]), 0.0)
+            },
+            None => {
+                warn!("Streamed animation ended before it's first frame was decoded!");
+                (ColorImage::new([1, 1], vec![Color32::TRANSPARENT]), 0.0)
+            },
+        };
+
+        Self {
+            texture: ctx.load_texture("streamed_animation", colour_image, texture_options),
+            stream,
+            size,
+            delay,
+            texture_options,
+        }
+    }
+
+    /// Writes the next frame over the texture if it's been decoded yet, 
+    /// returns false (keeping the current frame) if it hasn't.
+    pub fn next_frame(&mut self) -> bool {
+        match self.stream.try_next_frame() {
+            Some(Ok((pixels, delay))) => {
+                self.texture.set(
+                    ImageResource::rgba8_pixels_into_egui_color_image(pixels, self.size),
+                    self.texture_options
+                );
+                self.delay = delay;
+
+                true
+            },
+            Some(Err(error)) => {
+                warn!("Streamed animation stopped on an error: {}", error);
+                false
+            },
+            None => false,
+        }
+    }
+
+    pub fn texture(&self) -> &TextureHandle {
+        &self.texture
+    }
+
+    pub fn delay(&self) -> f32 {
+        self.delay
+    }
+}
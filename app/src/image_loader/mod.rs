@@ -5,6 +5,10 @@ pub mod state;
 pub mod uploading;
 pub mod optimization;
 pub mod image_resource;
+pub mod transform;
 
+mod pages;
+mod cropping;
+mod adjusting;
 mod dynamic_sampling;
 mod multi_threaded_sampling;
\ No newline at end of file
@@ -1,27 +1,43 @@
 use log::debug;
-use roseate_core::{decoded_image::{DecodedImageContent, ImageSize}, fast_downsample::experimental_fast_downsample, modifications::{ImageModification, ImageModifications}};
+use roseate_core::{decoded_image::{DecodedImage, DecodedImageContent, ImageSize}, fast_downsample::experimental_fast_downsample, modifications::ModificationPipeline};
 
 use crate::{image::Image, image_loader::ImageLoader};
 
 impl ImageLoader {
-    pub fn snatch_resize_modification_and_get_size(image_modifications: &mut ImageModifications) -> Option<ImageSize> {
-        for modification in image_modifications.clone().iter() {
-            #[allow(irrefutable_let_patterns)]
-            if let ImageModification::Resize(width, height) = modification {
-                image_modifications.remove(&modification);
-                return Some((*width, *height));
-            }
-        }
-
-        None
+    pub fn snatch_resize_modification_and_get_size(image_modifications: &mut ModificationPipeline) -> Option<ImageSize> {
+        image_modifications.pop_resize()
     }
 
     pub fn perform_multi_threaded_downsample(target_size: ImageSize, image: &mut Image, number_of_threads: Option<usize>) {
         debug!("Using the experimental multi-threaded function to downsample this image...");
 
         if let Some(decoded_image) = image.decoded.lock().unwrap().as_mut() {
-            match &mut decoded_image.content {
-                DecodedImageContent::Static(pixels) => {
+            Self::multi_threaded_downsample_decoded_image(target_size, decoded_image, number_of_threads);
+        }
+    }
+
+    pub(super) fn multi_threaded_downsample_decoded_image(
+        target_size: ImageSize,
+        decoded_image: &mut DecodedImage,
+        number_of_threads: Option<usize>
+    ) {
+        match &mut decoded_image.content {
+            DecodedImageContent::Static(pixels) => {
+                (*pixels, decoded_image.size) = experimental_fast_downsample(
+                    pixels,
+                    target_size,
+                    &decoded_image.size,
+                    &decoded_image.info.colour_type,
+                    number_of_threads
+                );
+            },
+            DecodedImageContent::Animated(frames) => {
+                let mut index = 0;
+
+                for (pixels, _) in frames {
+                    debug!("Downsampling frame {}...", index);
+
+                    // NOTE: This will need testing.
                     (*pixels, decoded_image.size) = experimental_fast_downsample(
                         pixels,
                         target_size,
@@ -29,26 +45,37 @@ impl ImageLoader {
                         &decoded_image.info.colour_type,
                         number_of_threads
                     );
-                },
-                DecodedImageContent::Animated(frames) => {
-                    let mut index = 0;
-
-                    for (pixels, _) in frames {
-                        debug!("Downsampling frame {}...", index);
-
-                        // NOTE: This will need testing.
-                        (*pixels, decoded_image.size) = experimental_fast_downsample(
-                            pixels,
-                            target_size,
-                            &decoded_image.size,
-                            &decoded_image.info.colour_type,
-                            number_of_threads
-                        );
-
-                        index += 1;
-                    }
-                },
-            }
+
+                    index += 1;
+                }
+            },
+            // the frames of a streamed animation are decoded as it plays so they're shown at full size.
+            DecodedImageContent::Streamed(_) => {
+                debug!("Image is a streamed animation, there's no frames in memory to downsample, skipping...");
+            },
+            DecodedImageContent::Pages(pages) => {
+                let first_page_size = decoded_image.size;
+
+                for (index, page) in pages.iter_mut().enumerate() {
+                    debug!("Downsampling page {}...", index + 1);
+
+                    // the other pages are scaled down by the same amount as the first.
+                    let page_target_size = (
+                        (page.size.0 as f32 * target_size.0 as f32 / first_page_size.0 as f32).round().max(1.0) as u32,
+                        (page.size.1 as f32 * target_size.1 as f32 / first_page_size.1 as f32).round().max(1.0) as u32,
+                    );
+
+                    (page.pixels, page.size) = experimental_fast_downsample(
+                        &page.pixels,
+                        page_target_size,
+                        &page.size,
+                        &page.colour_type,
+                        number_of_threads
+                    );
+                }
+
+                decoded_image.size = pages[0].size;
+            },
         }
     }
 }
\ No newline at end of file
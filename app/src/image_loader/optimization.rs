@@ -1,7 +1,7 @@
 use std::thread::available_parallelism;
 
 use log::warn;
-use roseate_core::decoded_image::ImageSize;
+use roseate_core::{decoded_image::ImageSize, modifications::ResizeFilter};
 
 use crate::monitor_size::MonitorSize;
 
@@ -11,6 +11,10 @@ pub struct ImageOptimizations {
     pub dynamic_sampling: Option<DynamicSampling>,
     pub consume_pixels_during_gpu_upload: bool,
     pub multi_threaded_sampling: Option<MultiThreadedSampling>,
+    pub raw_embedded_preview: bool,
+    pub progressive_decoding: bool,
+    /// The filter the image is resampled with when it's resized.
+    pub resize_filter: ResizeFilter,
 }
 
 impl ImageOptimizations {
@@ -19,13 +23,19 @@ impl ImageOptimizations {
             monitor_downsampling: Some(MonitorDownsampling::default()),
             dynamic_sampling: None,
             consume_pixels_during_gpu_upload: true,
-            multi_threaded_sampling: Some(MultiThreadedSampling::default())
+            multi_threaded_sampling: Some(MultiThreadedSampling::default()),
+            raw_embedded_preview: false,
+            progressive_decoding: true,
+            resize_filter: ResizeFilter::Lanczos3,
         }
     }
 
     pub fn speed() -> Self {
         Self {
             monitor_downsampling: None,
+            raw_embedded_preview: true,
+            progressive_decoding: false,
+            resize_filter: ResizeFilter::Bilinear,
             ..Self::balanced()
         }
     }
@@ -34,6 +44,7 @@ impl ImageOptimizations {
         Self {
             monitor_downsampling: None,
             multi_threaded_sampling: None,
+            resize_filter: ResizeFilter::Lanczos3,
             ..Self::balanced()
         }
     }
@@ -50,7 +61,10 @@ impl ImageOptimizations {
     //         monitor_downsampling: None,
     //         dynamic_sampling: None,
     //         consume_pixels_during_gpu_upload: false,
-    //         multi_threaded_sampling: None
+    //         multi_threaded_sampling: None,
+    //         raw_embedded_preview: false,
+    //         progressive_decoding: false,
+    //         resize_filter: ResizeFilter::Bilinear,
     //     }
     // }
 
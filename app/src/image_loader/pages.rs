@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use cirrus_egui::notifier::{Notifier, banner::BannerPlacement};
+use cirrus_soft_binds::egui::{BoxedEguiInputReaderFunc, parse_and_get_egui_input_reader_from_string};
+use eframe::egui::{Key, Ui};
+use egui_notify::ToastLevel;
+use log::debug;
+
+use crate::{image::backend::DefaultDecodingBackend, image_selector::ImageSelector, monitor_size::MonitorSize};
+
+use super::ImageLoader;
+
+impl ImageLoader {
+    /// Flips through the pages of multi-page images (tiffs and icos), 
+    /// each page is decoded on it's own when we flip to it.
+    pub fn handle_page_input(
+        &mut self,
+        ui: &Ui,
+        image_selector: &mut ImageSelector,
+        monitor_size: &MonitorSize,
+        backend: DefaultDecodingBackend,
+        notifier: &mut Notifier,
+        next_page_key: &String,
+        previous_page_key: &String,
+    ) {
+        let next_page_reader = self.next_page_input_reader.get_or_insert_with(
+            || Self::get_key_bind_input_reader(next_page_key, Key::N, notifier)
+        );
+
+        let next_page_pressed = ui.input(next_page_reader);
+
+        let previous_page_reader = self.previous_page_input_reader.get_or_insert_with(
+            || Self::get_key_bind_input_reader(previous_page_key, Key::P, notifier)
+        );
+
+        let previous_page_pressed = ui.input(previous_page_reader);
+
+        let page_offset: isize = match (next_page_pressed, previous_page_pressed) {
+            (true, false) => 1,
+            (false, true) => -1,
+            _ => return,
+        };
+
+        if self.state.is_loading() {
+            debug!("Not changing page as the image is still being loaded...");
+            return;
+        }
+
+        if let Some(image) = image_selector.get_mutable_image() {
+            let page_count = image.pages.lock().unwrap().len();
+
+            if page_count < 2 {
+                return;
+            }
+
+            let page = image.page.saturating_add_signed(page_offset).min(page_count - 1);
+
+            if page == image.page {
+                return;
+            }
+
+            debug!("Changing to page {} of {}...", page + 1, page_count);
+
+            image.select_page(page);
+
+            notifier.show_banner(
+                format!("Page {} of {}", page + 1, page_count),
+                BannerPlacement::BOTTOM,
+                Duration::from_secs(2)
+            );
+
+            self.reset();
+
+            self.load(
+                image,
+                true,
+                backend,
+                monitor_size,
+                notifier,
+            );
+        }
+    }
+
+    pub(super) fn get_key_bind_input_reader(key_bind: &String, fallback_key: Key, notifier: &mut Notifier) -> BoxedEguiInputReaderFunc {
+        match parse_and_get_egui_input_reader_from_string(key_bind, |i, key| i.key_pressed(key)) {
+            Ok(reader) => Box::new(reader),
+            Err(error) => {
+                notifier.toast(
+                    error.to_string(),
+                    ToastLevel::Error,
+                    |_| {}
+                );
+
+                Box::new(move |i| i.key_pressed(fallback_key))
+            },
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::sync::{Arc, Mutex};
 
+use roseate_core::decoded_image::DecodedImage;
+
 pub(super) enum InnerState {
     Idling,
     Decoding,
@@ -16,6 +18,9 @@ impl Default for InnerState {
 pub struct ImageLoaderState {
     pub(super) inner_state: Arc<Mutex<InnerState>>,
     pub(super) load_image_to_gpu: Arc<Mutex<bool>>,
+    /// The latest partially decoded image handed to us while progressively
+    /// decoding, or the image's embedded thumbnail before any of those come in.
+    pub(super) snapshot: Arc<Mutex<Option<DecodedImage>>>,
 }
 
 impl ImageLoaderState {
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use cirrus_egui::notifier::{Notifier, banner::BannerPlacement};
+use eframe::egui::{Key, Ui};
+use log::debug;
+use roseate_core::orientation::ImageOrientation;
+
+use crate::{image::backend::DefaultDecodingBackend, image_selector::ImageSelector, monitor_size::MonitorSize};
+
+use super::ImageLoader;
+
+/// The key binds to rotate and flip the image with.
+pub struct TransformKeyBinds<'a> {
+    pub rotate_clockwise: &'a String,
+    pub rotate_anticlockwise: &'a String,
+    pub flip_horizontally: &'a String,
+    pub flip_vertically: &'a String,
+}
+
+impl ImageLoader {
+    /// Rotates and flips the image with the key binds (or the context menu), it's 
+    /// only how the image is shown that changes, the image file itself is never touched.
+    pub fn handle_transform_input(
+        &mut self,
+        ui: &Ui,
+        image_selector: &mut ImageSelector,
+        monitor_size: &MonitorSize,
+        backend: DefaultDecodingBackend,
+        notifier: &mut Notifier,
+        key_binds: TransformKeyBinds,
+    ) {
+        let transform = match self.queued_transform.take() {
+            Some(transform) => transform,
+            None => {
+                let input_readers = [
+                    (&mut self.rotate_clockwise_input_reader, key_binds.rotate_clockwise, Key::E, ImageOrientation::Rotate90),
+                    (&mut self.rotate_anticlockwise_input_reader, key_binds.rotate_anticlockwise, Key::Q, ImageOrientation::Rotate270),
+                    (&mut self.flip_horizontally_input_reader, key_binds.flip_horizontally, Key::H, ImageOrientation::FlipHorizontal),
+                    (&mut self.flip_vertically_input_reader, key_binds.flip_vertically, Key::V, ImageOrientation::FlipVertical),
+                ];
+
+                let mut pressed_transform = None;
+
+                for (input_reader, key_bind, fallback_key, transform) in input_readers {
+                    let input_reader = input_reader.get_or_insert_with(
+                        || Self::get_key_bind_input_reader(key_bind, fallback_key, notifier)
+                    );
+
+                    if ui.input(input_reader) && pressed_transform.is_none() {
+                        pressed_transform = Some(transform);
+                    }
+                }
+
+                match pressed_transform {
+                    Some(transform) => transform,
+                    None => return,
+                }
+            },
+        };
+
+        if self.state.is_loading() {
+            debug!("Not rotating or flipping the image as it's still being loaded...");
+            return;
+        }
+
+        if let Some(image) = image_selector.get_mutable_image() {
+            debug!("Transforming image ({:?})...", transform);
+
+            image.apply_transform(transform);
+
+            notifier.show_banner(
+                match transform {
+                    ImageOrientation::Rotate90 => "Rotated clockwise",
+                    ImageOrientation::Rotate270 => "Rotated anticlockwise",
+                    ImageOrientation::FlipHorizontal => "Flipped horizontally",
+                    _ => "Flipped vertically",
+                },
+                BannerPlacement::BOTTOM,
+                Duration::from_secs(1)
+            );
+
+            self.reset();
+
+            self.load(
+                image,
+                true,
+                backend,
+                monitor_size,
+                notifier,
+            );
+        }
+    }
+
+    /// Rotates or flips the image the next time input is handled, for 
+    /// the context menu which doesn't have the image selector to hand.
+    pub fn queue_transform(&mut self, transform: ImageOrientation) {
+        self.queued_transform = Some(transform);
+    }
+}
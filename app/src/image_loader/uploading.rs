@@ -3,10 +3,17 @@ use std::{hash::{DefaultHasher, Hash, Hasher}, time::Instant};
 use cirrus_egui::notifier::Notifier;
 use eframe::egui::{Context, TextureFilter, TextureOptions, TextureWrapMode};
 use log::debug;
-use roseate_core::{colour_type::ImageColourType, image_info::info::ImageInfo};
+use roseate_core::{colour_type::ImageColourType, decoded_image::DecodedImage, image_info::info::ImageInfo};
 
 use crate::{image::Image, image_loader::{ImageLoader, image_resource::ImageResource, state::InnerState}, image_selector::ImageSelector};
 
+const TEXTURE_OPTIONS: TextureOptions = TextureOptions {
+    magnification: TextureFilter::Linear,
+    minification: TextureFilter::Linear,
+    wrap_mode: TextureWrapMode::ClampToEdge,
+    mipmap_mode: None,
+};
+
 /// Image uploaded to the GPU.
 pub struct UploadedImage {
     pub image: Image,
@@ -32,30 +39,23 @@ impl ImageLoader {
 
                         notifier.set_loading(Some("Converting image to texture to be uploaded to the GPU..."));
 
-                        let texture_options = TextureOptions {
-                            magnification: TextureFilter::Linear,
-                            minification: TextureFilter::Linear,
-                            wrap_mode: TextureWrapMode::ClampToEdge,
-                            mipmap_mode: None,
-                        };
-
                         let is_rgba_8 = decoded_image.info.colour_type == ImageColourType::Rgba8;
 
                         self.uploaded_image = Some(
                             UploadedImage {
                                 image: image.clone(),
                                 resource: match can_free_memory_or_consume && is_rgba_8 {
-                                    true => ImageResource::from_rgba8_decoded_image_zero_copy(ctx, decoded_image, texture_options),
-                                    false => ImageResource::from_decoded_image(ctx, &decoded_image, texture_options, notifier),
+                                    true => ImageResource::from_rgba8_decoded_image_zero_copy(ctx, decoded_image, TEXTURE_OPTIONS),
+                                    false => ImageResource::from_decoded_image(
+                                        ctx,
+                                        &decoded_image,
+                                        TEXTURE_OPTIONS,
+                                        self.tone_mapper,
+                                        self.dithering,
+                                    ),
                                 },
                                 image_info: decoded_image.info.clone(),
-                                image_hash: {
-                                    let mut hasher = DefaultHasher::new();
-
-                                    image.hash(&mut hasher);
-
-                                    hasher.finish()
-                                }
+                                image_hash: Self::hash_image(image),
                             }
                         );
 
@@ -81,6 +81,25 @@ impl ImageLoader {
                         "Image uploaded to gpu in '{}' seconds.",
                         now.elapsed().as_secs_f32()
                     );
+                } else if let Some(snapshot) = self.take_snapshot() {
+                    debug!("Uploading snapshot of partially decoded image to the GPU...");
+
+                    // snapshots are thrown away after this so there's no 
+                    // need to use zero-copy or free anything, it's all ours.
+                    self.uploaded_image = Some(
+                        UploadedImage {
+                            image: image.clone(),
+                            resource: ImageResource::from_decoded_image(
+                                ctx,
+                                &snapshot,
+                                TEXTURE_OPTIONS,
+                                self.tone_mapper,
+                                self.dithering,
+                            ),
+                            image_info: snapshot.info.clone(),
+                            image_hash: Self::hash_image(image),
+                        }
+                    );
                 }
 
                 self.uploaded_image.as_ref()
@@ -88,4 +107,23 @@ impl ImageLoader {
             None => None,
         }
     }
-}
\ No newline at end of file
+
+    /// Takes the latest snapshot of the image being progressively decoded, if there is one. 
+    /// 
+    /// The decoding thread may be holding the lock while handing over a new 
+    /// snapshot, in which case we'll just pick it up on the next frame.
+    fn take_snapshot(&self) -> Option<DecodedImage> {
+        match self.state.snapshot.try_lock() {
+            Ok(mut snapshot) => snapshot.take(),
+            Err(_) => None,
+        }
+    }
+
+    fn hash_image(image: &Image) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        image.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
@@ -14,11 +14,16 @@ pub struct ImageSelector {
     selected: usize,
 
     images: HashMap<usize, Image>,
+    /// Whether images get turned the right way up with their exif orientation.
+    auto_orientation: bool,
 }
 
 impl ImageSelector {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(auto_orientation: bool) -> Self {
+        Self {
+            auto_orientation,
+            ..Default::default()
+        }
     }
 
     pub fn get_image(&self) -> Option<&Image> {
@@ -32,7 +37,7 @@ impl ImageSelector {
     pub fn select_image_from_path(&mut self, path: PathBuf) -> Result<()> {
         // NOTE: we don't support loading multiple images yet 
         // so we're not gonna handle any of that logic yet here 
-        self.images.insert(0, Image::new(path)?);
+        self.images.insert(0, Image::new(path, self.auto_orientation)?);
 
         Ok(())
     }
@@ -35,6 +35,7 @@ mod viewport;
 mod settings;
 mod about_window;
 mod context_menu;
+mod crop_tool;
 mod tutorial;
 mod image_selector;
 
@@ -178,9 +179,15 @@ fn main() -> eframe::Result {
     let image_optimizations = config.image.optimizations.get_optimizations()
         .normalize();
 
-    let mut image_selector = ImageSelector::new();
+    let mut image_selector = ImageSelector::new(config.image.orientation.auto);
     // TODO: rename to ImageLoader and make ImageSelector what stores and owns the Image struct
-    let mut image_loader = ImageLoader::new(image_optimizations);
+    let mut image_loader = ImageLoader::new(
+        image_optimizations,
+        config.image.hdr.get_tone_mapper(),
+        config.image.quantization.get_dithering(),
+        config.image.backend.get_decode_limits(),
+        config.image.animation.get_animation_streaming()
+    );
 
     if let Some(image_path_string) = cli_args.image {
         info!("Image '{}' loading from path...", image_path_string);
@@ -198,7 +198,8 @@ impl SettingsMenu {
                 SectionOverrides {
                     choices: Some(
                         vec![
-                            "image-rs".into()
+                            "image-rs".into(),
+                            "zune-image".into()
                         ]
                     ),
                     ..Default::default()
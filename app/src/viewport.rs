@@ -7,10 +7,29 @@ use log::debug;
 use roseate_core::decoded_image::ImageSize;
 use std::hash::Hasher;
 use cirrus_egui::{notifier::{Notifier, banner::BannerPlacement}, scheduler::Scheduler};
-use eframe::egui::{self, CursorIcon, InputState, Key, Rect, Sense, Ui, Vec2};
+use eframe::egui::{self, Align2, Color32, ColorImage, CursorIcon, InputState, Key, Pos2, Rect, Sense, TextureHandle, TextureOptions, Ui, Vec2};
 
 use crate::{image_loader::image_resource::ImageResource};
 
+// TODO: config to customize image corner radius.
+const IMAGE_CORNER_RADIUS: f32 = 10.0;
+/// How big (in points) each square of the transparency checkerboard is.
+const CHECKERBOARD_SQUARE_SIZE: f32 = 8.0;
+/// How long we hold onto the frame of a streamed animation before 
+/// looking again when the next frame wasn't decoded in time.
+const STREAMED_FRAME_RETRY_DELAY: Duration = Duration::from_millis(5);
+
+/// What shows through the transparent parts of an image.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TransparencyBackground {
+    /// The grey checkerboard most image editors use.
+    Checkerboard,
+    /// A single solid colour.
+    Solid(Color32),
+    /// Nothing, so the theme's background shows through.
+    Theme,
+}
+
 pub struct Viewport {
     pub zoom: f32,
     offset: Vec2,
@@ -36,6 +55,12 @@ pub struct Viewport {
 
     last_window_size: Vec2,
     last_fit_to_window_image_scale: f32,
+
+    /// Where on screen the image was last painted.
+    pub image_rect: Rect,
+
+    /// The checkerboard texture and whether it was made for dark mode.
+    checkerboard_texture: Option<(bool, TextureHandle)>,
 }
 
 impl Viewport {
@@ -60,6 +85,10 @@ impl Viewport {
 
             last_window_size: Vec2::ZERO,
             last_fit_to_window_image_scale: 1.0,
+
+            image_rect: Rect::NOTHING,
+
+            checkerboard_texture: None,
         }
     }
 
@@ -98,12 +127,14 @@ impl Viewport {
         ui: &mut Ui,
         image_size: &ImageSize,
         image_resource: ImageResource,
+        (page, page_count): (usize, usize),
         notifier: &mut Notifier,
         padding: f32,
         zoom_into_cursor: bool,
         fit_to_window: bool,
         animate_fit_to_window: bool,
         animate_reset: bool,
+        transparency_background: TransparencyBackground,
         reset_viewport_key: &String,
     ) {
         let window_size = ui.input(|i: &egui::InputState| i.viewport_rect()).size();
@@ -248,10 +279,106 @@ impl Viewport {
 
                 egui::Image::from_texture(texture)
             },
-        }.corner_radius(10.0); // TODO: config to customize image corner radius.
+            ImageResource::StreamedAnimation(streamed_animation) => {
+                let mut streamed_animation = streamed_animation.lock().unwrap();
+
+                let delay = streamed_animation.delay();
+
+                ui.ctx().request_repaint_after_secs(delay / 4.0);
+
+                match &mut self.animated_image_schedule {
+                    Some(schedule) => {
+                        if schedule.update().is_some() {
+                            // if the decoder has fallen behind we stay on this frame a little 
+                            // longer rather than blocking the ui until the next one is ready.
+                            self.animated_image_schedule = match streamed_animation.next_frame() {
+                                true => None,
+                                false => {
+                                    ui.ctx().request_repaint_after(STREAMED_FRAME_RETRY_DELAY);
+
+                                    Some(Scheduler::new(|| {}, STREAMED_FRAME_RETRY_DELAY))
+                                },
+                            };
+                        }
+                    },
+                    None => {
+                        self.animated_image_schedule = Some(
+                            Scheduler::new(
+                                || {},
+                                Duration::from_secs_f32(delay)
+                            )
+                        )
+                    },
+                }
+
+                egui::Image::from_texture(streamed_animation.texture())
+            },
+        }.corner_radius(IMAGE_CORNER_RADIUS);
+
+        self.paint_transparency_background(ui, image_rect, transparency_background);
 
         // Drawing the image to the viewport.
         egui_image.paint_at(ui, image_rect);
+
+        self.image_rect = image_rect;
+
+        if page_count > 1 {
+            Self::show_page_indicator(ui, page, page_count);
+        }
+    }
+
+    fn paint_transparency_background(&mut self, ui: &Ui, image_rect: Rect, transparency_background: TransparencyBackground) {
+        match transparency_background {
+            TransparencyBackground::Checkerboard => {
+                let dark_mode = ui.visuals().dark_mode;
+
+                let texture = match &self.checkerboard_texture {
+                    Some((texture_dark_mode, texture)) if *texture_dark_mode == dark_mode => texture.clone(),
+                    _ => {
+                        debug!("Creating transparency checkerboard texture...");
+
+                        let (light, dark) = match dark_mode {
+                            true => (Color32::from_gray(60), Color32::from_gray(40)),
+                            false => (Color32::from_gray(255), Color32::from_gray(204)),
+                        };
+
+                        // a single 2x2 checker that the gpu repeats across the whole image.
+                        let texture = ui.ctx().load_texture(
+                            "transparency_checkerboard",
+                            ColorImage::new([2, 2], vec![light, dark, dark, light]),
+                            TextureOptions::NEAREST_REPEAT
+                        );
+
+                        self.checkerboard_texture = Some((dark_mode, texture.clone()));
+
+                        texture
+                    },
+                };
+
+                // uv coordinates past 1.0 repeat the texture, which is two squares wide.
+                let uv_size = image_rect.size() / (CHECKERBOARD_SQUARE_SIZE * 2.0);
+
+                egui::Image::from_texture(&texture)
+                    .uv(Rect::from_min_size(Pos2::ZERO, uv_size))
+                    .corner_radius(IMAGE_CORNER_RADIUS)
+                    .paint_at(ui, image_rect);
+            },
+            TransparencyBackground::Solid(colour) => {
+                ui.painter().rect_filled(image_rect, IMAGE_CORNER_RADIUS, colour);
+            },
+            TransparencyBackground::Theme => {},
+        }
+    }
+
+    fn show_page_indicator(ui: &Ui, page: usize, page_count: usize) {
+        egui::Window::new("page_indicator_window")
+            .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 16.0))
+            .title_bar(false)
+            .resizable(false)
+            .interactable(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Page {} / {}", page + 1, page_count));
+            });
     }
 
     fn pan_and_zoom_reset_update(
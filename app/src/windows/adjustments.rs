@@ -0,0 +1,95 @@
+use eframe::egui::{self, Response};
+use eframe::egui::{Pos2, RichText, Slider, Ui, WidgetText};
+
+use crate::{image::adjustments::ColourAdjustments, image_loader::uploading::UploadedImage};
+
+/// Sliders for the exposure, contrast, gamma, saturation and white balance of the image.
+pub struct AdjustmentsWindow {
+    adjustments: ColourAdjustments,
+    image_hash: Option<u64>,
+
+    /// The adjustments the user changed the sliders to, waiting for the image loader to pick them up.
+    changed_adjustments: Option<ColourAdjustments>,
+}
+
+impl AdjustmentsWindow {
+    pub fn new() -> Self {
+        Self {
+            adjustments: ColourAdjustments::default(),
+            image_hash: None,
+
+            changed_adjustments: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &Ui, uploaded_image: &UploadedImage, show: &mut bool) -> Response {
+        // the sliders start from wherever a different image (or page) has been adjusted to.
+        if self.image_hash != Some(uploaded_image.image_hash) {
+            self.image_hash = Some(uploaded_image.image_hash);
+            self.adjustments = uploaded_image.image.adjustments;
+            self.changed_adjustments = None;
+        }
+
+        let mut adjustments = self.adjustments;
+
+        let window = egui::Window::new(
+            WidgetText::RichText(
+                RichText::new("🎨 Adjustments").size(15.0).into()
+            )
+        );
+
+        let response = window.default_pos(Pos2::new(200.0, 200.0))
+            .min_width(250.0)
+            .resizable(false)
+            .fade_in(false)
+            .fade_out(false)
+            .open(show)
+            .show(ui.ctx(), |ui| {
+                egui::Grid::new("adjustments_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Exposure");
+                        ui.add(Slider::new(&mut adjustments.exposure, -5.0..=5.0).suffix(" EV"));
+                        ui.end_row();
+
+                        ui.label("Contrast");
+                        ui.add(Slider::new(&mut adjustments.contrast, 0.0..=2.0));
+                        ui.end_row();
+
+                        ui.label("Gamma");
+                        ui.add(Slider::new(&mut adjustments.gamma, 0.2..=3.0));
+                        ui.end_row();
+
+                        ui.label("Saturation");
+                        ui.add(Slider::new(&mut adjustments.saturation, 0.0..=2.0));
+                        ui.end_row();
+
+                        ui.label("Temperature");
+                        ui.add(Slider::new(&mut adjustments.temperature, -1.0..=1.0));
+                        ui.end_row();
+
+                        ui.label("Tint");
+                        ui.add(Slider::new(&mut adjustments.tint, -1.0..=1.0));
+                        ui.end_row();
+                    });
+
+                ui.separator();
+
+                if ui.add_enabled(!adjustments.is_neutral(), egui::Button::new("Reset")).clicked() {
+                    adjustments = ColourAdjustments::default();
+                }
+            });
+
+        if adjustments != self.adjustments {
+            self.adjustments = adjustments;
+            self.changed_adjustments = Some(adjustments);
+        }
+
+        response.unwrap().response
+    }
+
+    pub fn take_adjustments(&mut self) -> Option<ColourAdjustments> {
+        self.changed_adjustments.take()
+    }
+}
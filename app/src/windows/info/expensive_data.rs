@@ -101,6 +101,10 @@ impl ExpensiveData {
 
                     size as f64
                 },
+                // only the one frame is ever on the gpu.
+                ImageResource::StreamedAnimation(streamed_animation) => {
+                    streamed_animation.lock().unwrap().texture().byte_size() as f64
+                },
             },
 
             location: Arc::new(Mutex::new(None)),
@@ -107,18 +107,22 @@ impl ImageInfoWindow {
 
                         match show_extra {
                             true => {
-                                let texture_handle: Option<&TextureHandle> = match &uploaded_image.resource {
-                                    ImageResource::Texture(texture_handle) => Some(texture_handle),
+                                let texture_handle: Option<TextureHandle> = match &uploaded_image.resource {
+                                    ImageResource::Texture(texture_handle) => Some(texture_handle.clone()),
                                     ImageResource::AnimatedTexture(frames) => {
                                         frames.get(0)
                                             .and_then(
-                                                |(texture_handle,_)| Some(texture_handle)
+                                                |(texture_handle,_)| Some(texture_handle.clone())
                                             )
-                                    }
+                                    },
+                                    // shows whatever frame the animation is on.
+                                    ImageResource::StreamedAnimation(streamed_animation) => {
+                                        Some(streamed_animation.lock().unwrap().texture().clone())
+                                    },
                                 };
 
                                 ui.vertical(|ui| {
-                                    if let Some(texture) = texture_handle {
+                                    if let Some(texture) = &texture_handle {
                                         ui.add(
                                             egui::Image::from_texture(texture)
                                                 // 16 is the padding from
@@ -251,6 +255,22 @@ impl ImageInfoWindow {
                             );
                             ui.end_row();
                         }
+
+                        if image_optimizations.raw_embedded_preview {
+                            ui_non_select_label(ui, "Raw embedded preview:");
+                            ui.label("enabled");
+                            ui.end_row();
+                        }
+
+                        if image_optimizations.progressive_decoding {
+                            ui_non_select_label(ui, "Progressive decoding:");
+                            ui.label("enabled");
+                            ui.end_row();
+                        }
+
+                        ui_non_select_label(ui, "Resize filter:");
+                        ui.label(format!("{:?}", image_optimizations.resize_filter));
+                        ui.end_row();
                     });
 
             }).response
@@ -289,10 +309,50 @@ impl ImageInfoWindow {
                 ui.label(format!("{}", image.format));
                 ui.end_row();
 
+                if let Some(document) = &image_info.document {
+                    ui_non_select_label(ui, "Layers:")
+                        .on_hover_text(
+                            "Only the flattened composite of the document is displayed."
+                        );
+                    ui.label(
+                        match document.layer_count {
+                            0 => RichText::new("None (flattened)").weak(),
+                            layer_count => RichText::new(layer_count.to_string()),
+                        }
+                    );
+                    ui.end_row();
+
+                    if show_extra {
+                        ui_non_select_label(ui, "Document Size:");
+                        ui.label(
+                            format!(
+                                "{}x{}", document.size.0, document.size.1
+                            )
+                        );
+                        ui.end_row();
+                    }
+                }
+
                 if show_extra {
                     ui_non_select_label(ui, "Colour:");
                     ui.label(format!("{}", image_info.colour_type));
                     ui.end_row();
+
+                    ui_non_select_label(ui, "Colour Profile:")
+                        .on_hover_text(
+                            "The ICC profile embedded into the image. Images \
+                                with one are converted to sRGB for display."
+                        );
+                    ui.label(
+                        match &image_info.colour_profile {
+                            Some(colour_profile) => match &colour_profile.description {
+                                Some(description) => RichText::new(description),
+                                None => RichText::new("Unnamed").weak(),
+                            },
+                            None => RichText::new("None (sRGB assumed)").weak(),
+                        }
+                    );
+                    ui.end_row();
                 }
 
                 ui_non_select_label(ui, "Created:")
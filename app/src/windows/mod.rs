@@ -5,20 +5,24 @@ use cirrus_soft_binds::egui::parse_and_get_egui_input_reader_from_string;
 use eframe::egui::{Context, InputState, Key, Rect, Ui};
 use egui_notify::ToastLevel;
 
-use crate::{image_loader::{optimization::ImageOptimizations, uploading::UploadedImage}, monitor_size::MonitorSize, windows::info::ImageInfoWindow};
+use crate::{image::adjustments::ColourAdjustments, image_loader::{optimization::ImageOptimizations, uploading::UploadedImage}, monitor_size::MonitorSize, windows::{adjustments::AdjustmentsWindow, info::ImageInfoWindow}};
 
 mod info;
+mod adjustments;
 
 type InputReader = Box<dyn FnMut(&InputState) -> bool>;
 
 pub struct WindowsManager {
     info_window: ImageInfoWindow,
+    adjustments_window: AdjustmentsWindow,
 
     show_info_reader: Option<InputReader>,
     show_extra_info_reader: Option<InputReader>,
+    show_adjustments_reader: Option<InputReader>,
 
     pub show_info: bool,
     pub show_extra_info: bool,
+    pub show_adjustments: bool,
 
     pub rect: Rect
 }
@@ -26,15 +30,19 @@ pub struct WindowsManager {
 impl WindowsManager {
     pub fn new() -> Self {
         let info_window = ImageInfoWindow::new();
+        let adjustments_window = AdjustmentsWindow::new();
 
         Self {
             info_window,
+            adjustments_window,
 
             show_info_reader: None,
             show_extra_info_reader: None,
+            show_adjustments_reader: None,
 
             show_info: false,
             show_extra_info: false,
+            show_adjustments: false,
 
             rect: Rect::NOTHING
         }
@@ -45,7 +53,8 @@ impl WindowsManager {
         ctx: &Context,
         notifier: &mut Notifier,
         show_image_info_key: &String,
-        show_extra_image_info_key: &String
+        show_extra_image_info_key: &String,
+        show_adjustments_key: &String
     ) {
         // TODO: put this into some nice function without loosing too much control somehow.
         let show_info_reader = self.show_info_reader.get_or_insert_with(|| {
@@ -84,6 +93,40 @@ impl WindowsManager {
             }
         });
 
+        let show_adjustments_reader = self.show_adjustments_reader.get_or_insert_with(|| {
+            match parse_and_get_egui_input_reader_from_string(
+                show_adjustments_key,
+                |i, key| i.key_pressed(key)
+            ) {
+                Ok(reader) => Box::new(reader),
+                Err(error) => {
+                    notifier.toast(
+                        error.to_string(),
+                        ToastLevel::Error,
+                        |_| {}
+                    );
+
+                    Box::new(|i| i.key_pressed(Key::B))
+                },
+            }
+        });
+
+        if ctx.input(show_adjustments_reader) {
+            self.show_adjustments = !self.show_adjustments;
+
+            notifier.show_banner(
+                BannerText::new(
+                    match self.show_adjustments {
+                        true => format!("Show adjustments ({show_adjustments_key})"),
+                        false => format!("Hide adjustments ({show_adjustments_key})"),
+                    },
+                    None
+                ),
+                BannerPlacement::BOTTOM,
+                Duration::from_secs(2)
+            );
+        }
+
         let show_extra_info = ctx.input(show_extra_info_reader);
 
         if ctx.input(show_info_reader) || show_extra_info {
@@ -134,6 +177,21 @@ impl WindowsManager {
             new_rect = new_rect.union(response.rect);
         }
 
+        if self.show_adjustments {
+            let response = self.adjustments_window.show(
+                ui,
+                uploaded_image,
+                &mut self.show_adjustments,
+            );
+
+            new_rect = new_rect.union(response.rect);
+        }
+
         self.rect = new_rect;
     }
+
+    /// The colour adjustments the user has changed the sliders of the adjustments window to, if they have.
+    pub fn take_adjustments(&mut self) -> Option<ColourAdjustments> {
+        self.adjustments_window.take_adjustments()
+    }
 }
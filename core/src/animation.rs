@@ -0,0 +1,99 @@
+use std::{fmt::Debug, sync::{Arc, Mutex, mpsc::{self, Receiver}}, thread};
+
+use crate::{error::{Error, Result}, pixels::Pixels};
+
+/// A frame of an animation and how long (in seconds) it's shown for.
+pub type AnimationFrame = (Pixels, f32);
+
+/// When animations are streamed instead of having every one of their frames decoded up front.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationStreaming {
+    /// Animations that would need more than this many bytes to hold every frame get streamed,
+    /// anything smaller is decoded all at once as usual so it plays back from memory.
+    pub max_cached_bytes: u64,
+    /// How many decoded frames are kept ready ahead of the one being shown.
+    pub frames_ahead: usize,
+}
+
+/// The frames of an animation, decoded one by one on a background thread as they're taken.
+///
+/// The frames go through a bounded queue (our ring buffer) so the thread is never more than
+/// `frames_ahead` frames ahead of whoever is playing it back, memory stays the same however
+/// long the animation is. Once the last frame is decoded it starts again from the first.
+///
+/// Clones share the same stream, the thread stops once every clone has been dropped.
+#[derive(Clone)]
+pub struct AnimationStream {
+    receiver: Arc<Mutex<Receiver<Result<AnimationFrame>>>>,
+    frames_ahead: usize,
+}
+
+impl AnimationStream {
+    /// Spawns the thread that decodes the frames. Every time round, `decode_frames` has to decode the
+    /// animation from the start, handing each frame over until it's told to stop (by `false` being returned).
+    pub(crate) fn spawn<F>(frames_ahead: usize, mut decode_frames: F) -> Self
+    where
+        F: FnMut(&mut dyn FnMut(AnimationFrame) -> bool) -> Result<()> + Send + 'static,
+    {
+        let frames_ahead = frames_ahead.max(1);
+        let (sender, receiver) = mpsc::sync_channel(frames_ahead);
+
+        thread::spawn(move || {
+            loop {
+                let mut frame_count = 0;
+                let mut is_streaming = true;
+
+                // sending blocks while the queue is full, which is what keeps us only a few frames ahead.
+                let result = decode_frames(
+                    &mut |frame| {
+                        frame_count += 1;
+                        is_streaming = sender.send(Ok(frame)).is_ok();
+
+                        is_streaming
+                    }
+                );
+
+                if !is_streaming {
+                    log::debug!("Animation stream was dropped, stopping the decoding of it's frames...");
+                    break;
+                }
+
+                let error = match result {
+                    Ok(()) if frame_count > 0 => continue,
+                    Ok(()) => Error::AnimatedImageHasNoFrames,
+                    Err(error) => error,
+                };
+
+                let _ = sender.send(Err(error));
+                break;
+            }
+        });
+
+        Self {
+            receiver: Arc::new(Mutex::new(receiver)),
+            frames_ahead,
+        }
+    }
+
+    /// Waits for the next frame to be decoded, `None` if the stream has ended (it only ends on an error).
+    pub fn next_frame(&self) -> Option<Result<AnimationFrame>> {
+        self.receiver.lock().unwrap().recv().ok()
+    }
+
+    /// The next frame if it's been decoded already, otherwise `None` without waiting for it.
+    pub fn try_next_frame(&self) -> Option<Result<AnimationFrame>> {
+        self.receiver.lock().unwrap().try_recv().ok()
+    }
+
+    pub fn frames_ahead(&self) -> usize {
+        self.frames_ahead
+    }
+}
+
+impl Debug for AnimationStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnimationStream")
+            .field("frames_ahead", &self.frames_ahead)
+            .finish_non_exhaustive()
+    }
+}
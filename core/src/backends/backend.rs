@@ -1,4 +1,4 @@
-use crate::{decoded_image::DecodedImage, error::Result, format::ImageFormat, modifications::ImageModification, reader::ImageReader};
+use crate::{decoded_image::{DecodedImage, ImageSize, PageInfo}, error::Result, format::ImageFormat, modifications::ImageModification, reader::ImageReader};
 
 pub trait DecodeBackend {
     const SUPPORTED_FORMATS: &[ImageFormat];
@@ -10,4 +10,47 @@ pub trait DecodeBackend {
     // We use "self" instead of "&mut self", as decode will always be the final function call on this struct.
     // After this function call and once we've receive "DecodedImage" we no longer need this struct any more.
     fn decode(self) -> Result<DecodedImage>;
-}
\ No newline at end of file
+    /// Like [`DecodeBackend::decode`] but `on_snapshot` is handed snapshots of the partially 
+    /// decoded image along the way (every few rows, interlace pass or progressive jpeg scan) so they can be shown early.
+    /// 
+    /// Backends (and formats) that can't be decoded a bit at a time never call `on_snapshot`.
+    fn decode_progressively<F>(self, _on_snapshot: F) -> Result<DecodedImage>
+    where
+        F: FnMut(DecodedImage),
+        Self: Sized,
+    {
+        self.decode()
+    }
+}
+
+/// Backends that can decode images which hold more than one image, like multi-page 
+/// tiffs and icos (we call each of an ico's entries a page too).
+pub trait MultiPageDecodeBackend: DecodeBackend {
+    /// The size and colour type of each page, in the order they get decoded. 
+    /// 
+    /// This is empty if the image is not made up of multiple pages.
+    fn pages(&self) -> Vec<PageInfo>;
+    /// Narrows decoding down to this one page so [`DecodeBackend::decode`] 
+    /// returns it as a static image instead of returning every page.
+    fn select_page(&mut self, index: usize) -> Result<()>;
+}
+
+/// Backends that can decode some formats straight at a fraction of their size (e.g. jpegs at 1/2, 
+/// 1/4 and 1/8 by only running part of the IDCT), which is a lot quicker and lighter on memory 
+/// than decoding the whole thing and resizing it after. These backends satisfy 
+/// [`ImageModification::Resize`] by decoding at the nearest larger scale and resizing the rest of the way.
+pub trait ScaledDecodeBackend: DecodeBackend {
+    /// The scales this image can be decoded at as denominators (`4` being 1/4), `1` is always one of them.
+    fn decode_scales(&self) -> &'static [u32];
+}
+
+/// Picks the smallest of `decode_scales` that still decodes the image at `target_size` or bigger.
+pub(crate) fn pick_decode_scale(decode_scales: &[u32], image_size: ImageSize, target_size: ImageSize) -> u32 {
+    decode_scales.iter()
+        .copied()
+        .filter(|scale| {
+            image_size.0.div_ceil(*scale) >= target_size.0 && image_size.1.div_ceil(*scale) >= target_size.1
+        })
+        .max()
+        .unwrap_or(1)
+}
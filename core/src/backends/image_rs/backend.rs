@@ -1,7 +1,7 @@
-use std::{collections::HashSet};
+use std::io::Cursor;
 
 use image::{
-    AnimationDecoder, ImageDecoder, ImageError, codecs::{
+    AnimationDecoder, Frame, Frames, ImageDecoder, ImageError, codecs::{
         gif::GifDecoder, jpeg::JpegDecoder, png::PngDecoder, webp::WebPDecoder, qoi::QoiDecoder
     },
 };
@@ -11,20 +11,25 @@ use image::codecs::avif::AvifDecoder;
 
 #[cfg(feature = "image-rs-extra-formats")]
 use image::codecs::{
-    bmp::BmpDecoder, ico::IcoDecoder, tiff::TiffDecoder
+    bmp::BmpDecoder, ico::IcoDecoder, tiff::TiffDecoder, openexr::OpenExrDecoder, hdr::HdrDecoder,
+    pnm::PnmDecoder, tga::TgaDecoder, farbfeld::FarbfeldDecoder
 };
 
+#[cfg(feature = "image-rs-extra-formats")]
+use crate::backends::image_rs::{codecs::{dds::DdsDecoder, open_raster, pfm::PfmDecoder}, multi_page::{MultiPageImage, PageSource}};
+
 use log::debug;
 
 use crate::{
-    backends::{backend::DecodeBackend, image_rs::buffer_image::{BufferImage, BufferImageVariant}}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent, ImageSize}, error::{Error, Result}, format::ImageFormat, image_info::metadata::ImageMetadata, modifications::{ImageModification, ImageModifications}, pixels::Pixels, reader::{EncodedImageReader, ImageReader, ImageReaderData}
+    animation::{AnimationStream, AnimationStreaming}, backends::{backend::{DecodeBackend, MultiPageDecodeBackend, ScaledDecodeBackend, pick_decode_scale}, image_rs::{buffer_image::{BufferImage, BufferImageVariant}, scaled::JPEG_DECODE_SCALES}}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent, ImageSize, Page, PageInfo}, error::{Error, Result}, format::ImageFormat, image_info::{colour_profile::ColourProfile, metadata::ImageMetadata}, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, orientation::{ImageOrientation, orientation_to_decode_with}, pixels::Pixels, processing::{adjustments::adjust_pixels, colour_management::convert_frame_to_srgb}, reader::{EncodedImageBytes, EncodedImageReader, ImageReader, ImageReaderData}
 };
 
 // TODO: Fill with debug logs
 
 enum Decoder {
-    Png(PngDecoder<EncodedImageReader>),
-    Jpeg(JpegDecoder<EncodedImageReader>),
+    /// Png is kept encoded until we decode as it can also be decoded progressively.
+    Png(EncodedImageReader),
+    Jpeg(EncodedImageReader),
     Webp(WebPDecoder<EncodedImageReader>),
     Gif(GifDecoder<EncodedImageReader>),
     Qoi(QoiDecoder<EncodedImageReader>),
@@ -36,23 +41,60 @@ enum Decoder {
     Bmp(BmpDecoder<EncodedImageReader>),
     #[cfg(feature = "image-rs-extra-formats")]
     Ico(IcoDecoder<EncodedImageReader>),
+    // the exr decoder is a lot larger than the rest so we keep it on the heap.
+    #[cfg(feature = "image-rs-extra-formats")]
+    Exr(Box<OpenExrDecoder<EncodedImageReader>>),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Hdr(HdrDecoder<EncodedImageReader>),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Pnm(PnmDecoder<EncodedImageReader>),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Pfm(PfmDecoder),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Tga(TgaDecoder<EncodedImageReader>),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Dds(DdsDecoder),
+    #[cfg(feature = "image-rs-extra-formats")]
+    Farbfeld(FarbfeldDecoder<EncodedImageReader>),
+    /// The flattened png inside of the OpenRaster zip.
+    #[cfg(feature = "image-rs-extra-formats")]
+    OpenRaster(PngDecoder<EncodedImageReader>),
 }
 
 enum Buffer {
     Image(BufferImage),
     Animation((Vec<(BufferImage, f32)>, ImageSize, ImageColourType)),
+    Pages(Vec<BufferImage>),
 }
 
 enum Source {
     Decoder(Decoder),
     Buffer(Buffer),
+    /// Multi-page tiffs and icos, each page gets it's own decoder when it's decoded.
+    #[cfg(feature = "image-rs-extra-formats")]
+    MultiPage(MultiPageImage),
 }
 
 pub struct ImageRSBackend {
     source: Source,
-    modifications: ImageModifications,
+    modifications: ModificationPipeline,
     image_exif_chunk: Option<Vec<u8>>,
+    image_icc_profile: Option<Vec<u8>>,
     image_format: ImageFormat,
+    limits: DecodeLimits,
+    orientation: ImageOrientation,
+    /// Kept so animations can be streamed, a new decoder is made over them every time they loop.
+    encoded_image_bytes: Option<EncodedImageBytes>,
+    animation_streaming: Option<AnimationStreaming>,
+}
+
+/// What an animation needs to be streamed, if it turns out to be too big to be decoded all at once.
+struct StreamSource {
+    animation_streaming: AnimationStreaming,
+    encoded_image_bytes: EncodedImageBytes,
+    colour_profile: Option<ColourProfile>,
+    /// Made to each frame once it's sRGB, like they are to any other image.
+    adjustments: Vec<ImageModification>,
 }
 
 impl DecodeBackend for ImageRSBackend {
@@ -70,23 +112,93 @@ impl DecodeBackend for ImageRSBackend {
         ImageFormat::Bmp,
         #[cfg(feature = "image-rs-extra-formats")]
         ImageFormat::Ico,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Exr,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Hdr,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Pnm,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Pfm,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Tga,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Dds,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::Farbfeld,
+        #[cfg(feature = "image-rs-extra-formats")]
+        ImageFormat::OpenRaster,
     ];
 
-    // TODO: allow user to set and change memory allocation limitations on decoders 
-    // and return an special error when the decoder fails due to it exceeding that limit.
     fn from_reader(image_reader: ImageReader) -> Result<Self> {
         match image_reader.data {
             ImageReaderData::EncodedImage(cursor) => {
                 log::debug!("Initializing image-rs backend decoders with buf reader...");
 
+                let encoded_image_bytes = cursor.get_ref().clone();
+
                 let error_func = |error: ImageError| Error::DecoderInitFailure {
                     error: error.to_string(),
                 };
 
+                #[cfg(feature = "image-rs-extra-formats")]
+                let cursor = {
+                    let page_sources = PageSource::find_all(cursor.get_ref(), &image_reader.image_format);
+
+                    if page_sources.len() > 1 {
+                        log::debug!("Image has {} pages, initializing a decoder for each page...", page_sources.len());
+
+                        // the page decoders share the encoded image bytes, only the tiff's header gets copied.
+                        let multi_page_image = MultiPageImage::new(cursor.get_ref().clone(), page_sources);
+
+                        // with one page (or none) left that we can decode there's no pages to pick between.
+                        if multi_page_image.pages.len() > 1 {
+                            let first_page_source = multi_page_image.pages[0].0;
+                            let mut first_page_decoder = multi_page_image.page_decoder(first_page_source)?;
+
+                            let exif_chunk = first_page_decoder.exif_metadata()
+                                .map_err(|error| Error::DecoderRetrieveExifFailure { error: error.to_string() })?;
+
+                            // the pages are all taken to be in the first page's colour profile.
+                            let icc_profile = Self::icc_profile_or_warn(first_page_decoder.icc_profile());
+
+                            return Ok(
+                                Self {
+                                    source: Source::MultiPage(multi_page_image),
+                                    modifications: ModificationPipeline::new(),
+                                    orientation: orientation_to_decode_with(exif_chunk.as_deref(), image_reader.auto_orientation),
+                                    image_exif_chunk: exif_chunk,
+                                    image_icc_profile: icc_profile,
+                                    image_format: image_reader.image_format,
+                                    limits: image_reader.limits,
+                                    encoded_image_bytes: Some(encoded_image_bytes),
+                                    animation_streaming: None,
+                                }
+                            );
+                        }
+
+                        log::debug!(
+                            "Only {} of the image's pages can be decoded, decoding it as a single page image...",
+                            multi_page_image.pages.len()
+                        );
+                    }
+
+                    cursor
+                };
+
                 let mut image_decoder = match image_reader.image_format {
                     ImageFormat::Gif => Decoder::Gif(GifDecoder::new(cursor).map_err(error_func)?),
-                    ImageFormat::Png => Decoder::Png(PngDecoder::new(cursor).map_err(error_func)?),
-                    ImageFormat::Jpeg => Decoder::Jpeg(JpegDecoder::new(cursor).map_err(error_func)?),
+                    ImageFormat::Png => {
+                        PngDecoder::new(Cursor::new(cursor.get_ref().as_slice())).map_err(error_func)?;
+
+                        Decoder::Png(cursor)
+                    },
+                    ImageFormat::Jpeg => {
+                        // jpegs may be decoded at a reduced scale by another decoder so we hold onto the cursor.
+                        JpegDecoder::new(Cursor::new(cursor.get_ref().as_slice())).map_err(error_func)?;
+
+                        Decoder::Jpeg(cursor)
+                    },
                     ImageFormat::Webp => Decoder::Webp(WebPDecoder::new(cursor).map_err(error_func)?),
                     ImageFormat::Qoi => Decoder::Qoi(QoiDecoder::new(cursor).map_err(error_func)?),
                     #[cfg(feature = "native-formats")]
@@ -97,6 +209,29 @@ impl DecodeBackend for ImageRSBackend {
                     ImageFormat::Bmp => Decoder::Bmp(BmpDecoder::new(cursor).map_err(error_func)?),
                     #[cfg(feature = "image-rs-extra-formats")]
                     ImageFormat::Ico => Decoder::Ico(IcoDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Exr => Decoder::Exr(Box::new(OpenExrDecoder::new(cursor).map_err(error_func)?)),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Hdr => Decoder::Hdr(HdrDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Pnm => Decoder::Pnm(PnmDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Pfm => Decoder::Pfm(PfmDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Tga => Decoder::Tga(TgaDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Dds => Decoder::Dds(DdsDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::Farbfeld => Decoder::Farbfeld(FarbfeldDecoder::new(cursor).map_err(error_func)?),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    ImageFormat::OpenRaster => {
+                        let merged_image = open_raster::extract_merged_image(cursor.get_ref())
+                            .ok_or_else(|| Error::DecoderInitFailure {
+                                error: String::from("OpenRaster file has no readable 'mergedimage.png'!"),
+                            })?;
+
+                        Decoder::OpenRaster(PngDecoder::new(EncodedImageReader::new(merged_image.into())).map_err(error_func)?)
+                    },
                     unsupported_format => {
                         return Err(
                             Error::DecoderImageFormatNotSupported {
@@ -108,8 +243,10 @@ impl DecodeBackend for ImageRSBackend {
                 };
 
                 let exif_chunk = match &mut image_decoder {
-                    Decoder::Png(png_decoder) => png_decoder.exif_metadata(),
-                    Decoder::Jpeg(jpeg_decoder) => jpeg_decoder.exif_metadata(),
+                    Decoder::Png(cursor) => PngDecoder::new(Cursor::new(cursor.get_ref().as_slice()))
+                        .and_then(|mut png_decoder| png_decoder.exif_metadata()),
+                    Decoder::Jpeg(cursor) => JpegDecoder::new(Cursor::new(cursor.get_ref().as_slice()))
+                        .and_then(|mut jpeg_decoder| jpeg_decoder.exif_metadata()),
                     Decoder::Webp(web_pdecoder) => web_pdecoder.exif_metadata(),
                     Decoder::Gif(gif_decoder) => gif_decoder.exif_metadata(),
                     Decoder::Qoi(qoi_decoder) => qoi_decoder.exif_metadata(),
@@ -121,14 +258,51 @@ impl DecodeBackend for ImageRSBackend {
                     Decoder::Bmp(bmp_decoder) => bmp_decoder.exif_metadata(),
                     #[cfg(feature = "image-rs-extra-formats")]
                     Decoder::Ico(ico_decoder) => ico_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Exr(exr_decoder) => exr_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Hdr(hdr_decoder) => hdr_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Pnm(pnm_decoder) => pnm_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Pfm(pfm_decoder) => pfm_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Tga(tga_decoder) => tga_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Dds(dds_decoder) => dds_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::Farbfeld(farbfeld_decoder) => farbfeld_decoder.exif_metadata(),
+                    #[cfg(feature = "image-rs-extra-formats")]
+                    Decoder::OpenRaster(png_decoder) => png_decoder.exif_metadata(),
                 }.map_err(|error| Error::DecoderRetrieveExifFailure { error: error.to_string() })?;
 
+                // only the formats that actually embed an icc profile are asked for one.
+                let icc_profile = Self::icc_profile_or_warn(
+                    match &mut image_decoder {
+                        Decoder::Png(cursor) => PngDecoder::new(Cursor::new(cursor.get_ref().as_slice()))
+                            .and_then(|mut png_decoder| png_decoder.icc_profile()),
+                        Decoder::Jpeg(cursor) => JpegDecoder::new(Cursor::new(cursor.get_ref().as_slice()))
+                            .and_then(|mut jpeg_decoder| jpeg_decoder.icc_profile()),
+                        Decoder::Webp(webp_decoder) => webp_decoder.icc_profile(),
+                        #[cfg(feature = "native-formats")]
+                        Decoder::Avif(avif_decoder) => avif_decoder.icc_profile(),
+                        #[cfg(feature = "image-rs-extra-formats")]
+                        Decoder::Tiff(tiff_decoder) => tiff_decoder.icc_profile(),
+                        _ => Ok(None),
+                    }
+                );
+
                 Ok(
                     Self {
                         source: Source::Decoder(image_decoder),
-                        modifications: HashSet::new(),
+                        modifications: ModificationPipeline::new(),
+                        orientation: orientation_to_decode_with(exif_chunk.as_deref(), image_reader.auto_orientation),
                         image_exif_chunk: exif_chunk,
-                        image_format: image_reader.image_format
+                        image_icc_profile: icc_profile,
+                        image_format: image_reader.image_format,
+                        limits: image_reader.limits,
+                        encoded_image_bytes: Some(encoded_image_bytes),
+                        animation_streaming: None,
                     }
                 )
             },
@@ -147,10 +321,16 @@ impl DecodeBackend for ImageRSBackend {
                         Ok(
                             Self {
                                 source: Source::Buffer(Buffer::Image(image_buffer)),
-                                modifications: HashSet::new(),
+                                modifications: ModificationPipeline::new(),
                                 image_exif_chunk: None, // decoded image should 
                                 // contain it so we don't need the chunk no more
-                                image_format: image_reader.image_format
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                                encoded_image_bytes: None,
+                                animation_streaming: None,
                             }
                         )
                     },
@@ -176,12 +356,44 @@ impl DecodeBackend for ImageRSBackend {
                                         decoded_image.colour_type
                                     ))
                                 ),
-                                modifications: HashSet::new(),
+                                modifications: ModificationPipeline::new(),
                                 image_exif_chunk: None,
-                                image_format: image_reader.image_format
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                                encoded_image_bytes: None,
+                                animation_streaming: None,
                             }
                         )
-                    }
+                    },
+                    DecodedImageContent::Pages(pages) => {
+                        let mut page_buffers = Vec::new();
+
+                        for page in pages {
+                            page_buffers.push(
+                                BufferImage::from_pixels(page.pixels, page.size, page.colour_type)?
+                            );
+                        }
+
+                        Ok(
+                            Self {
+                                source: Source::Buffer(Buffer::Pages(page_buffers)),
+                                modifications: ModificationPipeline::new(),
+                                image_exif_chunk: None,
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                                encoded_image_bytes: None,
+                                animation_streaming: None,
+                            }
+                        )
+                    },
+                    // we can't decode a streamed animation from memory, there's only ever a few of it's frames in memory.
+                    DecodedImageContent::Streamed(_) => Err(Error::StreamedAnimationNotInMemory),
                 }
             }
         }
@@ -194,10 +406,190 @@ impl DecodeBackend for ImageRSBackend {
         self.modifications.extend(modifications);
     }
 
-    fn decode(self) -> Result<DecodedImage> {
+    fn decode(mut self) -> Result<DecodedImage> {
+        // the image is oriented before any of the modifications asked of us are made.
+        self.modifications = self.modifications.oriented_first(self.orientation);
+        let adjustments = self.take_colour_adjustments();
+        let colour_profile = self.image_icc_profile.take().and_then(ColourProfile::new);
+
+        let mut decoded_image = self.decode_source(colour_profile.as_ref(), &adjustments)?;
+
+        // the colours are adjusted once they're sRGB like they would be for any other image.
+        if let Some(colour_profile) = colour_profile {
+            decoded_image.convert_to_srgb(colour_profile);
+        }
+
+        decoded_image.adjust_colours(&adjustments);
+
+        Ok(decoded_image)
+    }
+
+    fn decode_progressively<F>(mut self, mut on_snapshot: F) -> Result<DecodedImage>
+    where
+        F: FnMut(DecodedImage),
+    {
+        match self.source {
+            Source::Decoder(Decoder::Png(cursor)) if !Self::is_animated_png(cursor.get_ref()) => {
+                // like `decode`, the colours are adjusted once they're sRGB.
+                let adjustments = self.modifications.colour_adjustments();
+                self.modifications.retain(|modification| !modification.is_colour_adjustment());
+
+                let colour_profile = self.image_icc_profile.and_then(ColourProfile::new);
+
+                let mut decoded_image = Self::decode_png_progressively(
+                    cursor.into_inner(),
+                    self.modifications.oriented_first(self.orientation),
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits,
+                    |mut snapshot| {
+                        Self::colour_snapshot(&mut snapshot, colour_profile.as_ref(), &adjustments);
+
+                        on_snapshot(snapshot);
+                    }
+                )?;
+
+                if let Some(colour_profile) = colour_profile {
+                    decoded_image.convert_to_srgb(colour_profile);
+                }
+
+                decoded_image.adjust_colours(&adjustments);
+
+                Ok(decoded_image)
+            },
+            Source::Decoder(Decoder::Jpeg(ref cursor)) => {
+                let adjustments = self.modifications.colour_adjustments();
+                let colour_profile = self.image_icc_profile.clone().and_then(ColourProfile::new);
+
+                let mut modifications = self.modifications.oriented_first(self.orientation);
+                modifications.retain(|modification| !modification.is_colour_adjustment());
+
+                Self::hand_out_jpeg_snapshots(
+                    cursor.get_ref().as_slice(),
+                    &modifications,
+                    &self.image_format,
+                    self.limits,
+                    |mut snapshot| {
+                        Self::colour_snapshot(&mut snapshot, colour_profile.as_ref(), &adjustments);
+
+                        on_snapshot(snapshot);
+                    }
+                );
+
+                self.decode()
+            },
+            _ => self.decode(),
+        }
+    }
+}
+
+impl MultiPageDecodeBackend for ImageRSBackend {
+    fn pages(&self) -> Vec<PageInfo> {
+        match &self.source {
+            #[cfg(feature = "image-rs-extra-formats")]
+            Source::MultiPage(multi_page_image) => multi_page_image.pages.iter()
+                .map(|(_, page_info)| page_info.clone())
+                .collect(),
+            Source::Buffer(Buffer::Pages(page_buffers)) => page_buffers.iter()
+                .map(|buffer_image| PageInfo {
+                    size: buffer_image.size,
+                    colour_type: buffer_image.colour_type,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn select_page(&mut self, index: usize) -> Result<()> {
+        let page_count = self.pages().len();
+
+        // images that aren't made up of pages are their own first (and only) page.
+        if page_count == 0 {
+            return match index {
+                0 => Ok(()),
+                _ => Err(Error::PageOutOfRange { index, page_count: 1 }),
+            };
+        }
+
+        if index >= page_count {
+            return Err(Error::PageOutOfRange { index, page_count });
+        }
+
+        match &mut self.source {
+            #[cfg(feature = "image-rs-extra-formats")]
+            Source::MultiPage(multi_page_image) => multi_page_image.selected_page = Some(index),
+            Source::Buffer(Buffer::Pages(page_buffers)) => {
+                let buffer_image = page_buffers.swap_remove(index);
+                self.source = Source::Buffer(Buffer::Image(buffer_image));
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+impl ScaledDecodeBackend for ImageRSBackend {
+    fn decode_scales(&self) -> &'static [u32] {
+        match &self.source {
+            Source::Decoder(Decoder::Jpeg(_)) => JPEG_DECODE_SCALES,
+            _ => &[1],
+        }
+    }
+}
+
+impl ImageRSBackend {
+    /// The scale (as a denominator, `4` being 1/4) an image of `image_format` would be decoded at to get it 
+    /// down to `target_size`, so we can tell before initializing a backend if it's worth handing it the resize.
+    pub fn decode_scale(image_format: &ImageFormat, image_size: ImageSize, target_size: ImageSize) -> u32 {
+        match image_format {
+            ImageFormat::Jpeg => pick_decode_scale(JPEG_DECODE_SCALES, image_size, target_size),
+            _ => 1,
+        }
+    }
+
+    /// Snapshots are shown so they need to be in the same colours as the final image.
+    fn colour_snapshot(snapshot: &mut DecodedImage, colour_profile: Option<&ColourProfile>, adjustments: &[ImageModification]) {
+        if let Some(colour_profile) = colour_profile {
+            snapshot.convert_to_srgb(colour_profile.clone());
+        }
+
+        snapshot.adjust_colours(adjustments);
+    }
+
+    /// Has animations that turn out too big to hold in memory streamed instead 
+    /// (see [`AnimationStreaming`]), `None` (the default) always decodes every frame.
+    pub fn stream_animations(&mut self, animation_streaming: Option<AnimationStreaming>) {
+        self.animation_streaming = animation_streaming;
+    }
+
+    /// Takes the colour adjustments out of the modifications, they're made once the image is sRGB 
+    /// rather than in whatever colour space it's stored in so every backend ends up with the same colours.
+    fn take_colour_adjustments(&mut self) -> Vec<ImageModification> {
+        let adjustments = self.modifications.colour_adjustments();
+        self.modifications.retain(|modification| !modification.is_colour_adjustment());
+
+        adjustments
+    }
+
+    /// Decodes the image and makes the modifications, orientation included (colour adjustments 
+    /// are only made here to the frames of streamed animations, `decode` makes them otherwise).
+    fn decode_source(self, colour_profile: Option<&ColourProfile>, adjustments: &[ImageModification]) -> Result<DecodedImage> {
+        let stream_source = self.animation_streaming.zip(self.encoded_image_bytes)
+            .map(|(animation_streaming, encoded_image_bytes)| StreamSource {
+                animation_streaming,
+                encoded_image_bytes,
+                colour_profile: colour_profile.cloned(),
+                adjustments: adjustments.to_vec(),
+            });
+
         match self.source {
             Source::Decoder(decoder) => match decoder {
-                Decoder::Png(png_decoder) => {
+                Decoder::Png(cursor) => {
+                    let png_decoder = PngDecoder::new(cursor).map_err(
+                        |error| Error::DecoderInitFailure { error: error.to_string() }
+                    )?;
+
                     let has_animation = png_decoder.is_apng().map_err(|error| {
                         Error::DecoderAnimationCheckFailure {
                             error: error.to_string(),
@@ -213,14 +605,17 @@ impl DecodeBackend for ImageRSBackend {
                                 apng_decoder,
                                 self.modifications,
                                 self.image_format,
-                                self.image_exif_chunk
+                                self.image_exif_chunk,
+                                self.limits,
+                                stream_source
                             )
                         },
                         false => Self::decode_image(
                             png_decoder,
                             self.modifications,
                             self.image_format,
-                            self.image_exif_chunk
+                            self.image_exif_chunk,
+                            self.limits
                         )
                     }
                 },
@@ -230,13 +625,16 @@ impl DecodeBackend for ImageRSBackend {
                             webp_decoder,
                             self.modifications,
                             self.image_format,
-                            self.image_exif_chunk
+                            self.image_exif_chunk,
+                            self.limits,
+                            stream_source
                         ),
                         false => Self::decode_image(
                             webp_decoder,
                             self.modifications,
                             self.image_format,
-                            self.image_exif_chunk
+                            self.image_exif_chunk,
+                            self.limits
                         ),
                     }
                 },
@@ -244,26 +642,31 @@ impl DecodeBackend for ImageRSBackend {
                     gif_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits,
+                    stream_source
                 ),
-                Decoder::Jpeg(jpeg_decoder) => Self::decode_image(
-                    jpeg_decoder,
+                Decoder::Jpeg(cursor) => Self::decode_jpeg(
+                    cursor,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
                 ),
                 Decoder::Qoi(qoi_decoder) => Self::decode_image(
                     qoi_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
                 ),
                 #[cfg(feature = "native-formats")]
                 Decoder::Avif(avif_decoder) => Self::decode_image(
                     avif_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
                 ),
                 // might switch this out with 'geotiff-rust'.
                 #[cfg(feature = "image-rs-extra-formats")]
@@ -271,19 +674,109 @@ impl DecodeBackend for ImageRSBackend {
                     tiff_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
                 ),
                 Decoder::Bmp(bmp_decoder) => Self::decode_image(
                     bmp_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
                 ),
                 Decoder::Ico(ico_decoder) => Self::decode_image(
                     ico_decoder,
                     self.modifications,
                     self.image_format,
-                    self.image_exif_chunk
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Exr(exr_decoder) => Self::decode_image(
+                    exr_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Hdr(hdr_decoder) => Self::decode_image(
+                    hdr_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Pnm(pnm_decoder) => Self::decode_image(
+                    pnm_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Pfm(pfm_decoder) => Self::decode_image(
+                    pfm_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Tga(tga_decoder) => Self::decode_image(
+                    tga_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Dds(dds_decoder) => Self::decode_image(
+                    dds_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::Farbfeld(farbfeld_decoder) => Self::decode_image(
+                    farbfeld_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                #[cfg(feature = "image-rs-extra-formats")]
+                Decoder::OpenRaster(png_decoder) => Self::decode_image(
+                    png_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+            },
+            #[cfg(feature = "image-rs-extra-formats")]
+            Source::MultiPage(multi_page_image) => match multi_page_image.selected_page {
+                Some(index) => {
+                    log::debug!("Decoding page {} of multi-page image...", index + 1);
+
+                    let page_source = multi_page_image.pages[index].0;
+
+                    Self::decode_image(
+                        multi_page_image.page_decoder(page_source)?,
+                        self.modifications,
+                        self.image_format,
+                        self.image_exif_chunk,
+                        self.limits
+                    )
+                },
+                None => Self::decode_pages(
+                    multi_page_image,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
                 ),
             },
             Source::Buffer(buffer) => {
@@ -307,7 +800,7 @@ impl DecodeBackend for ImageRSBackend {
                             )
                         )
                     },
-                    Buffer::Animation((buffer_image_frames, size, colour_type)) => {
+                    Buffer::Animation((buffer_image_frames, mut size, colour_type)) => {
                         let mut animated_pixels = Vec::new();
 
                         for (index, (mut buffer_image, delay)) in buffer_image_frames.into_iter().enumerate() {
@@ -318,7 +811,10 @@ impl DecodeBackend for ImageRSBackend {
                                 &mut buffer_image,
                             );
 
-                            let (pixels, _, _) = buffer_image.to_pixels();
+                            let (pixels, frame_size, _) = buffer_image.to_pixels();
+
+                            // the frames are resized and rotated so the animation's size changes with them.
+                            size = frame_size;
 
                             animated_pixels.push((pixels, delay));
                         }
@@ -333,18 +829,53 @@ impl DecodeBackend for ImageRSBackend {
                             )
                         )
                     },
+                    Buffer::Pages(page_buffers) => {
+                        let first_page_size = page_buffers[0].size;
+
+                        let mut pages = Vec::new();
+
+                        for (index, mut buffer_image) in page_buffers.into_iter().enumerate() {
+                            debug!("Applying modifications to page {}...", index + 1);
+
+                            Self::apply_modifications_to_buffer_image(
+                                self.modifications.scaled_to_page(first_page_size, buffer_image.size),
+                                &mut buffer_image,
+                            );
+
+                            let (pixels, size, colour_type) = buffer_image.to_pixels();
+
+                            pages.push(Page { size, colour_type, pixels });
+                        }
+
+                        Ok(
+                            DecodedImage::new(
+                                pages[0].size,
+                                self.image_format,
+                                pages[0].colour_type,
+                                Self::get_decoded_image_metadata(self.image_exif_chunk),
+                                DecodedImageContent::Pages(pages),
+                            )
+                        )
+                    },
                 }
             }
         }
     }
-}
 
-impl ImageRSBackend {
+    fn is_animated_png(buffer: &[u8]) -> bool {
+        // if the png is broken we let the normal decode report it.
+        PngDecoder::new(Cursor::new(buffer))
+            .and_then(|png_decoder| png_decoder.is_apng())
+            .unwrap_or(true)
+    }
+
     fn decode_animated_image<'a, T: AnimationDecoder<'a>>(
         animation_decoder: T,
-        modifications: ImageModifications,
+        modifications: ModificationPipeline,
         image_format: ImageFormat,
         image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+        mut stream_source: Option<StreamSource>,
     ) -> Result<DecodedImage> {
         let mut image_size_and_metadata: Option<(ImageSize, ImageMetadata)> = None;
 
@@ -354,37 +885,39 @@ impl ImageRSBackend {
 
         let mut image_pixels: Vec<(Pixels, f32)> = Vec::new();
 
-        let perform_modifications = !modifications.is_empty();
+        // we only find out how big an animation is as it's frames are decoded.
+        let mut allocated_bytes = 0u64;
 
         for frame_result in animation_decoder.into_frames() {
-            let frame = frame_result.map_err(
-                // NOTE: I might change this to a less generic error.
-                |error| Error::DecodingFailure {
-                    error: format!(
-                        "Image-rs decoder failed to decode animated frame: {}",
-                        error.to_string()
-                    ),
-                },
-            )?;
+            let frame = frame_result.map_err(Self::animated_frame_error)?;
 
-            let (numerator, denominator) = frame.delay().numer_denom_ms();
-            let delay_seconds = (numerator as f32 / denominator as f32) / 1000.0;
+            let size = frame.buffer().dimensions();
 
-            let image_buffer = frame.into_buffer();
+            limits.check_dimensions(size)?;
 
-            let size = image_buffer.dimensions();
+            allocated_bytes += frame.buffer().as_raw().len() as u64;
 
-            let mut buffer_image = BufferImage {
-                size,
-                colour_type: ImageColourType::Rgba8,
-                variant: BufferImageVariant::Rgba8(image_buffer),
+            // small animations are held in memory, big ones are thrown away and streamed.
+            let is_too_big_to_hold = |stream_source: &mut StreamSource| {
+                allocated_bytes > stream_source.animation_streaming.max_cached_bytes
             };
 
-            if perform_modifications {
-                Self::apply_modifications_to_buffer_image(modifications.clone(), &mut buffer_image);
+            if let Some(stream_source) = stream_source.take_if(is_too_big_to_hold) {
+                return Ok(
+                    Self::stream_animated_image(
+                        stream_source,
+                        modifications.output_size(size),
+                        modifications,
+                        image_format,
+                        image_exif_chunk,
+                        limits
+                    )
+                );
             }
 
-            let (pixels, size, _) = buffer_image.to_pixels();
+            limits.check_allocation(allocated_bytes)?;
+
+            let (pixels, size, delay_seconds) = Self::animated_frame_to_pixels(frame, &modifications);
 
             image_size_and_metadata.get_or_insert_with(
                 || init_size_and_metadata(size)
@@ -409,17 +942,203 @@ impl ImageRSBackend {
         )
     }
 
-    fn decode_image<T: ImageDecoder>(
+    /// Hands the animation over to a thread that decodes it's frames as they're played back 
+    /// (see [`AnimationStream`]), each frame is modified and colour managed as it's decoded.
+    fn stream_animated_image(
+        stream_source: StreamSource,
+        size: ImageSize,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> DecodedImage {
+        log::debug!(
+            "Animation is too big to hold every frame of in memory, streaming it \
+                {} frames ahead instead...",
+            stream_source.animation_streaming.frames_ahead
+        );
+
+        let StreamSource { animation_streaming, encoded_image_bytes, colour_profile, adjustments } = stream_source;
+        let frames_image_format = image_format.clone();
+
+        let animation_stream = AnimationStream::spawn(
+            animation_streaming.frames_ahead,
+            move |send_frame| {
+                for frame_result in Self::animation_frames(encoded_image_bytes.clone(), &frames_image_format)? {
+                    let frame = frame_result.map_err(Self::animated_frame_error)?;
+
+                    limits.check_dimensions(frame.buffer().dimensions())?;
+
+                    let (mut pixels, _, delay_seconds) = Self::animated_frame_to_pixels(frame, &modifications);
+
+                    if let Some(colour_profile) = &colour_profile {
+                        convert_frame_to_srgb(&mut pixels, ImageColourType::Rgba8, colour_profile);
+                    }
+
+                    adjust_pixels(&mut pixels, ImageColourType::Rgba8, &adjustments);
+
+                    if !send_frame((pixels, delay_seconds)) {
+                        break;
+                    }
+                }
+
+                Ok(())
+            }
+        );
+
+        DecodedImage::new(
+            size,
+            image_format,
+            ImageColourType::Rgba8,
+            Self::get_decoded_image_metadata(image_exif_chunk),
+            DecodedImageContent::Streamed(animation_stream),
+        )
+    }
+
+    /// A new decoder over the animation, to go through it's frames from the start again.
+    fn animation_frames(encoded_image_bytes: EncodedImageBytes, image_format: &ImageFormat) -> Result<Frames<'static>> {
+        let cursor = EncodedImageReader::new(encoded_image_bytes);
+
+        let error_func = |error: ImageError| Error::DecoderInitFailure {
+            error: error.to_string(),
+        };
+
+        match image_format {
+            ImageFormat::Gif => Ok(GifDecoder::new(cursor).map_err(error_func)?.into_frames()),
+            ImageFormat::Png => Ok(
+                PngDecoder::new(cursor)
+                    .and_then(|png_decoder| png_decoder.apng())
+                    .map_err(error_func)?
+                    .into_frames()
+            ),
+            ImageFormat::Webp => Ok(WebPDecoder::new(cursor).map_err(error_func)?.into_frames()),
+            unsupported_format => Err(
+                Error::DecoderImageFormatNotSupported {
+                    image_format: unsupported_format.to_string(),
+                    backend: String::from("image-rs"),
+                }
+            ),
+        }
+    }
+
+    /// Makes the modifications to an animation frame, returning it's pixels, size and delay in seconds.
+    fn animated_frame_to_pixels(frame: Frame, modifications: &ModificationPipeline) -> (Pixels, ImageSize, f32) {
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay_seconds = (numerator as f32 / denominator as f32) / 1000.0;
+
+        let image_buffer = frame.into_buffer();
+
+        let mut buffer_image = BufferImage {
+            size: image_buffer.dimensions(),
+            colour_type: ImageColourType::Rgba8,
+            variant: BufferImageVariant::Rgba8(image_buffer),
+        };
+
+        if !modifications.is_empty() {
+            Self::apply_modifications_to_buffer_image(modifications.clone(), &mut buffer_image);
+        }
+
+        let (pixels, size, _) = buffer_image.to_pixels();
+
+        (pixels, size, delay_seconds)
+    }
+
+    fn animated_frame_error(error: ImageError) -> Error {
+        // NOTE: I might change this to a less generic error.
+        Error::DecodingFailure {
+            error: format!(
+                "Image-rs decoder failed to decode animated frame: {}",
+                error
+            ),
+        }
+    }
+
+    #[cfg(feature = "image-rs-extra-formats")]
+    fn decode_pages(
+        multi_page_image: MultiPageImage,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        let first_page_size = multi_page_image.pages[0].1.size;
+
+        // every page is held in memory at once so they all count towards the limit.
+        let mut required_bytes = 0u64;
+
+        for (_, page_info) in &multi_page_image.pages {
+            limits.check_dimensions(page_info.size)?;
+
+            required_bytes += page_info.size.0 as u64 * page_info.size.1 as u64
+                * page_info.colour_type.bytes_per_pixel() as u64;
+        }
+
+        limits.check_allocation(required_bytes)?;
+
+        let mut pages = Vec::with_capacity(multi_page_image.pages.len());
+
+        for index in 0..multi_page_image.pages.len() {
+            log::debug!("Decoding page {} of multi-page image...", index + 1);
+
+            let (page_source, page_info) = multi_page_image.pages[index].clone();
+
+            let (pixels, size, colour_type) = Self::decode_image_to_pixels(
+                multi_page_image.page_decoder(page_source)?,
+                modifications.scaled_to_page(first_page_size, page_info.size),
+                DecodeLimits::default(),
+            )?;
+
+            pages.push(Page { size, colour_type, pixels });
+        }
+
+        Ok(
+            DecodedImage::new(
+                pages[0].size,
+                image_format,
+                pages[0].colour_type,
+                Self::get_decoded_image_metadata(image_exif_chunk),
+                DecodedImageContent::Pages(pages),
+            )
+        )
+    }
+
+    pub(super) fn decode_image<T: ImageDecoder>(
         image_decoder: T,
-        modifications: ImageModifications,
+        modifications: ModificationPipeline,
         image_format: ImageFormat,
         image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
     ) -> Result<DecodedImage> {
+        let (image_pixels, image_size, image_colour_type) = Self::decode_image_to_pixels(
+            image_decoder,
+            modifications,
+            limits
+        )?;
+
+        Ok(
+            DecodedImage::new(
+                image_size,
+                image_format,
+                image_colour_type,
+                Self::get_decoded_image_metadata(image_exif_chunk),
+                DecodedImageContent::Static(image_pixels),
+            )
+        )
+    }
+
+    fn decode_image_to_pixels<T: ImageDecoder>(
+        image_decoder: T,
+        modifications: ModificationPipeline,
+        limits: DecodeLimits,
+    ) -> Result<(Pixels, ImageSize, ImageColourType)> {
         log::debug!("Decoding image with image-rs decoder...");
 
         let image_size = image_decoder.dimensions();
         let image_colour_type = ImageColourType::try_from(image_decoder.color_type())?;
 
+        limits.check_dimensions(image_size)?;
+        limits.check_allocation(image_decoder.total_bytes())?;
+
         let mut image_pixels = Pixels::new(
             &image_colour_type,
             image_decoder.total_bytes() as usize
@@ -440,22 +1159,12 @@ impl ImageRSBackend {
 
         log::debug!("Image-rs decoder successfully decoded to pixels...");
 
-        let metadata = Self::get_decoded_image_metadata(image_exif_chunk);
-
         if modifications.is_empty() {
             log::debug!(
                 "No image modifications so we're constructing decoded image directly from image pixels..."
             );
 
-            return Ok(
-                DecodedImage::new(
-                    image_size,
-                    image_format,
-                    image_colour_type,
-                    metadata,
-                    DecodedImageContent::Static(image_pixels),
-                )
-            );
+            return Ok((image_pixels, image_size, image_colour_type));
         }
 
         log::debug!(
@@ -474,20 +1183,19 @@ impl ImageRSBackend {
 
         log::debug!("Converting image buffer back to pixels to construct into decoded image...");
 
-        let (image_pixels, image_size, image_colour_type) = buffer_image.to_pixels();
+        Ok(buffer_image.to_pixels())
+    }
 
-        Ok(
-            DecodedImage::new(
-                image_size,
-                image_format,
-                image_colour_type,
-                metadata,
-                DecodedImageContent::Static(image_pixels),
-            )
-        )
+    /// Not being able to read the icc profile isn't worth failing the decode over.
+    fn icc_profile_or_warn(icc_profile: std::result::Result<Option<Vec<u8>>, ImageError>) -> Option<Vec<u8>> {
+        icc_profile.unwrap_or_else(|error| {
+            log::warn!("Failed to retrieve the image's icc profile: {}", error);
+
+            None
+        })
     }
 
-    fn get_decoded_image_metadata(image_exif_chunk: Option<Vec<u8>>) -> ImageMetadata {
+    pub(super) fn get_decoded_image_metadata(image_exif_chunk: Option<Vec<u8>>) -> ImageMetadata {
         match image_exif_chunk {
             Some(exif_chunk) => match ImageMetadata::new(exif_chunk) {
                 Ok(metadata) => metadata,
@@ -21,6 +21,23 @@ pub enum BufferImageVariant {
 }
 
 impl BufferImageVariant {
+    pub fn dimensions(&self) -> ImageSize {
+        match self {
+            Self::Grey8(image_buffer) => image_buffer.dimensions(),
+            Self::GreyA8(image_buffer) => image_buffer.dimensions(),
+            Self::Rgb8(image_buffer) => image_buffer.dimensions(),
+            Self::Rgba8(image_buffer) => image_buffer.dimensions(),
+            Self::Grey16(image_buffer) => image_buffer.dimensions(),
+            Self::GreyA16(image_buffer) => image_buffer.dimensions(),
+            Self::Rgb16(image_buffer) => image_buffer.dimensions(),
+            Self::Rgba16(image_buffer) => image_buffer.dimensions(),
+            Self::Grey32F(image_buffer) => image_buffer.dimensions(),
+            Self::GreyA32F(image_buffer) => image_buffer.dimensions(),
+            Self::Rgb32F(image_buffer) => image_buffer.dimensions(),
+            Self::Rgba32F(image_buffer) => image_buffer.dimensions(),
+        }
+    }
+
     pub fn from_pixels_and_colour_type(pixels: Pixels, size: ImageSize, colour_type: ImageColourType) -> Self {
         let (width, height) = size;
 
@@ -0,0 +1,688 @@
+use image::{ColorType, ImageDecoder, ImageResult, error::{DecodingError, ImageError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind}};
+use rayon::{iter::{IndexedParallelIterator, ParallelIterator}, slice::{ParallelSlice, ParallelSliceMut}};
+
+use crate::reader::{EncodedImageBytes, EncodedImageReader};
+
+const HEADER_LENGTH: usize = 128;
+const DX10_HEADER_LENGTH: usize = 20;
+
+const PIXEL_FORMAT_ALPHA_PIXELS: u32 = 0x1;
+const PIXEL_FORMAT_ALPHA: u32 = 0x2;
+const PIXEL_FORMAT_FOURCC: u32 = 0x4;
+const PIXEL_FORMAT_RGB: u32 = 0x40;
+const PIXEL_FORMAT_LUMINANCE: u32 = 0x20000;
+
+/// How the top level texture of a DDS is stored, every
+/// other mip level, cube face and array slice is ignored.
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4 { signed: bool },
+    Bc5 { signed: bool },
+    Bc7,
+    /// Red, green, blue and alpha bit masks.
+    Uncompressed { bits_per_pixel: u32, masks: [u32; 4] },
+}
+
+impl Encoding {
+    fn block_length(&self) -> usize {
+        match self {
+            Encoding::Bc1 | Encoding::Bc4 { .. } => 8,
+            Encoding::Bc2 | Encoding::Bc3 | Encoding::Bc5 { .. } | Encoding::Bc7 => 16,
+            Encoding::Uncompressed { .. } => unreachable!("Uncompressed textures aren't stored in blocks!"),
+        }
+    }
+}
+
+/// A DDS (DirectDraw Surface) texture decoder that plugs into image-rs's [`ImageDecoder`] trait.
+///
+/// image-rs's own dds decoder only does BC1 - BC3 so this one decodes BC1 - BC5, BC7 and
+/// uncompressed textures, all to RGBA8. BC6H is left out as it's an HDR format.
+pub struct DdsDecoder {
+    width: u32,
+    height: u32,
+    encoding: Encoding,
+    buffer: EncodedImageBytes,
+    data_offset: usize,
+}
+
+impl DdsDecoder {
+    pub fn new(cursor: EncodedImageReader) -> ImageResult<Self> {
+        let buffer = cursor.into_inner();
+
+        if buffer.len() < HEADER_LENGTH || &buffer[0..4] != b"DDS " {
+            return Err(decoding_error("Invalid DDS header!"));
+        }
+
+        let height = read_u32(&buffer, 12);
+        let width = read_u32(&buffer, 16);
+
+        let pixel_format_flags = read_u32(&buffer, 80);
+        let fourcc = &buffer[84..88];
+
+        let mut data_offset = HEADER_LENGTH;
+
+        let encoding = match pixel_format_flags & PIXEL_FORMAT_FOURCC != 0 {
+            true => match fourcc {
+                b"DXT1" => Encoding::Bc1,
+                b"DXT2" | b"DXT3" => Encoding::Bc2,
+                b"DXT4" | b"DXT5" => Encoding::Bc3,
+                b"ATI1" | b"BC4U" => Encoding::Bc4 { signed: false },
+                b"BC4S" => Encoding::Bc4 { signed: true },
+                b"ATI2" | b"BC5U" => Encoding::Bc5 { signed: false },
+                b"BC5S" => Encoding::Bc5 { signed: true },
+                b"DX10" => {
+                    if buffer.len() < HEADER_LENGTH + DX10_HEADER_LENGTH {
+                        return Err(decoding_error("DDS DX10 header is truncated!"));
+                    }
+
+                    data_offset += DX10_HEADER_LENGTH;
+
+                    Self::dxgi_format_to_encoding(read_u32(&buffer, HEADER_LENGTH))?
+                },
+                fourcc => return Err(
+                    unsupported_error(format!("DDS FourCC '{}'", String::from_utf8_lossy(fourcc)))
+                ),
+            },
+            false => {
+                let bits_per_pixel = read_u32(&buffer, 88);
+
+                let red_mask = read_u32(&buffer, 92);
+                let mut masks = [red_mask, read_u32(&buffer, 96), read_u32(&buffer, 100), 0];
+
+                if pixel_format_flags & (PIXEL_FORMAT_ALPHA_PIXELS | PIXEL_FORMAT_ALPHA) != 0 {
+                    masks[3] = read_u32(&buffer, 104);
+                }
+
+                if pixel_format_flags & PIXEL_FORMAT_LUMINANCE != 0 {
+                    masks[1] = red_mask;
+                    masks[2] = red_mask;
+                }
+
+                let is_uncompressed = pixel_format_flags & (PIXEL_FORMAT_RGB | PIXEL_FORMAT_LUMINANCE | PIXEL_FORMAT_ALPHA) != 0;
+
+                if !is_uncompressed || ![8, 16, 24, 32].contains(&bits_per_pixel) {
+                    return Err(unsupported_error(format!("DDS pixel format with flags {:#x}", pixel_format_flags)));
+                }
+
+                Encoding::Uncompressed { bits_per_pixel, masks }
+            },
+        };
+
+        if width == 0 || height == 0 {
+            return Err(decoding_error("DDS texture has no size!"));
+        }
+
+        let data_length = match encoding {
+            Encoding::Uncompressed { bits_per_pixel, .. } => {
+                width as usize * height as usize * (bits_per_pixel as usize / 8)
+            },
+            encoding => {
+                width.div_ceil(4) as usize * height.div_ceil(4) as usize * encoding.block_length()
+            },
+        };
+
+        if buffer.len() < data_offset + data_length {
+            return Err(decoding_error("DDS texture data is truncated!"));
+        }
+
+        Ok(
+            Self {
+                width,
+                height,
+                encoding,
+                buffer,
+                data_offset,
+            }
+        )
+    }
+
+    fn dxgi_format_to_encoding(dxgi_format: u32) -> ImageResult<Encoding> {
+        let rgba_masks = [0x000000ff, 0x0000ff00, 0x00ff0000, 0xff000000];
+        let bgra_masks = [0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000];
+        let bgrx_masks = [0x00ff0000, 0x0000ff00, 0x000000ff, 0];
+
+        let encoding = match dxgi_format {
+            27..=29 => Encoding::Uncompressed { bits_per_pixel: 32, masks: rgba_masks },
+            70..=72 => Encoding::Bc1,
+            73..=75 => Encoding::Bc2,
+            76..=78 => Encoding::Bc3,
+            79 | 80 => Encoding::Bc4 { signed: false },
+            81 => Encoding::Bc4 { signed: true },
+            82 | 83 => Encoding::Bc5 { signed: false },
+            84 => Encoding::Bc5 { signed: true },
+            87 | 90 | 91 => Encoding::Uncompressed { bits_per_pixel: 32, masks: bgra_masks },
+            88 | 92 | 93 => Encoding::Uncompressed { bits_per_pixel: 32, masks: bgrx_masks },
+            97..=99 => Encoding::Bc7,
+            dxgi_format => return Err(unsupported_error(format!("DDS DXGI format {}", dxgi_format))),
+        };
+
+        Ok(encoding)
+    }
+}
+
+impl ImageDecoder for DdsDecoder {
+    fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn color_type(&self) -> ColorType {
+        ColorType::Rgba8
+    }
+
+    fn read_image(self, buf: &mut [u8]) -> ImageResult<()>
+    where
+        Self: Sized,
+    {
+        let width = self.width as usize;
+        let data = &self.buffer[self.data_offset..];
+
+        if let Encoding::Uncompressed { bits_per_pixel, masks } = self.encoding {
+            let bytes_per_pixel = bits_per_pixel as usize / 8;
+
+            buf.par_chunks_mut(4)
+                .zip(data.par_chunks(bytes_per_pixel))
+                .for_each(|(pixel, source_pixel)| {
+                    let mut value_bytes = [0u8; 4];
+                    value_bytes[..bytes_per_pixel].copy_from_slice(source_pixel);
+
+                    let value = u32::from_le_bytes(value_bytes);
+
+                    for (channel, mask) in masks.iter().enumerate() {
+                        pixel[channel] = match (*mask, channel) {
+                            (0, 3) => u8::MAX,
+                            (0, _) => 0,
+                            (mask, _) => {
+                                let bits = mask.count_ones();
+                                let sample = (value & mask) >> mask.trailing_zeros();
+
+                                ((sample as u64 * 255 + ((1u64 << bits) - 1) / 2) / ((1u64 << bits) - 1)) as u8
+                            },
+                        };
+                    }
+                });
+
+            return Ok(());
+        }
+
+        let blocks_wide = width.div_ceil(4);
+        let block_length = self.encoding.block_length();
+
+        // each chunk is a row of blocks (4 rows of pixels, or less at the bottom edge).
+        buf.par_chunks_mut(width * 4 * 4)
+            .enumerate()
+            .for_each(|(block_y, pixel_rows)| {
+                let row_count = pixel_rows.len() / (width * 4);
+
+                for block_x in 0..blocks_wide {
+                    let block_offset = (block_y * blocks_wide + block_x) * block_length;
+                    let block = &data[block_offset..block_offset + block_length];
+
+                    let texels = match self.encoding {
+                        Encoding::Bc1 => decode_bc1_block(block, true),
+                        Encoding::Bc2 => decode_bc2_block(block),
+                        Encoding::Bc3 => decode_bc3_block(block),
+                        Encoding::Bc4 { signed } => decode_bc4_block(block, signed),
+                        Encoding::Bc5 { signed } => decode_bc5_block(block, signed),
+                        Encoding::Bc7 => decode_bc7_block(block),
+                        Encoding::Uncompressed { .. } => unreachable!(),
+                    };
+
+                    for row in 0..row_count {
+                        for column in 0..4.min(width - block_x * 4) {
+                            let index = (row * width + block_x * 4 + column) * 4;
+
+                            pixel_rows[index..index + 4].copy_from_slice(&texels[row * 4 + column]);
+                        }
+                    }
+                }
+            });
+
+        Ok(())
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
+
+type Texels = [[u8; 4]; 16];
+
+fn expand_565(colour: u16) -> [u8; 4] {
+    let red = ((colour >> 11) & 0x1f) as u8;
+    let green = ((colour >> 5) & 0x3f) as u8;
+    let blue = (colour & 0x1f) as u8;
+
+    [(red << 3) | (red >> 2), (green << 2) | (green >> 4), (blue << 3) | (blue >> 2), u8::MAX]
+}
+
+fn blend(first: [u8; 4], second: [u8; 4], first_weight: u16, second_weight: u16) -> [u8; 4] {
+    let total = first_weight + second_weight;
+
+    [0, 1, 2, 3].map(|channel| {
+        ((first[channel] as u16 * first_weight + second[channel] as u16 * second_weight) / total) as u8
+    })
+}
+
+/// BC1 (DXT1), two 565 colours and 2 bit indices into a palette interpolated between them.
+fn decode_bc1_block(block: &[u8], allow_transparency: bool) -> Texels {
+    let first = u16::from_le_bytes([block[0], block[1]]);
+    let second = u16::from_le_bytes([block[2], block[3]]);
+
+    let (first_colour, second_colour) = (expand_565(first), expand_565(second));
+
+    // when the first colour isn't bigger it switches to a three colour
+    // mode with transparent black, which isn't a thing in BC2 and BC3.
+    let palette = match first > second || !allow_transparency {
+        true => [
+            first_colour,
+            second_colour,
+            blend(first_colour, second_colour, 2, 1),
+            blend(first_colour, second_colour, 1, 2),
+        ],
+        false => [
+            first_colour,
+            second_colour,
+            blend(first_colour, second_colour, 1, 1),
+            [0, 0, 0, 0],
+        ],
+    };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    std::array::from_fn(|texel| palette[((indices >> (texel * 2)) & 0b11) as usize])
+}
+
+/// BC2 (DXT3), explicit 4 bit alpha followed by a BC1 colour block.
+fn decode_bc2_block(block: &[u8]) -> Texels {
+    let alphas = u64::from_le_bytes(block[0..8].try_into().unwrap());
+    let mut texels = decode_bc1_block(&block[8..16], false);
+
+    for (texel_index, texel) in texels.iter_mut().enumerate() {
+        texel[3] = ((alphas >> (texel_index * 4)) & 0xf) as u8 * 17;
+    }
+
+    texels
+}
+
+/// BC3 (DXT5), a BC4 block for alpha followed by a BC1 colour block.
+fn decode_bc3_block(block: &[u8]) -> Texels {
+    let alphas = decode_bc4_channel(&block[0..8], false);
+    let mut texels = decode_bc1_block(&block[8..16], false);
+
+    for (texel, alpha) in texels.iter_mut().zip(alphas) {
+        texel[3] = alpha;
+    }
+
+    texels
+}
+
+/// BC4, a single (red) channel. We display it as greyscale.
+fn decode_bc4_block(block: &[u8], signed: bool) -> Texels {
+    decode_bc4_channel(block, signed).map(|value| [value, value, value, u8::MAX])
+}
+
+/// BC5, two BC4 blocks for the red and green channels (usually a normal map).
+fn decode_bc5_block(block: &[u8], signed: bool) -> Texels {
+    let reds = decode_bc4_channel(&block[0..8], signed);
+    let greens = decode_bc4_channel(&block[8..16], signed);
+
+    std::array::from_fn(|texel| [reds[texel], greens[texel], 0, u8::MAX])
+}
+
+/// Two 8 bit end points and 3 bit indices into a palette interpolated between them.
+fn decode_bc4_channel(block: &[u8], signed: bool) -> [u8; 16] {
+    let (first, second, min, max) = match signed {
+        // -128 and -127 both mean -1.0.
+        true => ((block[0] as i8).max(-127) as i32, (block[1] as i8).max(-127) as i32, -127, 127),
+        false => (block[0] as i32, block[1] as i32, 0, 255),
+    };
+
+    let mut palette = [first, second, 0, 0, 0, 0, min, max];
+
+    match first > second {
+        true => for step in 1..7 {
+            palette[step + 1] = ((7 - step as i32) * first + step as i32 * second) / 7;
+        },
+        false => for step in 1..5 {
+            palette[step + 1] = ((5 - step as i32) * first + step as i32 * second) / 5;
+        },
+    }
+
+    let mut index_bytes = [0u8; 8];
+    index_bytes[..6].copy_from_slice(&block[2..8]);
+
+    let indices = u64::from_le_bytes(index_bytes);
+
+    std::array::from_fn(|texel| {
+        let value = palette[((indices >> (texel * 3)) & 0b111) as usize];
+
+        match signed {
+            true => ((value + 127) * 255 / 254) as u8,
+            false => value as u8,
+        }
+    })
+}
+
+struct Bc7Mode {
+    subset_count: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    colour_bits: u32,
+    alpha_bits: u32,
+    has_endpoint_p_bits: bool,
+    has_shared_p_bits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode { subset_count: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, colour_bits: 4, alpha_bits: 0, has_endpoint_p_bits: true, has_shared_p_bits: false, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subset_count: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, colour_bits: 6, alpha_bits: 0, has_endpoint_p_bits: false, has_shared_p_bits: true, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subset_count: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, colour_bits: 5, alpha_bits: 0, has_endpoint_p_bits: false, has_shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subset_count: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, colour_bits: 7, alpha_bits: 0, has_endpoint_p_bits: true, has_shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subset_count: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, colour_bits: 5, alpha_bits: 6, has_endpoint_p_bits: false, has_shared_p_bits: false, index_bits: 2, secondary_index_bits: 3 },
+    Bc7Mode { subset_count: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, colour_bits: 7, alpha_bits: 8, has_endpoint_p_bits: false, has_shared_p_bits: false, index_bits: 2, secondary_index_bits: 2 },
+    Bc7Mode { subset_count: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, colour_bits: 7, alpha_bits: 7, has_endpoint_p_bits: true, has_shared_p_bits: false, index_bits: 4, secondary_index_bits: 0 },
+    Bc7Mode { subset_count: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, colour_bits: 5, alpha_bits: 5, has_endpoint_p_bits: true, has_shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+];
+
+/// Which texels are in the second subset of each two subset partition (bit per texel).
+const BC7_PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80,
+    0xc800, 0xffec, 0xfe80, 0xe800, 0xffe8, 0xff00, 0xfff0, 0xf000,
+    0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c,
+    0xaaaa, 0xf0f0, 0x5a5a, 0x33cc, 0x3c3c, 0x55aa, 0x9696, 0xa55a,
+    0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c,
+    0x9336, 0x9cc6, 0x817e, 0xe718, 0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+/// The subset of each texel in each three subset partition.
+const BC7_PARTITIONS_3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+/// The anchor texel of the second subset in two subset partitions.
+const BC7_ANCHORS_2: [usize; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15,
+    2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15,
+    2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2,
+    15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// The anchor texels of the second and third subsets in three subset partitions.
+const BC7_ANCHORS_3: [[usize; 2]; 64] = [
+    [3, 15], [3, 8], [15, 8], [15, 3], [8, 15], [3, 15], [15, 3], [15, 8],
+    [8, 15], [8, 15], [6, 15], [6, 15], [6, 15], [5, 15], [3, 15], [3, 8],
+    [3, 15], [3, 8], [8, 15], [15, 3], [3, 15], [3, 8], [6, 15], [10, 8],
+    [5, 3], [8, 15], [8, 6], [6, 10], [8, 15], [5, 15], [15, 10], [15, 8],
+    [8, 15], [15, 3], [3, 15], [5, 10], [6, 10], [10, 8], [8, 9], [15, 10],
+    [15, 6], [3, 15], [15, 8], [5, 15], [15, 3], [15, 6], [15, 6], [15, 8],
+    [3, 15], [15, 3], [5, 15], [5, 15], [5, 15], [8, 15], [5, 15], [10, 15],
+    [5, 15], [10, 15], [8, 15], [13, 15], [15, 3], [12, 15], [3, 15], [3, 8],
+];
+
+const BC7_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+
+        let value = (self.bits >> self.position) as u32 & ((1u64 << count) - 1) as u32;
+        self.position += count;
+
+        value
+    }
+}
+
+/// BC7, a block is split into up to three subsets (picked from a fixed table of partitions),
+/// each with its own end points. Eight modes trade end point precision for index precision.
+fn decode_bc7_block(block: &[u8]) -> Texels {
+    // the mode is the number of zero bits before the first set bit.
+    let mode_index = match block[0] {
+        0 => return [[0; 4]; 16], // reserved, the spec says to output transparent black.
+        first_byte => first_byte.trailing_zeros(),
+    };
+
+    let mode = &BC7_MODES[mode_index as usize];
+
+    let mut reader = BitReader {
+        bits: u128::from_le_bytes(block.try_into().unwrap()),
+        position: mode_index + 1,
+    };
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    let endpoint_count = mode.subset_count * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+
+    for channel in 0..3 {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = reader.read(mode.colour_bits);
+        }
+    }
+
+    if mode.alpha_bits > 0 {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[3] = reader.read(mode.alpha_bits);
+        }
+    }
+
+    let mut colour_precision = mode.colour_bits;
+    let mut alpha_precision = mode.alpha_bits;
+
+    let apply_p_bit = |endpoint: &mut [u32; 4], p_bit: u32| {
+        endpoint.iter_mut().for_each(|value| *value = (*value << 1) | p_bit);
+    };
+
+    if mode.has_endpoint_p_bits {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            let p_bit = reader.read(1);
+            apply_p_bit(endpoint, p_bit);
+        }
+    }
+
+    if mode.has_shared_p_bits {
+        for subset in 0..mode.subset_count {
+            let p_bit = reader.read(1);
+
+            for endpoint in &mut endpoints[subset * 2..subset * 2 + 2] {
+                apply_p_bit(endpoint, p_bit);
+            }
+        }
+    }
+
+    if mode.has_endpoint_p_bits || mode.has_shared_p_bits {
+        colour_precision += 1;
+
+        if alpha_precision > 0 {
+            alpha_precision += 1;
+        }
+    }
+
+    let endpoints = endpoints.map(|endpoint| [
+        expand_bits(endpoint[0], colour_precision),
+        expand_bits(endpoint[1], colour_precision),
+        expand_bits(endpoint[2], colour_precision),
+        match alpha_precision {
+            0 => u8::MAX,
+            precision => expand_bits(endpoint[3], precision),
+        },
+    ]);
+
+    let subset_of = |texel: usize| -> usize {
+        match mode.subset_count {
+            2 => ((BC7_PARTITIONS_2[partition] >> texel) & 1) as usize,
+            3 => BC7_PARTITIONS_3[partition][texel] as usize,
+            _ => 0,
+        }
+    };
+
+    // anchor texels have an implicit zero top bit so they're stored with one bit less.
+    let is_anchor = |texel: usize| -> bool {
+        texel == 0 || match mode.subset_count {
+            2 => texel == BC7_ANCHORS_2[partition],
+            3 => BC7_ANCHORS_3[partition].contains(&texel),
+            _ => false,
+        }
+    };
+
+    let mut indices = [0u32; 16];
+
+    for (texel, index) in indices.iter_mut().enumerate() {
+        *index = reader.read(mode.index_bits - is_anchor(texel) as u32);
+    }
+
+    let mut secondary_indices = [0u32; 16];
+
+    if mode.secondary_index_bits > 0 {
+        for (texel, index) in secondary_indices.iter_mut().enumerate() {
+            *index = reader.read(mode.secondary_index_bits - (texel == 0) as u32);
+        }
+    }
+
+    std::array::from_fn(|texel| {
+        let subset = subset_of(texel);
+        let (first, second) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+
+        let ((colour_index, colour_index_bits), (alpha_index, alpha_index_bits)) = match (mode.secondary_index_bits, index_selection) {
+            (0, _) => ((indices[texel], mode.index_bits), (indices[texel], mode.index_bits)),
+            (_, 0) => ((indices[texel], mode.index_bits), (secondary_indices[texel], mode.secondary_index_bits)),
+            _ => ((secondary_indices[texel], mode.secondary_index_bits), (indices[texel], mode.index_bits)),
+        };
+
+        let colour_weight = bc7_weight(colour_index, colour_index_bits);
+        let alpha_weight = bc7_weight(alpha_index, alpha_index_bits);
+
+        let mut texel = [0, 1, 2, 3].map(|channel| {
+            let weight = match channel {
+                3 => alpha_weight,
+                _ => colour_weight,
+            };
+
+            (((64 - weight) * first[channel] as u32 + weight * second[channel] as u32 + 32) >> 6) as u8
+        });
+
+        // rotation swaps alpha with one of the colour channels, giving that channel the separate indices.
+        if rotation > 0 {
+            texel.swap(3, rotation as usize - 1);
+        }
+
+        texel
+    })
+}
+
+fn bc7_weight(index: u32, index_bits: u32) -> u32 {
+    match index_bits {
+        2 => BC7_WEIGHTS_2[index as usize],
+        3 => BC7_WEIGHTS_3[index as usize],
+        _ => BC7_WEIGHTS_4[index as usize],
+    }
+}
+
+/// Expands an n bit value to 8 bits by replicating it's top bits into the new low bits.
+fn expand_bits(value: u32, bits: u32) -> u8 {
+    let value = value << (8 - bits);
+
+    (value | (value >> bits)) as u8
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+}
+
+fn decoding_error(message: &str) -> ImageError {
+    ImageError::Decoding(DecodingError::new(ImageFormatHint::Name("DDS".into()), message))
+}
+
+fn unsupported_error(feature: String) -> ImageError {
+    ImageError::Unsupported(
+        UnsupportedError::from_format_and_kind(
+            ImageFormatHint::Name("DDS".into()),
+            UnsupportedErrorKind::GenericFeature(feature),
+        )
+    )
+}
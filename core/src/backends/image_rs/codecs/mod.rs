@@ -0,0 +1,5 @@
+pub(crate) mod pfm;
+pub(crate) mod open_raster;
+
+#[cfg(feature = "image-rs-extra-formats")]
+pub(crate) mod dds;
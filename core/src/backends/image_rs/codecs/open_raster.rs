@@ -0,0 +1,88 @@
+const MIMETYPE: &[u8] = b"image/openraster";
+const MERGED_IMAGE_NAME: &[u8] = b"mergedimage.png";
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+/// The spec requires the archive to start with an uncompressed `mimetype` file
+/// so (like the epub format) we can tell it apart from any other zip right away.
+pub(crate) fn is_open_raster(buffer: &[u8]) -> bool {
+    buffer.len() >= 38 + MIMETYPE.len()
+        && read_u32(buffer, 0) == Some(LOCAL_FILE_HEADER_SIGNATURE)
+        && &buffer[30..38] == b"mimetype"
+        && &buffer[38..38 + MIMETYPE.len()] == MIMETYPE
+}
+
+/// OpenRaster files are zip archives of the layers (as pngs) and a `stack.xml`, they also
+/// have to contain a flattened `mergedimage.png` which is all we need for viewing.
+///
+/// This finds `mergedimage.png` in the zip's central directory and returns it (inflated if needs be).
+pub(crate) fn extract_merged_image(buffer: &[u8]) -> Option<Vec<u8>> {
+    // the end of central directory record is at the very end unless there's
+    // a comment after it, which can't be any bigger than a u16.
+    let search_start = buffer.len().saturating_sub(22 + u16::MAX as usize);
+    let end_of_central_directory = (search_start..buffer.len().saturating_sub(21))
+        .rev()
+        .find(|&offset| read_u32(buffer, offset) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))?;
+
+    let entry_count = read_u16(buffer, end_of_central_directory + 10)? as usize;
+    let mut offset = read_u32(buffer, end_of_central_directory + 16)? as usize;
+
+    for _ in 0..entry_count {
+        if read_u32(buffer, offset)? != CENTRAL_DIRECTORY_SIGNATURE {
+            return None;
+        }
+
+        let compression_method = read_u16(buffer, offset + 10)?;
+        let compressed_size = read_u32(buffer, offset + 20)? as usize;
+        let uncompressed_size = read_u32(buffer, offset + 24)? as usize;
+        let name_length = read_u16(buffer, offset + 28)? as usize;
+        let extra_length = read_u16(buffer, offset + 30)? as usize;
+        let comment_length = read_u16(buffer, offset + 32)? as usize;
+        let local_header_offset = read_u32(buffer, offset + 42)? as usize;
+
+        let name = buffer.get(offset + 46..offset + 46 + name_length)?;
+
+        offset += 46 + name_length + extra_length + comment_length;
+
+        if name != MERGED_IMAGE_NAME {
+            continue;
+        }
+
+        if read_u32(buffer, local_header_offset)? != LOCAL_FILE_HEADER_SIGNATURE {
+            return None;
+        }
+
+        // the local header's name and extra field can differ in length from the central directory's.
+        let data_offset = local_header_offset
+            + 30
+            + read_u16(buffer, local_header_offset + 26)? as usize
+            + read_u16(buffer, local_header_offset + 28)? as usize;
+
+        let data = buffer.get(data_offset..data_offset + compressed_size)?;
+
+        return match compression_method {
+            0 => Some(data.to_vec()),
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(data, uncompressed_size).ok(),
+            unsupported_method => {
+                log::warn!(
+                    "OpenRaster merged image uses an unsupported zip compression method ({})!",
+                    unsupported_method
+                );
+
+                None
+            },
+        };
+    }
+
+    None
+}
+
+fn read_u16(buffer: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(buffer.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buffer.get(offset..offset + 4)?.try_into().ok()?))
+}
@@ -0,0 +1,153 @@
+use crate::decoded_image::ImageSize;
+
+#[cfg(feature = "image-rs-extra-formats")]
+use image::{ColorType, ImageDecoder, ImageResult, error::{DecodingError, ImageError, ImageFormatHint}};
+
+#[cfg(feature = "image-rs-extra-formats")]
+use crate::reader::{EncodedImageBytes, EncodedImageReader};
+
+/// The header of a PFM (portable float map), the float member of the netpbm family
+/// that image-rs doesn't support. It's `PF` (rgb) or `Pf` (grey), the width and height
+/// and then a scale, which we only care about the sign of (negative is little endian).
+pub(crate) struct PfmHeader {
+    pub size: ImageSize,
+    pub is_grey: bool,
+    pub is_little_endian: bool,
+    pub data_offset: usize,
+}
+
+impl PfmHeader {
+    pub fn parse(buffer: &[u8]) -> Option<Self> {
+        let is_grey = match buffer.get(0..2)? {
+            b"PF" => false,
+            b"Pf" => true,
+            _ => return None,
+        };
+
+        let mut position = 2;
+        let mut tokens = Vec::with_capacity(3);
+
+        while tokens.len() < 3 {
+            while buffer.get(position)?.is_ascii_whitespace() {
+                position += 1;
+            }
+
+            let start = position;
+
+            while !buffer.get(position)?.is_ascii_whitespace() {
+                position += 1;
+            }
+
+            tokens.push(std::str::from_utf8(&buffer[start..position]).ok()?);
+        }
+
+        let width = tokens[0].parse::<u32>().ok()?;
+        let height = tokens[1].parse::<u32>().ok()?;
+        let scale = tokens[2].parse::<f32>().ok()?;
+
+        if width == 0 || height == 0 || scale == 0.0 || !scale.is_finite() {
+            return None;
+        }
+
+        Some(
+            Self {
+                size: (width, height),
+                is_grey,
+                is_little_endian: scale < 0.0,
+                // a single whitespace character separates the header from the pixels.
+                data_offset: position + 1,
+            }
+        )
+    }
+}
+
+/// A PFM decoder that plugs into image-rs's [`ImageDecoder`] trait.
+///
+/// image-rs has no single channel f32 colour type so grey PFMs get expanded to rgb.
+#[cfg(feature = "image-rs-extra-formats")]
+pub struct PfmDecoder {
+    header: PfmHeader,
+    buffer: EncodedImageBytes,
+}
+
+#[cfg(feature = "image-rs-extra-formats")]
+impl PfmDecoder {
+    pub fn new(cursor: EncodedImageReader) -> ImageResult<Self> {
+        let buffer = cursor.into_inner();
+
+        let header = PfmHeader::parse(&buffer).ok_or_else(
+            || decoding_error("Invalid PFM header!")
+        )?;
+
+        let channel_count = match header.is_grey {
+            true => 1,
+            false => 3,
+        };
+
+        let data_length = header.size.0 as usize * header.size.1 as usize * channel_count * 4;
+
+        if buffer.len() < header.data_offset + data_length {
+            return Err(decoding_error("PFM pixel data is truncated!"));
+        }
+
+        Ok(Self { header, buffer })
+    }
+}
+
+#[cfg(feature = "image-rs-extra-formats")]
+impl ImageDecoder for PfmDecoder {
+    fn dimensions(&self) -> (u32, u32) {
+        self.header.size
+    }
+
+    fn color_type(&self) -> ColorType {
+        ColorType::Rgb32F
+    }
+
+    fn read_image(self, buf: &mut [u8]) -> ImageResult<()>
+    where
+        Self: Sized,
+    {
+        let (width, height) = (self.header.size.0 as usize, self.header.size.1 as usize);
+
+        let source_channel_count = match self.header.is_grey {
+            true => 1,
+            false => 3,
+        };
+
+        let source_row_length = width * source_channel_count * 4;
+        let row_length = width * 3 * 4;
+
+        let data = &self.buffer[self.header.data_offset..];
+
+        // PFM rows are stored bottom to top.
+        for (y, row) in buf.chunks_exact_mut(row_length).enumerate() {
+            let source_row = &data[(height - 1 - y) * source_row_length..][..source_row_length];
+
+            for (x, pixel) in row.chunks_exact_mut(12).enumerate() {
+                for (channel, sample) in pixel.chunks_exact_mut(4).enumerate() {
+                    let source_index = (x * source_channel_count + channel % source_channel_count) * 4;
+                    let bytes: [u8; 4] = source_row[source_index..source_index + 4].try_into().unwrap();
+
+                    let value = match self.header.is_little_endian {
+                        true => f32::from_le_bytes(bytes),
+                        false => f32::from_be_bytes(bytes),
+                    };
+
+                    sample.copy_from_slice(&value.to_ne_bytes());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
+
+#[cfg(feature = "image-rs-extra-formats")]
+fn decoding_error(message: &str) -> ImageError {
+    ImageError::Decoding(DecodingError::new(ImageFormatHint::Name("PFM".into()), message))
+}
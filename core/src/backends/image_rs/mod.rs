@@ -3,4 +3,10 @@ pub use backend::*;
 
 mod colour;
 mod buffer_image;
-mod modifications;
\ No newline at end of file
+mod modifications;
+mod progressive;
+mod scaled;
+#[cfg(feature = "image-rs-extra-formats")]
+mod multi_page;
+
+pub(crate) mod codecs;
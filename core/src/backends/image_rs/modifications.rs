@@ -1,110 +1,227 @@
-use std::collections::HashSet;
+use image::{ImageBuffer, Pixel, imageops::{self, FilterType}};
 
-use image::imageops::{self, FilterType};
+use crate::{backends::image_rs::{ImageRSBackend, buffer_image::{BufferImage, BufferImageVariant}}, modifications::{ImageModification, ModificationPipeline, ResizeFilter, clamp_crop_rect}, processing::{adjustments::adjust_samples, alpha::{AlphaSample, premultiply_samples, unpremultiply_samples}}};
 
-use crate::{backends::image_rs::{ImageRSBackend, buffer_image::{BufferImage, BufferImageVariant}}, modifications::ImageModification};
+/// Crops, flips and rotations are the same for every kind of image buffer so there's no sense writing them out 12 times.
+macro_rules! map_buffer_image_variant {
+    ($variant:expr, |$image_buffer:ident| $operation:expr) => {
+        match $variant {
+            BufferImageVariant::Grey8($image_buffer) => BufferImageVariant::Grey8($operation),
+            BufferImageVariant::GreyA8($image_buffer) => BufferImageVariant::GreyA8($operation),
+            BufferImageVariant::Rgb8($image_buffer) => BufferImageVariant::Rgb8($operation),
+            BufferImageVariant::Rgba8($image_buffer) => BufferImageVariant::Rgba8($operation),
+            BufferImageVariant::Grey16($image_buffer) => BufferImageVariant::Grey16($operation),
+            BufferImageVariant::Grey32F($image_buffer) => BufferImageVariant::Grey32F($operation),
+            BufferImageVariant::GreyA16($image_buffer) => BufferImageVariant::GreyA16($operation),
+            BufferImageVariant::GreyA32F($image_buffer) => BufferImageVariant::GreyA32F($operation),
+            BufferImageVariant::Rgb16($image_buffer) => BufferImageVariant::Rgb16($operation),
+            BufferImageVariant::Rgb32F($image_buffer) => BufferImageVariant::Rgb32F($operation),
+            BufferImageVariant::Rgba16($image_buffer) => BufferImageVariant::Rgba16($operation),
+            BufferImageVariant::Rgba32F($image_buffer) => BufferImageVariant::Rgba32F($operation),
+        }
+    };
+}
+
+impl From<ResizeFilter> for FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Bilinear => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Gaussian => FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
 
 impl ImageRSBackend {
 
-    pub(super) fn apply_modifications_to_buffer_image(modifications: HashSet<ImageModification>, buffer_image: &mut BufferImage) {
-        // cloning shouldn't be too expensive, if that changes in the future we adjust this
-        for modification in modifications {
+    pub(super) fn apply_modifications_to_buffer_image(modifications: ModificationPipeline, buffer_image: &mut BufferImage) {
+        Self::apply_modifications_to_buffer_image_with_filter(modifications, buffer_image, None);
+    }
+
+    /// `filter` overrides the resampling filter the resize was asked for with.
+    pub(super) fn apply_modifications_to_buffer_image_with_filter(
+        modifications: ModificationPipeline,
+        buffer_image: &mut BufferImage,
+        filter: Option<FilterType>,
+    ) {
+        // any pipeline comes down to a crop, a resize then a flip and rotation, which 
+        // also means we're never resizing an image that's about to be cropped smaller.
+        let flattened_modifications = modifications.flatten(buffer_image.variant.dimensions());
+
+        for modification in flattened_modifications.to_modifications() {
 
             match modification {
-                ImageModification::Resize(width, height) => {
-                    log::debug!("Applying resize modification ({}x{})...", width, height);
+                ImageModification::Crop { x, y, width, height } => {
+                    let (x, y, width, height) = clamp_crop_rect(
+                        (x, y, width, height),
+                        buffer_image.variant.dimensions()
+                    );
+
+                    log::debug!("Applying crop modification ({}x{} at {}, {})...", width, height, x, y);
+
+                    buffer_image.variant = map_buffer_image_variant!(
+                        &buffer_image.variant,
+                        |image_buffer| imageops::crop_imm(image_buffer, x, y, width, height).to_image()
+                    );
+                },
+                ImageModification::Resize(width, height, resize_filter) => {
+                    let filter = filter.unwrap_or(FilterType::from(resize_filter));
+
+                    log::debug!("Applying resize modification ({}x{} with {:?} filter)...", width, height, filter);
 
                     let variant = &mut buffer_image.variant;
 
-                    *variant = match &variant {
+                    *variant = match variant {
                         BufferImageVariant::Grey8(image_buffer) => {
                             BufferImageVariant::Grey8(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::GreyA8(image_buffer) => {
                             BufferImageVariant::GreyA8(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                         BufferImageVariant::Rgb8(image_buffer) => {
                             BufferImageVariant::Rgb8(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::Rgba8(image_buffer) => {
                             BufferImageVariant::Rgba8(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                         BufferImageVariant::Grey16(image_buffer) => {
                             BufferImageVariant::Grey16(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::Grey32F(image_buffer) => {
                             BufferImageVariant::Grey32F(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::GreyA16(image_buffer) => {
                             BufferImageVariant::GreyA16(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                         BufferImageVariant::GreyA32F(image_buffer) => {
                             BufferImageVariant::GreyA32F(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                         BufferImageVariant::Rgb16(image_buffer) => {
                             BufferImageVariant::Rgb16(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::Rgb32F(image_buffer) => {
                             BufferImageVariant::Rgb32F(
                                 imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
+                                    image_buffer, width, height, filter
                                 )
                             )
                         },
                         BufferImageVariant::Rgba16(image_buffer) => {
                             BufferImageVariant::Rgba16(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                         BufferImageVariant::Rgba32F(image_buffer) => {
                             BufferImageVariant::Rgba32F(
-                                imageops::resize(
-                                    image_buffer, width, height, FilterType::Lanczos3
-                                )
+                                resize_premultiplied(image_buffer, width, height, filter)
                             )
                         },
                     };
                 },
+                ImageModification::Rotate90 => {
+                    log::debug!("Applying 90 degree rotation modification...");
+
+                    buffer_image.variant = map_buffer_image_variant!(&buffer_image.variant, |image_buffer| imageops::rotate90(image_buffer));
+                },
+                ImageModification::Rotate180 => {
+                    log::debug!("Applying 180 degree rotation modification...");
+
+                    buffer_image.variant = map_buffer_image_variant!(&buffer_image.variant, |image_buffer| imageops::rotate180(image_buffer));
+                },
+                ImageModification::Rotate270 => {
+                    log::debug!("Applying 270 degree rotation modification...");
+
+                    buffer_image.variant = map_buffer_image_variant!(&buffer_image.variant, |image_buffer| imageops::rotate270(image_buffer));
+                },
+                ImageModification::FlipHorizontal => {
+                    log::debug!("Applying horizontal flip modification...");
+
+                    buffer_image.variant = map_buffer_image_variant!(&buffer_image.variant, |image_buffer| imageops::flip_horizontal(image_buffer));
+                },
+                ImageModification::FlipVertical => {
+                    log::debug!("Applying vertical flip modification...");
+
+                    buffer_image.variant = map_buffer_image_variant!(&buffer_image.variant, |image_buffer| imageops::flip_vertical(image_buffer));
+                },
+                // colour adjustments aren't part of the flattened modifications, they're made below.
+                _ => {},
             }
 
         }
+
+        // the image is as small as it's going to get by now so there's less to adjust.
+        if !flattened_modifications.adjustments.is_empty() {
+            log::debug!("Applying colour adjustment modifications...");
+
+            Self::adjust_buffer_image_variant(&mut buffer_image.variant, &flattened_modifications.adjustments);
+        }
+    }
+
+    fn adjust_buffer_image_variant(variant: &mut BufferImageVariant, adjustments: &[ImageModification]) {
+        match variant {
+            BufferImageVariant::Grey8(image_buffer) => adjust_samples(image_buffer, 1, false, adjustments),
+            BufferImageVariant::GreyA8(image_buffer) => adjust_samples(image_buffer, 2, true, adjustments),
+            BufferImageVariant::Rgb8(image_buffer) => adjust_samples(image_buffer, 3, false, adjustments),
+            BufferImageVariant::Rgba8(image_buffer) => adjust_samples(image_buffer, 4, true, adjustments),
+            BufferImageVariant::Grey16(image_buffer) => adjust_samples(image_buffer, 1, false, adjustments),
+            BufferImageVariant::GreyA16(image_buffer) => adjust_samples(image_buffer, 2, true, adjustments),
+            BufferImageVariant::Rgb16(image_buffer) => adjust_samples(image_buffer, 3, false, adjustments),
+            BufferImageVariant::Rgba16(image_buffer) => adjust_samples(image_buffer, 4, true, adjustments),
+            BufferImageVariant::Grey32F(image_buffer) => adjust_samples(image_buffer, 1, false, adjustments),
+            BufferImageVariant::GreyA32F(image_buffer) => adjust_samples(image_buffer, 2, true, adjustments),
+            BufferImageVariant::Rgb32F(image_buffer) => adjust_samples(image_buffer, 3, false, adjustments),
+            BufferImageVariant::Rgba32F(image_buffer) => adjust_samples(image_buffer, 4, true, adjustments),
+        }
     }
-}
\ No newline at end of file
+}
+
+/// `imageops::resize` takes the alpha to already be premultiplied, otherwise the colour of transparent pixels 
+/// gets averaged into the edges around them (dark fringes). The buffer is premultiplied in place as it's 
+/// thrown away after anyway, then the resized buffer has it's alpha unpremultiplied again.
+fn resize_premultiplied<P>(
+    image_buffer: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+    width: u32,
+    height: u32,
+    filter: FilterType,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: AlphaSample + 'static,
+{
+    premultiply_samples(image_buffer, P::CHANNEL_COUNT as usize);
+
+    let mut resized_image_buffer = imageops::resize(image_buffer, width, height, filter);
+    unpremultiply_samples(&mut resized_image_buffer, P::CHANNEL_COUNT as usize);
+
+    resized_image_buffer
+}
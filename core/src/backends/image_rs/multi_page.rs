@@ -0,0 +1,342 @@
+use std::{cmp::Reverse, collections::HashSet, io::{self, BufRead, Read, Seek, SeekFrom}};
+
+use image::{ColorType, ImageDecoder, ImageError, ImageResult, codecs::{ico::IcoDecoder, tiff::TiffDecoder}};
+
+use crate::{colour_type::ImageColourType, decoded_image::PageInfo, error::{Error, Result}, format::ImageFormat, reader::{EncodedImageBytes, EncodedImageReader}};
+
+const TIFF_TAG_NEW_SUBFILE_TYPE: u16 = 0x00FE;
+
+/// Stops us from looping forever on a corrupted (or malicious) tiff, no
+/// scanner is ever going to hand us a document with more pages than this.
+const MAX_TIFF_PAGES: usize = 10_000;
+
+/// Where a page lives inside of the encoded image.
+#[derive(Clone, Copy)]
+pub(super) enum PageSource {
+    /// Offset of the page's image file directory.
+    Tiff(u64),
+    /// Index of the page's entry in the ico directory.
+    Ico(usize),
+}
+
+impl PageSource {
+    pub fn find_all(buffer: &[u8], image_format: &ImageFormat) -> Vec<Self> {
+        match image_format {
+            ImageFormat::Tiff => tiff_page_offsets(buffer).into_iter().map(Self::Tiff).collect(),
+            ImageFormat::Ico => (0..ico_entry_count(buffer)).map(Self::Ico).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// image-rs only ever decodes the first page of a tiff and the "best" entry of an ico, so
+/// we get it to decode the other pages by handing it an image that starts at the page we want.
+pub(super) struct MultiPageImage {
+    encoded_image_bytes: EncodedImageBytes,
+    pub pages: Vec<(PageSource, PageInfo)>,
+    pub selected_page: Option<usize>,
+}
+
+impl MultiPageImage {
+    /// Pages we can't decode are skipped, so there may be fewer pages than `page_sources`.
+    pub fn new(encoded_image_bytes: EncodedImageBytes, page_sources: Vec<PageSource>) -> Self {
+        let mut multi_page_image = Self {
+            encoded_image_bytes,
+            pages: Vec::with_capacity(page_sources.len()),
+            selected_page: None,
+        };
+
+        for (index, page_source) in page_sources.into_iter().enumerate() {
+            let page_info = multi_page_image.page_decoder(page_source).and_then(|page_decoder| {
+                Ok(
+                    PageInfo {
+                        size: page_decoder.dimensions(),
+                        colour_type: ImageColourType::try_from(page_decoder.color_type())?,
+                    }
+                )
+            });
+
+            match page_info {
+                Ok(page_info) => multi_page_image.pages.push((page_source, page_info)),
+                Err(error) => log::warn!("Skipping page {} as it can't be decoded! Error: {}", index + 1, error),
+            }
+        }
+
+        // we show icos from the largest entry to the smallest, the
+        // largest entry is also what image-rs would have picked for us.
+        if let Some((PageSource::Ico(_), _)) = multi_page_image.pages.first() {
+            multi_page_image.pages.sort_by_key(
+                |(_, page_info)| Reverse(page_info.size.0 as u64 * page_info.size.1 as u64)
+            );
+        }
+
+        multi_page_image
+    }
+
+    pub fn page_decoder(&self, page_source: PageSource) -> Result<PageDecoder> {
+        let error_func = |error: ImageError| Error::DecoderInitFailure {
+            error: error.to_string(),
+        };
+
+        match page_source {
+            PageSource::Tiff(ifd_offset) => {
+                let tiff_page_reader = TiffPageReader::new(self.encoded_image_bytes.clone(), ifd_offset);
+
+                Ok(
+                    PageDecoder::Tiff(
+                        Box::new(TiffDecoder::new(tiff_page_reader).map_err(error_func)?)
+                    )
+                )
+            },
+            PageSource::Ico(entry_index) => {
+                let single_entry_ico = single_entry_ico(&self.encoded_image_bytes, entry_index)
+                    .ok_or_else(|| Error::DecoderInitFailure {
+                        error: format!("Ico directory entry {} is out of bounds!", entry_index),
+                    })?;
+
+                Ok(
+                    PageDecoder::Ico(
+                        IcoDecoder::new(EncodedImageReader::new(single_entry_ico.into())).map_err(error_func)?
+                    )
+                )
+            },
+        }
+    }
+}
+
+pub(super) enum PageDecoder {
+    // the tiff decoder is a lot larger than the ico one so we keep it on the heap.
+    Tiff(Box<TiffDecoder<TiffPageReader>>),
+    Ico(IcoDecoder<EncodedImageReader>),
+}
+
+impl ImageDecoder for PageDecoder {
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            PageDecoder::Tiff(tiff_decoder) => tiff_decoder.dimensions(),
+            PageDecoder::Ico(ico_decoder) => ico_decoder.dimensions(),
+        }
+    }
+
+    fn color_type(&self) -> ColorType {
+        match self {
+            PageDecoder::Tiff(tiff_decoder) => tiff_decoder.color_type(),
+            PageDecoder::Ico(ico_decoder) => ico_decoder.color_type(),
+        }
+    }
+
+    fn exif_metadata(&mut self) -> ImageResult<Option<Vec<u8>>> {
+        match self {
+            PageDecoder::Tiff(tiff_decoder) => tiff_decoder.exif_metadata(),
+            PageDecoder::Ico(ico_decoder) => ico_decoder.exif_metadata(),
+        }
+    }
+
+    fn read_image(self, buf: &mut [u8]) -> ImageResult<()>
+    where
+        Self: Sized,
+    {
+        match self {
+            PageDecoder::Tiff(tiff_decoder) => tiff_decoder.read_image(buf),
+            PageDecoder::Ico(ico_decoder) => ico_decoder.read_image(buf),
+        }
+    }
+
+    fn read_image_boxed(self: Box<Self>, buf: &mut [u8]) -> ImageResult<()> {
+        (*self).read_image(buf)
+    }
+}
+
+/// Walks the chain of image file directories (classic and big tiff), skipping
+/// the reduced resolution ones as those are just thumbnails of another page.
+fn tiff_page_offsets(buffer: &[u8]) -> Vec<u64> {
+    let mut page_offsets = Vec::new();
+
+    let (is_little_endian, is_big_tiff) = match buffer.get(0..4) {
+        Some([b'I', b'I', 42, 0]) => (true, false),
+        Some([b'M', b'M', 0, 42]) => (false, false),
+        Some([b'I', b'I', 43, 0]) => (true, true),
+        Some([b'M', b'M', 0, 43]) => (false, true),
+        _ => return page_offsets,
+    };
+
+    let read_uint = |offset: u64, length: usize| -> Option<u64> {
+        let start = usize::try_from(offset).ok()?;
+        let bytes = buffer.get(start..start.checked_add(length)?)?;
+
+        Some(
+            bytes.iter().enumerate().fold(0u64, |value, (index, byte)| {
+                let shift = match is_little_endian {
+                    true => index,
+                    false => length - 1 - index,
+                } * 8;
+
+                value | (*byte as u64) << shift
+            })
+        )
+    };
+
+    // big tiffs use 8 byte offsets and counts where classic tiffs use 4 (and 2 for the entry count).
+    let (offset_length, entry_count_length, entry_length) = match is_big_tiff {
+        true => (8, 8, 20),
+        false => (4, 2, 12),
+    };
+
+    let mut ifd_offset = match read_uint(4 + is_big_tiff as u64 * 4, offset_length) {
+        Some(offset) => offset,
+        None => return page_offsets,
+    };
+
+    let mut visited_offsets = HashSet::new();
+
+    while ifd_offset != 0 && page_offsets.len() < MAX_TIFF_PAGES && visited_offsets.insert(ifd_offset) {
+        let entry_count = match read_uint(ifd_offset, entry_count_length) {
+            // a corrupted count could have us checking entries way past the end of the buffer.
+            Some(count) => count.min((buffer.len() / entry_length) as u64),
+            None => break,
+        };
+
+        let entries_offset = ifd_offset + entry_count_length as u64;
+
+        let is_reduced_resolution = (0..entry_count).any(|index| {
+            let entry_offset = entries_offset + index * entry_length as u64;
+
+            // the subfile type is a single long (or short) so it always fits in the entry's value field.
+            read_uint(entry_offset, 2) == Some(TIFF_TAG_NEW_SUBFILE_TYPE as u64)
+                && match read_uint(entry_offset + 2, 2) {
+                    Some(3) => read_uint(entry_offset + 4 + offset_length as u64, 2),
+                    _ => read_uint(entry_offset + 4 + offset_length as u64, 4),
+                }.is_some_and(|subfile_type| subfile_type & 1 == 1)
+        });
+
+        if !is_reduced_resolution {
+            page_offsets.push(ifd_offset);
+        }
+
+        ifd_offset = match read_uint(entries_offset + entry_count * entry_length as u64, offset_length) {
+            Some(offset) => offset,
+            None => break,
+        };
+    }
+
+    page_offsets
+}
+
+/// Reads the tiff with it's header pointed at another image file directory so that becomes the first 
+/// page. Only the header is copied, the rest is read straight from the (shared) encoded image bytes.
+pub(super) struct TiffPageReader {
+    header: Vec<u8>,
+    encoded_image_bytes: EncodedImageBytes,
+    position: u64,
+}
+
+impl TiffPageReader {
+    fn new(encoded_image_bytes: EncodedImageBytes, ifd_offset: u64) -> Self {
+        // big tiffs have the biggest header, 8 bytes in then an 8 byte offset.
+        let mut header = encoded_image_bytes[..encoded_image_bytes.len().min(16)].to_vec();
+
+        if header.len() == 16 {
+            set_tiff_first_ifd_offset(&mut header, ifd_offset);
+        }
+
+        Self {
+            header,
+            encoded_image_bytes,
+            position: 0,
+        }
+    }
+}
+
+impl Read for TiffPageReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+
+        Ok(length)
+    }
+}
+
+impl BufRead for TiffPageReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let position = usize::try_from(self.position).unwrap_or(usize::MAX);
+
+        // the header is handed out on it's own so a read never goes past the end of it.
+        Ok(
+            match self.header.get(position..) {
+                Some(header) if !header.is_empty() => header,
+                _ => self.encoded_image_bytes.get(position..).unwrap_or(&[]),
+            }
+        )
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount as u64;
+    }
+}
+
+impl Seek for TiffPageReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let new_position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.encoded_image_bytes.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        match new_position {
+            Some(new_position) => {
+                self.position = new_position;
+                Ok(new_position)
+            },
+            None => Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")
+            ),
+        }
+    }
+}
+
+/// Points the tiff header at another image file directory so that becomes the first page.
+fn set_tiff_first_ifd_offset(buffer: &mut [u8], ifd_offset: u64) {
+    let is_little_endian = buffer[0] == b'I';
+    let is_big_tiff = buffer[2] == 43 || buffer[3] == 43;
+
+    match (is_big_tiff, is_little_endian) {
+        (true, true) => buffer[8..16].copy_from_slice(&ifd_offset.to_le_bytes()),
+        (true, false) => buffer[8..16].copy_from_slice(&ifd_offset.to_be_bytes()),
+        (false, true) => buffer[4..8].copy_from_slice(&(ifd_offset as u32).to_le_bytes()),
+        (false, false) => buffer[4..8].copy_from_slice(&(ifd_offset as u32).to_be_bytes()),
+    }
+}
+
+fn ico_entry_count(buffer: &[u8]) -> usize {
+    let entry_count = match buffer.get(4..6) {
+        Some(bytes) => u16::from_le_bytes([bytes[0], bytes[1]]) as usize,
+        None => return 0,
+    };
+
+    // don't trust the count over the directory entries we actually have.
+    entry_count.min(buffer.len().saturating_sub(6) / 16)
+}
+
+/// Builds an ico that only holds this one entry, so it's the only one image-rs can pick.
+fn single_entry_ico(buffer: &[u8], entry_index: usize) -> Option<Vec<u8>> {
+    let entry = buffer.get(6 + entry_index * 16..6 + (entry_index + 1) * 16)?;
+
+    let data_length = u32::from_le_bytes(entry[8..12].try_into().ok()?) as usize;
+    let data_offset = u32::from_le_bytes(entry[12..16].try_into().ok()?) as usize;
+
+    let data = buffer.get(data_offset..data_offset.checked_add(data_length)?)?;
+
+    let mut single_entry_ico = Vec::with_capacity(22 + data.len());
+
+    // keep the reserved and image type (icon or cursor) fields.
+    single_entry_ico.extend_from_slice(&buffer[0..4]);
+    single_entry_ico.extend_from_slice(&1u16.to_le_bytes());
+    single_entry_ico.extend_from_slice(&entry[0..12]);
+    single_entry_ico.extend_from_slice(&22u32.to_le_bytes());
+    single_entry_ico.extend_from_slice(data);
+
+    Some(single_entry_ico)
+}
@@ -0,0 +1,348 @@
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use png::{BitDepth, ColorType, InterlaceInfo, Limits, Transformations};
+
+use crate::{backends::image_rs::{ImageRSBackend, buffer_image::BufferImage}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent, ImageSize}, error::{Error, Result}, format::ImageFormat, image_info::metadata::ImageMetadata, limits::DecodeLimits, modifications::ModificationPipeline, pixels::Pixels, reader::EncodedImageBytes};
+
+/// How many snapshots a non-interlaced png is split into.
+const SNAPSHOT_COUNT: u32 = 8;
+
+/// The frame header marker of a progressive (huffman coded) jpeg.
+const JPEG_PROGRESSIVE_SOF: u8 = 0xC2;
+/// The marker that starts each scan of a jpeg.
+const JPEG_SOS: u8 = 0xDA;
+/// The end of image marker.
+const JPEG_EOI: u8 = 0xD9;
+
+/// The first row, row step, first column and column step of each of the Adam7 interlace passes.
+const ADAM7_PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 8, 0, 8),
+    (0, 8, 4, 8),
+    (4, 8, 0, 4),
+    (0, 4, 2, 4),
+    (2, 4, 0, 2),
+    (0, 2, 1, 2),
+    (1, 2, 0, 1),
+];
+
+impl ImageRSBackend {
+    /// Decodes a png row by row with the png crate directly, handing out a snapshot every few rows.
+    /// Interlaced pngs hand out a snapshot after each pass instead, with the missing
+    /// pixels filled in from the pass so the image sharpens up as it's decoded.
+    pub(super) fn decode_png_progressively<F>(
+        buffer: EncodedImageBytes,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+        mut on_snapshot: F,
+    ) -> Result<DecodedImage>
+    where
+        F: FnMut(DecodedImage),
+    {
+        log::debug!("Decoding png progressively with png decoder...");
+
+        // the png decoder's limit only covers what it allocates itself, not our image buffer.
+        let mut decoder = png::Decoder::new_with_limits(
+            Cursor::new(buffer.as_slice()),
+            Limits {
+                bytes: limits.max_alloc.map_or(usize::MAX, |max_alloc| usize::try_from(max_alloc).unwrap_or(usize::MAX))
+            }
+        );
+
+        // same as image-rs, palettes and low bit depths get expanded to 8 bit.
+        decoder.set_transformations(Transformations::EXPAND);
+
+        let mut reader = decoder.read_info().map_err(
+            |error| Error::DecoderInitFailure { error: error.to_string() }
+        )?;
+
+        let image_colour_type = match reader.output_color_type() {
+            (ColorType::Grayscale, BitDepth::Eight) => ImageColourType::Grey8,
+            (ColorType::Grayscale, BitDepth::Sixteen) => ImageColourType::Grey16,
+            (ColorType::GrayscaleAlpha, BitDepth::Eight) => ImageColourType::GreyA8,
+            (ColorType::GrayscaleAlpha, BitDepth::Sixteen) => ImageColourType::GreyA16,
+            (ColorType::Rgb, BitDepth::Eight) => ImageColourType::Rgb8,
+            (ColorType::Rgb, BitDepth::Sixteen) => ImageColourType::Rgb16,
+            (ColorType::Rgba, BitDepth::Eight) => ImageColourType::Rgba8,
+            (ColorType::Rgba, BitDepth::Sixteen) => ImageColourType::Rgba16,
+            _ => return Err(Error::UnsupportedColourType),
+        };
+
+        let bytes_per_sample = match reader.output_color_type().1 {
+            BitDepth::Sixteen => 2,
+            _ => 1,
+        };
+
+        let image_size = (reader.info().width, reader.info().height);
+        let is_interlaced = reader.info().interlaced;
+
+        limits.check(image_size, image_colour_type)?;
+
+        let bytes_per_pixel = image_colour_type.channel_count() * bytes_per_sample;
+        let row_length = image_size.0 as usize * bytes_per_pixel;
+
+        let mut image_bytes = vec![0u8; row_length * image_size.1 as usize];
+
+        let decoding_error = |error: png::DecodingError| Error::DecodingFailure {
+            error: format!("Png decoder failed to decode row: {}", error),
+        };
+
+        let truncated_error = || Error::DecodingFailure {
+            error: String::from("Png ended before all of it's rows were decoded!"),
+        };
+
+        match is_interlaced {
+            false => {
+                let rows_per_snapshot = (image_size.1 / SNAPSHOT_COUNT).max(1) as usize;
+
+                for index in 0..image_size.1 as usize {
+                    let row = reader.next_row()
+                        .map_err(decoding_error)?
+                        .ok_or_else(truncated_error)?;
+
+                    image_bytes[index * row_length..(index + 1) * row_length].copy_from_slice(row.data());
+
+                    let rows_decoded = index + 1;
+
+                    if rows_decoded % rows_per_snapshot == 0 && rows_decoded < image_size.1 as usize {
+                        log::debug!("Handing out snapshot of png at row {}...", rows_decoded);
+
+                        on_snapshot(
+                            Self::png_snapshot(&image_bytes, image_size, image_colour_type, &modifications, &image_format)?
+                        );
+                    }
+                }
+            },
+            true => {
+                for (pass, (first_row, row_step, first_column, _)) in ADAM7_PASSES.into_iter().enumerate() {
+                    // the png decoder skips passes that have no pixels in them.
+                    let pass_row_count = match image_size.0 > first_column && image_size.1 > first_row {
+                        true => (image_size.1 - first_row).div_ceil(row_step),
+                        false => 0,
+                    };
+
+                    if pass_row_count == 0 {
+                        continue;
+                    }
+
+                    for _ in 0..pass_row_count {
+                        let row = reader.next_interlaced_row()
+                            .map_err(decoding_error)?
+                            .ok_or_else(truncated_error)?;
+
+                        if let InterlaceInfo::Adam7(adam7_info) = row.interlace() {
+                            png::splat_interlaced_row(
+                                &mut image_bytes,
+                                row_length,
+                                row.data(),
+                                adam7_info,
+                                (bytes_per_pixel * 8) as u8
+                            );
+                        }
+                    }
+
+                    // the last pass is the finished image.
+                    if pass < ADAM7_PASSES.len() - 1 {
+                        log::debug!("Handing out snapshot of png at interlace pass {}...", pass + 1);
+
+                        on_snapshot(
+                            Self::png_snapshot(&image_bytes, image_size, image_colour_type, &modifications, &image_format)?
+                        );
+                    }
+                }
+            },
+        }
+
+        log::debug!("Png decoder successfully decoded all rows...");
+
+        let mut image_pixels = Self::png_bytes_to_pixels(image_bytes, bytes_per_sample);
+        let (mut size, mut colour_type) = (image_size, image_colour_type);
+
+        if !modifications.is_empty() {
+            log::debug!("Applying modifications to progressively decoded png...");
+
+            let mut buffer_image = BufferImage::from_pixels(image_pixels, image_size, image_colour_type)?;
+
+            Self::apply_modifications_to_buffer_image(modifications, &mut buffer_image);
+
+            (image_pixels, size, colour_type) = buffer_image.to_pixels();
+        }
+
+        Ok(
+            DecodedImage::new(
+                size,
+                image_format,
+                colour_type,
+                Self::get_decoded_image_metadata(image_exif_chunk),
+                DecodedImageContent::Static(image_pixels),
+            )
+        )
+    }
+
+    /// Snapshots are thrown away as soon as the next one comes along
+    /// so modifications are applied with the fastest filter we have.
+    fn png_snapshot(
+        image_bytes: &[u8],
+        image_size: ImageSize,
+        image_colour_type: ImageColourType,
+        modifications: &ModificationPipeline,
+        image_format: &ImageFormat,
+    ) -> Result<DecodedImage> {
+        let bytes_per_sample = match image_colour_type {
+            ImageColourType::Grey16 | ImageColourType::GreyA16 | ImageColourType::Rgb16 | ImageColourType::Rgba16 => 2,
+            _ => 1,
+        };
+
+        let mut pixels = Self::png_bytes_to_pixels(image_bytes.to_vec(), bytes_per_sample);
+        let (mut size, mut colour_type) = (image_size, image_colour_type);
+
+        if !modifications.is_empty() {
+            let mut buffer_image = BufferImage::from_pixels(pixels, image_size, image_colour_type)?;
+
+            Self::apply_modifications_to_buffer_image_with_filter(
+                modifications.clone(),
+                &mut buffer_image,
+                Some(FilterType::Nearest)
+            );
+
+            (pixels, size, colour_type) = buffer_image.to_pixels();
+        }
+
+        Ok(
+            DecodedImage::new(
+                size,
+                image_format.clone(),
+                colour_type,
+                ImageMetadata::default(),
+                DecodedImageContent::Static(pixels),
+            )
+        )
+    }
+
+    /// Hands out a snapshot of a progressive jpeg after each of it's scans (bar the last) by decoding the 
+    /// jpeg as if it ended there, jpeg-decoder renders whatever coefficients it has been given so far so 
+    /// each snapshot is a little sharper than the last. Baseline jpegs are a single scan so they get no 
+    /// snapshots, they don't take long to decode anyway.
+    ///
+    /// Each snapshot is decoded from the start of the jpeg, it's the final image that's decoded as normal.
+    pub(super) fn hand_out_jpeg_snapshots<F>(
+        buffer: &[u8],
+        modifications: &ModificationPipeline,
+        image_format: &ImageFormat,
+        limits: DecodeLimits,
+        mut on_snapshot: F,
+    ) where
+        F: FnMut(DecodedImage),
+    {
+        let scan_ends = Self::jpeg_scan_ends(buffer);
+
+        if scan_ends.len() < 2 {
+            return;
+        }
+
+        log::debug!("Handing out snapshots of progressive jpeg with {} scans...", scan_ends.len());
+
+        for (scan, &scan_end) in scan_ends[..scan_ends.len() - 1].iter().enumerate() {
+            let mut scan_bytes = buffer[..scan_end].to_vec();
+            scan_bytes.extend_from_slice(&[0xFF, JPEG_EOI]);
+
+            // snapshots are thrown away as soon as the next one comes along so they're resized with the fastest filter.
+            let snapshot = Self::decode_with_jpeg_decoder(
+                &scan_bytes,
+                modifications,
+                image_format,
+                &None,
+                limits,
+                true,
+                Some(FilterType::Nearest)
+            );
+
+            match snapshot {
+                Ok(Some(snapshot)) => {
+                    log::debug!("Handing out snapshot of jpeg at scan {}...", scan + 1);
+
+                    on_snapshot(snapshot);
+                },
+                // the final decode is what reports the error (if it runs into it too).
+                Ok(None) => return,
+                Err(error) => {
+                    log::warn!("Failed to decode snapshot of jpeg, no more snapshots will be handed out! Error: {}", error);
+
+                    return;
+                },
+            }
+        }
+    }
+
+    /// The offsets that each scan of a progressive jpeg ends at (where the marker 
+    /// after it's entropy coded data starts), empty for jpegs that aren't progressive.
+    fn jpeg_scan_ends(buffer: &[u8]) -> Vec<usize> {
+        let mut scan_ends = Vec::new();
+        let mut is_progressive = false;
+
+        // skip the start of image marker.
+        let mut index = 2;
+
+        while index + 1 < buffer.len() {
+            if buffer[index] != 0xFF {
+                break;
+            }
+
+            let marker = buffer[index + 1];
+
+            // markers can be padded out with any number of fill bytes.
+            if marker == 0xFF {
+                index += 1;
+                continue;
+            }
+
+            if marker == JPEG_EOI {
+                break;
+            }
+
+            let segment_length = match buffer.get(index + 2..index + 4) {
+                Some(length) => u16::from_be_bytes([length[0], length[1]]) as usize,
+                None => break,
+            };
+
+            index += 2 + segment_length;
+
+            if marker == JPEG_PROGRESSIVE_SOF {
+                is_progressive = true;
+            }
+
+            if marker != JPEG_SOS {
+                continue;
+            }
+
+            // the entropy coded data runs until the next marker that isn't a 
+            // stuffed 0xFF byte (0xFF 0x00) or a restart marker (0xFFD0 to 0xFFD7).
+            while index + 1 < buffer.len()
+                && !(buffer[index] == 0xFF && buffer[index + 1] != 0x00 && !(0xD0..=0xD7).contains(&buffer[index + 1]))
+            {
+                index += 1;
+            }
+
+            scan_ends.push(index);
+        }
+
+        match is_progressive {
+            true => scan_ends,
+            false => Vec::new(),
+        }
+    }
+
+    /// 16 bit pngs are stored big endian.
+    fn png_bytes_to_pixels(image_bytes: Vec<u8>, bytes_per_sample: usize) -> Pixels {
+        match bytes_per_sample {
+            2 => Pixels::U16(
+                image_bytes.chunks_exact(2)
+                    .map(|sample| u16::from_be_bytes([sample[0], sample[1]]))
+                    .collect()
+            ),
+            _ => Pixels::U8(image_bytes),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::io::Cursor;
+
+use image::{codecs::jpeg::JpegDecoder, imageops::FilterType};
+use jpeg_decoder::PixelFormat;
+
+use crate::{backends::{backend::pick_decode_scale, image_rs::{ImageRSBackend, buffer_image::BufferImage}}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, error::{Error, Result}, format::ImageFormat, limits::DecodeLimits, modifications::ModificationPipeline, pixels::Pixels, reader::EncodedImageReader};
+
+/// jpeg-decoder can scale the IDCT down to 1/2, 1/4 and 1/8 of the image.
+pub(super) const JPEG_DECODE_SCALES: &[u32] = &[1, 2, 4, 8];
+
+impl ImageRSBackend {
+    /// Decodes jpegs at a reduced scale with jpeg-decoder when we're asked to resize them 
+    /// down by at least half, otherwise they're decoded at full size with image-rs as usual.
+    pub(super) fn decode_jpeg(
+        cursor: EncodedImageReader,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        if !modifications.is_empty()
+            && let Some(decoded_image) = Self::decode_with_jpeg_decoder(
+                cursor.get_ref().as_slice(),
+                &modifications,
+                &image_format,
+                &image_exif_chunk,
+                limits,
+                false,
+                None
+            )? {
+            return Ok(decoded_image);
+        }
+
+        let jpeg_decoder = JpegDecoder::new(cursor)
+            .map_err(|error| Error::DecoderInitFailure { error: error.to_string() })?;
+
+        Self::decode_image(
+            jpeg_decoder,
+            modifications,
+            image_format,
+            image_exif_chunk,
+            limits
+        )
+    }
+
+    /// Decodes the jpeg with jpeg-decoder at the smallest scale the resize allows. Returns `None` if the 
+    /// jpeg is in a colour space we'd rather image-rs dealt with (CMYK and 16 bit lossless jpegs) or, 
+    /// unless `decode_at_full_size` is set, if there's no resize or no smaller scale to decode at.
+    ///
+    /// `filter` overrides the filter the rest of the resize is done with.
+    pub(super) fn decode_with_jpeg_decoder(
+        buffer: &[u8],
+        modifications: &ModificationPipeline,
+        image_format: &ImageFormat,
+        image_exif_chunk: &Option<Vec<u8>>,
+        limits: DecodeLimits,
+        decode_at_full_size: bool,
+        filter: Option<FilterType>,
+    ) -> Result<Option<DecodedImage>> {
+        let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(buffer));
+
+        decoder.read_info()
+            .map_err(|error| Error::DecoderInitFailure { error: error.to_string() })?;
+
+        let (image_size, image_colour_type) = match decoder.info() {
+            Some(info) => {
+                let image_colour_type = match info.pixel_format {
+                    PixelFormat::L8 => ImageColourType::Grey8,
+                    PixelFormat::RGB24 => ImageColourType::Rgb8,
+                    PixelFormat::L16 | PixelFormat::CMYK32 => return Ok(None),
+                };
+
+                ((info.width as u32, info.height as u32), image_colour_type)
+            },
+            None => return Ok(None),
+        };
+
+        let flattened_modifications = modifications.flatten(image_size);
+
+        // a crop leaves less of the image to be resized so it's the cropped size that decides the scale.
+        let cropped_size = match flattened_modifications.crop {
+            Some((_, _, width, height)) => (width, height),
+            None => image_size,
+        };
+
+        let scale = match flattened_modifications.resize {
+            Some(target_size) => pick_decode_scale(JPEG_DECODE_SCALES, cropped_size, target_size),
+            None => 1,
+        };
+
+        if scale == 1 && !decode_at_full_size {
+            return Ok(None);
+        }
+
+        let scaled_size = match scale {
+            1 => image_size,
+            scale => {
+                let (scaled_width, scaled_height) = decoder.scale(
+                    image_size.0.div_ceil(scale) as u16,
+                    image_size.1.div_ceil(scale) as u16
+                ).map_err(|error| Error::DecoderInitFailure { error: error.to_string() })?;
+
+                (scaled_width as u32, scaled_height as u32)
+            },
+        };
+
+        limits.check(scaled_size, image_colour_type)?;
+
+        log::debug!(
+            "Decoding jpeg at 1/{} scale ({}x{}) with jpeg-decoder...",
+            scale,
+            scaled_size.0,
+            scaled_size.1
+        );
+
+        let image_bytes = decoder.decode()
+            .map_err(|error| Error::DecodingFailure { error: error.to_string() })?;
+
+        let mut buffer_image = BufferImage::from_pixels(
+            Pixels::U8(image_bytes),
+            scaled_size,
+            image_colour_type
+        )?;
+
+        // the crop is of the full size image so it has to be scaled down with it.
+        let modifications: ModificationPipeline = flattened_modifications
+            .scale_crop(image_size, scaled_size)
+            .to_modifications()
+            .into_iter()
+            .collect();
+
+        // the rest of the way is resized with whatever filter was asked for (nearest for pixel art and so on).
+        Self::apply_modifications_to_buffer_image_with_filter(modifications, &mut buffer_image, filter);
+
+        let (pixels, size, colour_type) = buffer_image.to_pixels();
+
+        Ok(
+            Some(
+                DecodedImage::new(
+                    size,
+                    image_format.clone(),
+                    colour_type,
+                    Self::get_decoded_image_metadata(image_exif_chunk.clone()),
+                    DecodedImageContent::Static(pixels),
+                )
+            )
+        )
+    }
+}
@@ -1,4 +1,7 @@
 pub mod backend;
 
 pub mod image_rs;
-pub mod zune_image; // TODO: implement zune-image backend
\ No newline at end of file
+pub mod zune_image;
+pub mod resvg;
+pub mod rawloader;
+pub mod psd;
@@ -0,0 +1,159 @@
+use crate::{
+    backends::{backend::DecodeBackend, image_rs::ImageRSBackend, psd::document::PsdDocument}, decoded_image::{DecodedImage, DecodedImageContent}, error::{Error, Result}, format::ImageFormat, image_info::{info::DocumentInfo, metadata::ImageMetadata}, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, reader::{EncodedImageBytes, ImageReader, ImageReaderData}
+};
+
+enum Source {
+    Encoded(EncodedImageBytes),
+    Decoded(Box<DecodedImage>),
+}
+
+/// A photoshop document (PSD and PSB) backend that decodes the flattened composite image.
+///
+/// Like the rawloader backend, modifications are handed over to the image-rs backend.
+pub struct PsdBackend {
+    source: Source,
+    modifications: ModificationPipeline,
+    image_format: ImageFormat,
+    limits: DecodeLimits,
+}
+
+impl DecodeBackend for PsdBackend {
+    const SUPPORTED_FORMATS: &[ImageFormat] = &[
+        ImageFormat::Psd,
+        ImageFormat::Psb,
+    ];
+
+    fn from_reader(image_reader: ImageReader) -> Result<Self> {
+        if !Self::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            return Err(
+                Error::DecoderImageFormatNotSupported {
+                    image_format: image_reader.image_format.to_string(),
+                    backend: String::from("psd"),
+                }
+            );
+        }
+
+        let source = match image_reader.data {
+            ImageReaderData::EncodedImage(cursor) => {
+                log::debug!("Initializing psd backend with photoshop document buffer...");
+
+                Source::Encoded(cursor.into_inner())
+            },
+            ImageReaderData::DecodedImage(decoded_image) => {
+                log::debug!("Initializing psd backend from already decoded composite image...");
+
+                Source::Decoded(decoded_image)
+            },
+        };
+
+        Ok(
+            Self {
+                source,
+                modifications: ModificationPipeline::new(),
+                image_format: image_reader.image_format,
+                limits: image_reader.limits,
+            }
+        )
+    }
+
+    fn modify<I>(&mut self, modifications: I)
+    where
+        I: IntoIterator<Item = ImageModification>,
+    {
+        self.modifications.extend(modifications);
+    }
+
+    fn decode(self) -> Result<DecodedImage> {
+        let buffer = match self.source {
+            Source::Encoded(buffer) => buffer,
+            Source::Decoded(decoded_image) => {
+                let metadata = decoded_image.info.metadata.clone();
+                let document = decoded_image.info.document.clone();
+
+                return Self::apply_modifications(
+                    *decoded_image,
+                    self.modifications,
+                    self.image_format,
+                    metadata,
+                    document,
+                    self.limits
+                );
+            },
+        };
+
+        log::debug!("Decoding photoshop document's composite image...");
+
+        let document = PsdDocument::parse(&buffer)?;
+
+        self.limits.check_dimensions(document.size)?;
+        self.limits.check_allocation(document.composite_allocation())?;
+        let (pixels, colour_type) = document.decode_composite()?;
+
+        log::debug!("Psd backend successfully decoded composite image...");
+
+        let metadata = match document.exif_chunk {
+            Some(exif_chunk) => match ImageMetadata::new(exif_chunk.to_vec()) {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    log::warn!("{}", error);
+
+                    ImageMetadata::default()
+                },
+            },
+            None => ImageMetadata::default(),
+        };
+
+        let document_info = DocumentInfo {
+            size: document.size,
+            layer_count: document.layer_count,
+        };
+
+        let decoded_image = DecodedImage::new(
+            document.size,
+            self.image_format.clone(),
+            colour_type,
+            ImageMetadata::default(),
+            DecodedImageContent::Static(pixels),
+        );
+
+        Self::apply_modifications(
+            decoded_image,
+            self.modifications,
+            self.image_format,
+            metadata,
+            Some(document_info),
+            self.limits
+        )
+    }
+}
+
+impl PsdBackend {
+    fn apply_modifications(
+        decoded_image: DecodedImage,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        metadata: ImageMetadata,
+        document: Option<DocumentInfo>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        let mut decoded_image = match modifications.is_empty() {
+            true => decoded_image,
+            false => {
+                log::debug!("Handing composite image to image-rs backend to apply modifications...");
+
+                let mut backend = ImageRSBackend::from_reader(
+                    ImageReader::new(decoded_image, image_format.clone()).with_limits(limits)
+                )?;
+
+                backend.modify(modifications);
+                backend.decode()?
+            },
+        };
+
+        decoded_image.info.format = image_format;
+        decoded_image.info.metadata = metadata;
+        decoded_image.info.document = document;
+
+        Ok(decoded_image)
+    }
+}
@@ -0,0 +1,516 @@
+use rayon::{iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator}, slice::ParallelSliceMut};
+
+use crate::{colour_type::ImageColourType, decoded_image::ImageSize, error::{Error, Result}, pixels::Pixels};
+
+const IMAGE_RESOURCE_VERSION_INFO: u16 = 0x0421;
+const IMAGE_RESOURCE_EXIF_DATA: u16 = 0x0422;
+
+/// Additional layer info keys that have an 8 byte length in PSBs.
+const PSB_LONG_LENGTH_KEYS: &[&[u8; 4]] = &[
+    b"LMsk", b"Lr16", b"Lr32", b"Layr", b"Mt16", b"Mt32", b"Mtrn",
+    b"Alph", b"FMsk", b"lnk2", b"FEid", b"FXid", b"PxSD",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColourMode {
+    Bitmap,
+    Grayscale,
+    Indexed,
+    Rgb,
+    Cmyk,
+    Duotone,
+    Unsupported(u16),
+}
+
+impl From<u16> for ColourMode {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => ColourMode::Bitmap,
+            1 => ColourMode::Grayscale,
+            2 => ColourMode::Indexed,
+            3 => ColourMode::Rgb,
+            4 => ColourMode::Cmyk,
+            8 => ColourMode::Duotone,
+            other => ColourMode::Unsupported(other),
+        }
+    }
+}
+
+/// A parsed PSD (or PSB, the "large document" version of it).
+///
+/// We don't composite the layers ourselves, Photoshop saves a flattened copy of the
+/// whole document at the end of the file (the image data section) which is what we decode.
+pub(crate) struct PsdDocument<'a> {
+    pub size: ImageSize,
+    pub layer_count: u32,
+    pub exif_chunk: Option<&'a [u8]>,
+    is_big: bool,
+    channel_count: usize,
+    depth: u16,
+    colour_mode: ColourMode,
+    palette: &'a [u8],
+    /// A negative layer count means the first extra channel of
+    /// the composite is the transparency of the merged result.
+    has_merged_transparency: bool,
+    image_data: &'a [u8],
+}
+
+impl<'a> PsdDocument<'a> {
+    pub fn parse(buffer: &'a [u8]) -> Result<Self> {
+        let mut reader = ByteReader { buffer, position: 0 };
+
+        if reader.read_bytes(4)? != b"8BPS" {
+            return Err(decoding_failure("Not a photoshop document, the signature is wrong!"));
+        }
+
+        let is_big = match reader.read_u16()? {
+            1 => false,
+            2 => true,
+            version => return Err(decoding_failure(&format!("Unknown photoshop document version ({})!", version))),
+        };
+
+        reader.read_bytes(6)?; // reserved
+
+        let channel_count = reader.read_u16()? as usize;
+        let height = reader.read_u32()?;
+        let width = reader.read_u32()?;
+        let depth = reader.read_u16()?;
+        let colour_mode = ColourMode::from(reader.read_u16()?);
+
+        let colour_mode_data_length = reader.read_u32()? as usize;
+        let palette = reader.read_bytes(colour_mode_data_length)?;
+
+        let image_resources_length = reader.read_u32()? as usize;
+        let exif_chunk = Self::parse_image_resources(reader.read_bytes(image_resources_length)?)?;
+
+        let layer_and_mask_length = reader.read_length(is_big)?;
+        let layer_count = Self::parse_layer_count(reader.read_bytes(layer_and_mask_length)?, is_big)?;
+
+        log::debug!(
+            "Parsed photoshop document header ({}x{}, {} channels, {} bit, {:?}, {} layers)...",
+            width,
+            height,
+            channel_count,
+            depth,
+            colour_mode,
+            layer_count.map_or(0, |count| count.unsigned_abs())
+        );
+
+        Ok(
+            Self {
+                size: (width, height),
+                layer_count: layer_count.map_or(0, |count| count.unsigned_abs() as u32),
+                exif_chunk,
+                is_big,
+                channel_count,
+                depth,
+                colour_mode,
+                palette,
+                has_merged_transparency: layer_count.is_some_and(|count| count < 0),
+                image_data: &buffer[reader.position..],
+            }
+        )
+    }
+
+    fn parse_image_resources(section: &'a [u8]) -> Result<Option<&'a [u8]>> {
+        let mut reader = ByteReader { buffer: section, position: 0 };
+        let mut exif_chunk = None;
+
+        while reader.remaining() >= 12 {
+            if reader.read_bytes(4)? != b"8BIM" {
+                break;
+            }
+
+            let id = reader.read_u16()?;
+
+            // pascal string name, padded so the length byte and name are even.
+            let name_length = reader.read_bytes(1)?[0] as usize;
+            reader.read_bytes(name_length + (name_length + 1) % 2)?;
+
+            let data_length = reader.read_u32()? as usize;
+            let data = reader.read_bytes(data_length)?;
+            reader.read_bytes(data_length % 2)?;
+
+            match id {
+                IMAGE_RESOURCE_EXIF_DATA => exif_chunk = Some(data),
+                // version, then whether there's real merged data.
+                IMAGE_RESOURCE_VERSION_INFO if data.get(4) == Some(&0) => log::warn!(
+                    "Photoshop document was saved without 'maximize compatibility', \
+                    the composite image will not have the document's layers in it!"
+                ),
+                _ => {},
+            }
+        }
+
+        Ok(exif_chunk)
+    }
+
+    /// The layer count is at the start of the layer info, or for 16 and 32 bit
+    /// documents in an `Lr16` / `Lr32` block of the additional layer information.
+    fn parse_layer_count(section: &[u8], is_big: bool) -> Result<Option<i16>> {
+        let mut reader = ByteReader { buffer: section, position: 0 };
+
+        if reader.remaining() == 0 {
+            return Ok(None);
+        }
+
+        let layer_info_length = reader.read_length(is_big)?;
+        let layer_info = reader.read_bytes(layer_info_length)?;
+
+        if layer_info.len() >= 2 {
+            return Ok(Some(i16::from_be_bytes([layer_info[0], layer_info[1]])));
+        }
+
+        let global_mask_length = reader.read_u32()? as usize;
+        reader.read_bytes(global_mask_length)?;
+
+        while reader.remaining() >= 12 {
+            let signature = reader.read_bytes(4)?;
+
+            if signature != b"8BIM" && signature != b"8B64" {
+                break;
+            }
+
+            let key: &[u8; 4] = reader.read_bytes(4)?.try_into().unwrap();
+
+            let length = match is_big && PSB_LONG_LENGTH_KEYS.contains(&key) {
+                true => reader.read_u64()? as usize,
+                false => reader.read_u32()? as usize,
+            };
+
+            let data = reader.read_bytes(length)?;
+
+            if matches!(key, b"Lr16" | b"Lr32" | b"Layr") && data.len() >= 2 {
+                return Ok(Some(i16::from_be_bytes([data[0], data[1]])));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Roughly how many bytes decoding the composite will allocate, the planes 
+    /// are read in separately before being interleaved into pixels.
+    pub fn composite_allocation(&self) -> u64 {
+        let bytes_per_sample = (self.depth as u64).div_ceil(8);
+        let plane_count = self.channel_count.min(4) as u64;
+
+        // indexed documents end up as 8 bit rgb.
+        let pixel_bytes = match self.colour_mode {
+            ColourMode::Indexed => 3,
+            _ => plane_count * bytes_per_sample,
+        };
+
+        self.size.0 as u64 * self.size.1 as u64 * (plane_count * bytes_per_sample + pixel_bytes)
+    }
+
+    /// Decodes the flattened composite image into interleaved pixels.
+    pub fn decode_composite(&self) -> Result<(Pixels, ImageColourType)> {
+        let colour_channel_count = match self.colour_mode {
+            ColourMode::Bitmap | ColourMode::Grayscale | ColourMode::Indexed | ColourMode::Duotone => 1,
+            ColourMode::Rgb => 3,
+            ColourMode::Cmyk => 4,
+            ColourMode::Unsupported(mode) => return Err(
+                decoding_failure(&format!("Photoshop documents in colour mode {} are not supported!", mode))
+            ),
+        };
+
+        let is_depth_supported = match self.colour_mode {
+            ColourMode::Bitmap => self.depth == 1,
+            ColourMode::Indexed => self.depth == 8,
+            _ => self.depth == 8 || self.depth == 16,
+        };
+
+        if !is_depth_supported {
+            return Err(
+                decoding_failure(&format!("{} bit {:?} photoshop documents are not supported!", self.depth, self.colour_mode))
+            );
+        }
+
+        if self.channel_count < colour_channel_count {
+            return Err(decoding_failure("Photoshop document has less channels than it's colour mode needs!"));
+        }
+
+        let has_alpha = self.has_merged_transparency
+            && self.channel_count > colour_channel_count
+            && !matches!(self.colour_mode, ColourMode::Bitmap | ColourMode::Indexed);
+
+        let plane_count = colour_channel_count + has_alpha as usize;
+        let planes = self.read_planes(plane_count)?;
+
+        let pixel_count = self.size.0 as usize * self.size.1 as usize;
+
+        let decoded = match (self.colour_mode, self.depth) {
+            (ColourMode::Bitmap, _) => {
+                let row_length = (self.size.0 as usize).div_ceil(8);
+                let width = self.size.0 as usize;
+
+                let pixels = (0..pixel_count)
+                    .map(|index| {
+                        let (x, y) = (index % width, index / width);
+                        let bit = (planes[0][y * row_length + x / 8] >> (7 - x % 8)) & 1;
+
+                        // a set bit is black.
+                        match bit {
+                            1 => 0,
+                            _ => u8::MAX,
+                        }
+                    })
+                    .collect();
+
+                (Pixels::U8(pixels), ImageColourType::Grey8)
+            },
+            (ColourMode::Indexed, _) => {
+                if self.palette.len() < 768 {
+                    return Err(decoding_failure("Indexed photoshop document has no colour table!"));
+                }
+
+                let mut pixels = vec![0u8; pixel_count * 3];
+
+                pixels.par_chunks_mut(3)
+                    .zip(planes[0].par_iter())
+                    .for_each(|(pixel, &index)| {
+                        // the colour table is all the reds, then all the greens and then all the blues.
+                        for (channel, value) in pixel.iter_mut().enumerate() {
+                            *value = self.palette[channel * 256 + index as usize];
+                        }
+                    });
+
+                (Pixels::U8(pixels), ImageColourType::Rgb8)
+            },
+            (colour_mode, 8) => {
+                let pixels = interleave(&planes, pixel_count, colour_mode, u8::MAX as u32, |sample| sample as u32);
+
+                (Pixels::U8(pixels), get_colour_type(colour_mode, has_alpha, false))
+            },
+            (colour_mode, _) => {
+                let planes: Vec<Vec<u16>> = planes.iter()
+                    .map(|plane| {
+                        plane.chunks_exact(2)
+                            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+                            .collect()
+                    })
+                    .collect();
+
+                let pixels = interleave(&planes, pixel_count, colour_mode, u16::MAX as u32, |sample| sample as u32);
+
+                (Pixels::U16(pixels), get_colour_type(colour_mode, has_alpha, true))
+            },
+        };
+
+        Ok(decoded)
+    }
+
+    /// Reads the first `plane_count` channels of the image data section as separate planes.
+    fn read_planes(&self, plane_count: usize) -> Result<Vec<Vec<u8>>> {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+
+        let row_length = match self.depth {
+            1 => width.div_ceil(8),
+            depth => width * (depth as usize / 8),
+        };
+
+        let mut reader = ByteReader { buffer: self.image_data, position: 0 };
+        let compression = reader.read_u16()?;
+
+        let mut planes = vec![vec![0u8; row_length * height]; plane_count];
+
+        match compression {
+            0 => {
+                for plane in planes.iter_mut() {
+                    plane.copy_from_slice(reader.read_bytes(row_length * height)?);
+                }
+            },
+            1 => {
+                // the byte counts of every row of every channel come first, then the rows.
+                let mut row_byte_counts = Vec::with_capacity(self.channel_count * height);
+
+                for _ in 0..self.channel_count * height {
+                    row_byte_counts.push(
+                        match self.is_big {
+                            true => reader.read_u32()? as usize,
+                            false => reader.read_u16()? as usize,
+                        }
+                    );
+                }
+
+                let mut row_offsets = Vec::with_capacity(row_byte_counts.len());
+                let mut offset = reader.position;
+
+                for byte_count in &row_byte_counts {
+                    row_offsets.push(offset);
+                    offset += byte_count;
+                }
+
+                if offset > self.image_data.len() {
+                    return Err(decoding_failure("Photoshop document's image data is truncated!"));
+                }
+
+                for (channel, plane) in planes.iter_mut().enumerate() {
+                    plane.par_chunks_mut(row_length)
+                        .enumerate()
+                        .try_for_each(|(y, row)| {
+                            let row_index = channel * height + y;
+                            let start = row_offsets[row_index];
+
+                            unpack_bits(&self.image_data[start..start + row_byte_counts[row_index]], row)
+                        })?;
+                }
+            },
+            compression => return Err(
+                decoding_failure(&format!("Unsupported photoshop image data compression ({})!", compression))
+            ),
+        }
+
+        Ok(planes)
+    }
+}
+
+fn get_colour_type(colour_mode: ColourMode, has_alpha: bool, is_16_bit: bool) -> ImageColourType {
+    let is_grey = matches!(colour_mode, ColourMode::Grayscale | ColourMode::Duotone);
+
+    match (is_grey, has_alpha, is_16_bit) {
+        (true, false, false) => ImageColourType::Grey8,
+        (true, true, false) => ImageColourType::GreyA8,
+        (true, false, true) => ImageColourType::Grey16,
+        (true, true, true) => ImageColourType::GreyA16,
+        (false, false, false) => ImageColourType::Rgb8,
+        (false, true, false) => ImageColourType::Rgba8,
+        (false, false, true) => ImageColourType::Rgb16,
+        (false, true, true) => ImageColourType::Rgba16,
+    }
+}
+
+/// Interleaves the planes into pixels, converting CMYK(A) to RGB(A) on the way.
+fn interleave<T>(
+    planes: &[Vec<T>],
+    pixel_count: usize,
+    colour_mode: ColourMode,
+    max: u32,
+    to_u32: impl Fn(T) -> u32 + Sync,
+) -> Vec<T>
+where
+    T: Copy + Default + Send + Sync + TryFrom<u32>,
+{
+    let output_channel_count = match colour_mode {
+        ColourMode::Cmyk => planes.len() - 1,
+        _ => planes.len(),
+    };
+
+    let mut pixels = vec![T::default(); pixel_count * output_channel_count];
+
+    pixels.par_chunks_mut(output_channel_count)
+        .enumerate()
+        .for_each(|(index, pixel)| {
+            match colour_mode {
+                // photoshop stores CMYK inverted (max is no ink), so
+                // it's just the colour multiplied by the key (black).
+                ColourMode::Cmyk => {
+                    let key = to_u32(planes[3][index]);
+
+                    for channel in 0..3 {
+                        let value = to_u32(planes[channel][index]) * key / max;
+                        pixel[channel] = T::try_from(value).unwrap_or_default();
+                    }
+
+                    if output_channel_count == 4 {
+                        pixel[3] = planes[4][index];
+                    }
+                },
+                _ => {
+                    for (channel, value) in pixel.iter_mut().enumerate() {
+                        *value = planes[channel][index];
+                    }
+                },
+            }
+        });
+
+    pixels
+}
+
+/// Decompresses a PackBits (the RLE Apple came up with) compressed row.
+fn unpack_bits(mut data: &[u8], row: &mut [u8]) -> Result<()> {
+    let mut position = 0;
+
+    while position < row.len() && !data.is_empty() {
+        let header = data[0] as i8;
+        data = &data[1..];
+
+        match header {
+            // copy the next n + 1 bytes literally.
+            0..=127 => {
+                let count = header as usize + 1;
+
+                if data.len() < count || position + count > row.len() {
+                    return Err(decoding_failure("Photoshop document has a corrupted RLE row!"));
+                }
+
+                row[position..position + count].copy_from_slice(&data[..count]);
+
+                data = &data[count..];
+                position += count;
+            },
+            // a no-op.
+            -128 => {},
+            // repeat the next byte 1 - n + 1 times.
+            _ => {
+                let count = 1 - header as isize;
+                let count = count as usize;
+
+                if data.is_empty() || position + count > row.len() {
+                    return Err(decoding_failure("Photoshop document has a corrupted RLE row!"));
+                }
+
+                row[position..position + count].fill(data[0]);
+
+                data = &data[1..];
+                position += count;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+struct ByteReader<'a> {
+    buffer: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn remaining(&self) -> usize {
+        self.buffer.len().saturating_sub(self.position)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        let bytes = self.buffer.get(self.position..self.position.saturating_add(length))
+            .ok_or_else(|| decoding_failure("Photoshop document is truncated!"))?;
+
+        self.position += length;
+
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Section lengths are 8 bytes in PSBs instead of 4.
+    fn read_length(&mut self, is_big: bool) -> Result<usize> {
+        match is_big {
+            true => Ok(self.read_u64()? as usize),
+            false => Ok(self.read_u32()? as usize),
+        }
+    }
+}
+
+fn decoding_failure(error: &str) -> Error {
+    Error::DecodingFailure { error: error.to_string() }
+}
@@ -0,0 +1,4 @@
+mod backend;
+pub use backend::*;
+
+mod document;
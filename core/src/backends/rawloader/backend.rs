@@ -0,0 +1,250 @@
+use std::io::Cursor;
+
+use crate::{
+    backends::{backend::DecodeBackend, image_rs::ImageRSBackend, rawloader::{develop::{DEVELOP_BYTES_PER_PIXEL, develop, develop_allocation}, tiff::Tiff}}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, error::{Error, Result}, format::ImageFormat, image_info::metadata::ImageMetadata, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, orientation::ImageOrientation, pixels::Pixels, reader::{EncodedImageBytes, ImageReader, ImageReaderData}
+};
+
+enum Source {
+    Encoded(EncodedImageBytes),
+    Decoded(Box<DecodedImage>),
+}
+
+/// A camera raw backend that develops the sensor data with rawloader.
+///
+/// Modifications (and already decoded images) are handed over to the image-rs
+/// backend once the raw has been developed, there's no point duplicating all that here.
+pub struct RawloaderBackend {
+    source: Source,
+    modifications: ModificationPipeline,
+    image_format: ImageFormat,
+    use_embedded_preview: bool,
+    limits: DecodeLimits,
+    auto_orientation: bool,
+}
+
+impl DecodeBackend for RawloaderBackend {
+    const SUPPORTED_FORMATS: &[ImageFormat] = &[
+        ImageFormat::Dng,
+        ImageFormat::Cr2,
+        ImageFormat::Nef,
+        ImageFormat::Arw,
+    ];
+
+    fn from_reader(image_reader: ImageReader) -> Result<Self> {
+        if !Self::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            return Err(
+                Error::DecoderImageFormatNotSupported {
+                    image_format: image_reader.image_format.to_string(),
+                    backend: String::from("rawloader"),
+                }
+            );
+        }
+
+        let source = match image_reader.data {
+            ImageReaderData::EncodedImage(cursor) => {
+                log::debug!("Initializing rawloader backend with raw image buffer...");
+
+                Source::Encoded(cursor.into_inner())
+            },
+            ImageReaderData::DecodedImage(decoded_image) => {
+                log::debug!("Initializing rawloader backend from already developed raw image...");
+
+                Source::Decoded(decoded_image)
+            },
+        };
+
+        Ok(
+            Self {
+                source,
+                modifications: ModificationPipeline::new(),
+                image_format: image_reader.image_format,
+                use_embedded_preview: false,
+                limits: image_reader.limits,
+                auto_orientation: image_reader.auto_orientation,
+            }
+        )
+    }
+
+    fn modify<I>(&mut self, modifications: I)
+    where
+        I: IntoIterator<Item = ImageModification>,
+    {
+        self.modifications.extend(modifications);
+    }
+
+    fn decode(self) -> Result<DecodedImage> {
+        let buffer = match self.source {
+            Source::Encoded(buffer) => buffer,
+            Source::Decoded(decoded_image) => {
+                let metadata = decoded_image.info.metadata.clone();
+
+                return Self::apply_modifications(
+                    *decoded_image,
+                    self.modifications,
+                    self.image_format,
+                    metadata,
+                    self.limits
+                );
+            },
+        };
+
+        let metadata = Self::get_raw_metadata(&buffer);
+
+        // the raw's orientation covers the embedded preview too, previews don't have one of their own.
+        let orientation = match self.auto_orientation {
+            true => metadata.orientation,
+            false => ImageOrientation::Normal,
+        };
+
+        // the raw is oriented before any of the modifications asked of us are made.
+        let modifications = self.modifications.oriented_first(orientation);
+
+        if self.use_embedded_preview {
+            match Self::decode_embedded_preview(&buffer, modifications.clone(), self.limits) {
+                Ok(Some(mut decoded_image)) => {
+                    decoded_image.info.format = self.image_format;
+                    decoded_image.info.metadata = metadata;
+
+                    return Ok(decoded_image);
+                },
+                Ok(None) => log::debug!(
+                    "Raw image has no full-size embedded preview, developing the raw instead..."
+                ),
+                // the preview being too big means the raw is too, no point trying to develop it.
+                Err(error @ (Error::MemoryLimitExceeded { .. } | Error::ImageDimensionsLimitExceeded { .. })) => {
+                    return Err(error);
+                },
+                Err(error) => log::warn!(
+                    "Failed to decode the raw's embedded preview, developing the raw instead... Error: {:?}",
+                    error
+                ),
+            }
+        }
+
+        // rawloader gives us no way to limit it so we check the raw's size ourselves before it allocates 
+        // anything, taking the sensor data to be the usual one 16 bit sample for every photosite.
+        if let Some(raw_size) = Tiff::parse(&buffer).and_then(|tiff| tiff.largest_size()) {
+            self.limits.check_dimensions(raw_size)?;
+            self.limits.check_allocation(
+                raw_size.0 as u64 * raw_size.1 as u64 * (2 + DEVELOP_BYTES_PER_PIXEL)
+            )?;
+        }
+
+        log::debug!("Decoding raw image with rawloader...");
+
+        let raw_image = rawloader::decode(&mut buffer.as_slice())
+            .map_err(|error| Error::DecodingFailure { error: error.to_string() })?;
+
+        // checked again now we know how big the sensor data really is (or if the raw isn't a tiff at all).
+        self.limits.check_dimensions((raw_image.width as u32, raw_image.height as u32))?;
+        self.limits.check_allocation(develop_allocation(&raw_image))?;
+
+        let (pixels, size) = develop(raw_image)?;
+
+        log::debug!("Rawloader backend successfully developed raw image...");
+
+        let decoded_image = DecodedImage::new(
+            size,
+            self.image_format.clone(),
+            ImageColourType::Rgb16,
+            ImageMetadata::default(),
+            DecodedImageContent::Static(Pixels::U16(pixels)),
+        );
+
+        Self::apply_modifications(
+            decoded_image,
+            modifications,
+            self.image_format,
+            metadata,
+            self.limits
+        )
+    }
+}
+
+impl RawloaderBackend {
+    /// Decode the full-size jpeg preview the camera embedded into the raw instead
+    /// of developing the raw. It's a LOT faster but it's only 8 bit and the camera's
+    /// own processing is baked in, so this should only be used if the user prefers speed.
+    pub fn use_embedded_preview(&mut self, use_embedded_preview: bool) {
+        self.use_embedded_preview = use_embedded_preview;
+    }
+
+    fn decode_embedded_preview(buffer: &[u8], modifications: ModificationPipeline, limits: DecodeLimits) -> Result<Option<DecodedImage>> {
+        let tiff = match Tiff::parse(buffer) {
+            Some(tiff) => tiff,
+            None => return Ok(None),
+        };
+
+        let (preview_range, (preview_width, preview_height)) = match tiff.largest_jpeg_preview() {
+            Some(preview) => preview,
+            None => return Ok(None),
+        };
+
+        // we don't want the tiny thumbnails, only previews that are (nearly) the size of the raw.
+        if let Some((raw_width, raw_height)) = tiff.largest_size()
+            && preview_width * 2 < raw_width && preview_height * 2 < raw_height {
+            return Ok(None);
+        }
+
+        log::debug!(
+            "Decoding raw's embedded {}x{} jpeg preview with image-rs...",
+            preview_width,
+            preview_height
+        );
+
+        let preview_reader = ImageReader::new(
+            Cursor::new(EncodedImageBytes::from(buffer[preview_range].to_vec())),
+            ImageFormat::Jpeg
+        ).with_limits(limits).with_auto_orientation(false);
+
+        // the crop is of the raw which the preview might be a little smaller than.
+        let modifications = match tiff.largest_size() {
+            Some(raw_size) => modifications.scaled_to_source(raw_size, (preview_width, preview_height)),
+            None => modifications,
+        };
+
+        let mut backend = ImageRSBackend::from_reader(preview_reader)?;
+        backend.modify(modifications);
+
+        Ok(Some(backend.decode()?))
+    }
+
+    fn apply_modifications(
+        decoded_image: DecodedImage,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        metadata: ImageMetadata,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        let mut decoded_image = match modifications.is_empty() {
+            true => decoded_image,
+            false => {
+                log::debug!("Handing developed raw image to image-rs backend to apply modifications...");
+
+                let mut backend = ImageRSBackend::from_reader(
+                    ImageReader::new(decoded_image, image_format.clone()).with_limits(limits)
+                )?;
+
+                backend.modify(modifications);
+                backend.decode()?
+            },
+        };
+
+        decoded_image.info.format = image_format;
+        decoded_image.info.metadata = metadata;
+
+        Ok(decoded_image)
+    }
+
+    /// Raws are tiff containers so the whole file can be handed to the exif reader.
+    fn get_raw_metadata(buffer: &[u8]) -> ImageMetadata {
+        match ImageMetadata::new(buffer.to_vec()) {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                log::warn!("{}", error);
+
+                ImageMetadata::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use rawloader::{RawImage, RawImageData};
+use rayon::{iter::{IndexedParallelIterator, ParallelIterator}, slice::{ParallelSlice, ParallelSliceMut}};
+
+use crate::{decoded_image::ImageSize, error::{Error, Result}};
+
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412453, 0.357580, 0.180423],
+    [0.212671, 0.715160, 0.072169],
+    [0.019334, 0.119193, 0.950227],
+];
+
+/// How much of the s-curve gets blended into the base tone curve.
+const BASE_CURVE_CONTRAST: f32 = 0.25;
+
+/// Bytes per pixel developing allocates on top of rawloader's sensor data, the f32 RGB
+/// buffer the raw is developed in (12) and the 16 bit RGB pixels that come out of it (6).
+pub(super) const DEVELOP_BYTES_PER_PIXEL: u64 = 18;
+
+/// The most memory (in bytes) there is allocated at once while developing `raw_image`, it's sensor data included.
+pub(super) fn develop_allocation(raw_image: &RawImage) -> u64 {
+    let sensor_data_bytes = match &raw_image.data {
+        RawImageData::Integer(data) => data.len() as u64 * 2,
+        RawImageData::Float(data) => data.len() as u64 * 4,
+    };
+
+    sensor_data_bytes + raw_image.width as u64 * raw_image.height as u64 * DEVELOP_BYTES_PER_PIXEL
+}
+
+/// Develops the raw sensor data into 16 bit RGB pixels.
+///
+/// This is deliberately a simple pipeline (black/white level scaling, white balance,
+/// bilinear demosaic, camera to sRGB colour matrix and then a base tone curve), it's meant
+/// for previewing raws, not competing with the output of a proper raw editor.
+pub(super) fn develop(raw_image: RawImage) -> Result<(Vec<u16>, ImageSize)> {
+    let [crop_top, crop_right, crop_bottom, crop_left] = raw_image.crops;
+
+    let cpp = raw_image.cpp;
+    let width = raw_image.width.saturating_sub(crop_left + crop_right);
+    let height = raw_image.height.saturating_sub(crop_top + crop_bottom);
+
+    if width == 0 || height == 0 || (cpp != 1 && cpp != 3) {
+        return Err(
+            Error::DecodingFailure {
+                error: format!(
+                    "Raw image has an unsupported layout ({}x{} with {} components per pixel)!",
+                    width,
+                    height,
+                    cpp
+                )
+            }
+        );
+    }
+
+    let white_balance = get_white_balance(&raw_image);
+    let camera_to_srgb = get_camera_to_srgb_matrix(&raw_image);
+
+    let black_levels = raw_image.blacklevels.map(|level| level as f32);
+    let white_levels = raw_image.whitelevels.map(|level| level as f32);
+
+    let cfa = raw_image.cropped_cfa();
+    let is_monochrome = raw_image.is_monochrome();
+
+    // normalize each photosite to 0.0 - 1.0 and white balance it.
+    let scale = |value: f32, colour: usize| {
+        let range = (white_levels[colour] - black_levels[colour]).max(1.0);
+
+        ((value - black_levels[colour]) / range).max(0.0) * white_balance[colour]
+    };
+
+    let source_width = raw_image.width * cpp;
+    let sample_at = |row: usize, column: usize| -> f32 {
+        let index = (row + crop_top) * source_width + (column + crop_left) * cpp;
+
+        match &raw_image.data {
+            RawImageData::Integer(data) => data[index] as f32,
+            RawImageData::Float(data) => data[index],
+        }
+    };
+
+    log::debug!("Developing {}x{} raw image ({})...", width, height, cfa.name);
+
+    let mut linear = vec![0.0f32; width * height * 3];
+
+    linear.par_chunks_mut(width * 3)
+        .enumerate()
+        .for_each(|(row, row_pixels)| {
+            for column in 0..width {
+                let pixel = &mut row_pixels[column * 3..column * 3 + 3];
+
+                if cpp == 3 {
+                    let index = (row + crop_top) * source_width + (column + crop_left) * 3;
+
+                    for (colour, value) in pixel.iter_mut().enumerate() {
+                        let sample = match &raw_image.data {
+                            RawImageData::Integer(data) => data[index + colour] as f32,
+                            RawImageData::Float(data) => data[index + colour],
+                        };
+
+                        *value = scale(sample, colour);
+                    }
+
+                    continue;
+                }
+
+                if is_monochrome {
+                    pixel.fill(scale(sample_at(row, column), 0));
+                    continue;
+                }
+
+                // bilinear demosaic, each colour the photosite doesn't have is
+                // the average of the neighbouring photosites that do have it.
+                let mut sums = [0.0f32; 3];
+                let mut counts = [0u32; 3];
+
+                for neighbour_row in row.saturating_sub(1)..(row + 2).min(height) {
+                    for neighbour_column in column.saturating_sub(1)..(column + 2).min(width) {
+                        let colour = cfa.color_at(neighbour_row, neighbour_column);
+                        let value = scale(sample_at(neighbour_row, neighbour_column), colour);
+
+                        // the fourth colour is almost always a second green.
+                        let channel = cfa_colour_to_channel(colour);
+
+                        sums[channel] += value;
+                        counts[channel] += 1;
+                    }
+                }
+
+                let own_channel = cfa_colour_to_channel(cfa.color_at(row, column));
+                let own_value = scale(sample_at(row, column), cfa.color_at(row, column));
+
+                for (channel, value) in pixel.iter_mut().enumerate() {
+                    *value = match channel == own_channel {
+                        true => own_value,
+                        false if counts[channel] > 0 => sums[channel] / counts[channel] as f32,
+                        false => 0.0,
+                    };
+                }
+            }
+        });
+
+    log::debug!("Applying colour matrix and base tone curve to developed raw image...");
+
+    let pixels = linear.par_chunks(3)
+        .flat_map_iter(|pixel| {
+            let srgb = match is_monochrome {
+                true => [pixel[0], pixel[1], pixel[2]],
+                false => multiply_matrix(&camera_to_srgb, pixel),
+            };
+
+            srgb.map(|value| (base_tone_curve(value) * u16::MAX as f32).round() as u16)
+        })
+        .collect();
+
+    Ok((pixels, (width as u32, height as u32)))
+}
+
+/// The camera's as shot white balance normalized to green,
+/// falling back to a neutral daylight one if it has none.
+fn get_white_balance(raw_image: &RawImage) -> [f32; 4] {
+    let mut white_balance = match raw_image.wb_coeffs[0..3].iter().all(|coefficient| coefficient.is_finite() && *coefficient > 0.0) {
+        true => raw_image.wb_coeffs,
+        false => raw_image.neutralwb(),
+    };
+
+    if !white_balance[3].is_finite() || white_balance[3] <= 0.0 {
+        white_balance[3] = white_balance[1];
+    }
+
+    match white_balance[1].is_finite() && white_balance[1] > 0.0 {
+        true => white_balance.map(|coefficient| coefficient / white_balance[1]),
+        false => [1.0; 4],
+    }
+}
+
+/// Builds the camera to linear sRGB matrix the same way dcraw does, the camera's
+/// xyz matrix is multiplied into sRGB, normalized so white stays white and then inverted.
+fn get_camera_to_srgb_matrix(raw_image: &RawImage) -> [[f32; 3]; 3] {
+    // cameras rawloader doesn't know the colour matrix of.
+    if raw_image.xyz_to_cam.iter().flatten().all(|value| *value == 0.0) {
+        return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    }
+
+    let mut srgb_to_camera = [[0.0f32; 3]; 4];
+
+    for (camera_row, xyz_row) in srgb_to_camera.iter_mut().zip(raw_image.xyz_to_cam.iter()) {
+        for (column, value) in camera_row.iter_mut().enumerate() {
+            *value = (0..3).map(|index| xyz_row[index] * SRGB_TO_XYZ[index][column]).sum();
+        }
+
+        let row_sum: f32 = camera_row.iter().sum();
+
+        if row_sum != 0.0 {
+            camera_row.iter_mut().for_each(|value| *value /= row_sum);
+        }
+    }
+
+    let camera_to_srgb = RawImage::pseudoinverse(srgb_to_camera);
+
+    camera_to_srgb.map(|row| [row[0], row[1], row[2]])
+}
+
+fn cfa_colour_to_channel(colour: usize) -> usize {
+    match colour {
+        3 => 1,
+        colour => colour.min(2),
+    }
+}
+
+fn multiply_matrix(matrix: &[[f32; 3]; 3], pixel: &[f32]) -> [f32; 3] {
+    matrix.map(|row| row[0] * pixel[0] + row[1] * pixel[1] + row[2] * pixel[2])
+}
+
+/// The sRGB transfer function with a touch of s-curve on top for some contrast.
+fn base_tone_curve(linear: f32) -> f32 {
+    let linear = linear.clamp(0.0, 1.0);
+
+    let encoded = match linear <= 0.0031308 {
+        true => linear * 12.92,
+        false => 1.055 * linear.powf(1.0 / 2.4) - 0.055,
+    };
+
+    let s_curve = encoded * encoded * (3.0 - 2.0 * encoded);
+
+    encoded + (s_curve - encoded) * BASE_CURVE_CONTRAST
+}
@@ -0,0 +1,323 @@
+use std::ops::Range;
+
+use crate::{decoded_image::ImageSize, format::ImageFormat};
+
+const TAG_IMAGE_WIDTH: u16 = 0x0100;
+const TAG_IMAGE_LENGTH: u16 = 0x0101;
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_MAKE: u16 = 0x010F;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_STRIP_BYTE_COUNTS: u16 = 0x0117;
+const TAG_SUB_IFDS: u16 = 0x014A;
+const TAG_JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+const TAG_JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+const TAG_DNG_VERSION: u16 = 0xC612;
+
+/// Camera raws can nest sub ifds quite a bit but never this deep, it
+/// stops us from looping forever on a corrupted (or malicious) file.
+const MAX_IFDS: usize = 32;
+
+/// A tiny tiff reader, just enough to identify camera raws
+/// and find the jpeg previews cameras embed into them.
+pub(crate) struct Tiff<'a> {
+    buffer: &'a [u8],
+    little_endian: bool,
+    ifds: Vec<Ifd>,
+}
+
+struct Ifd {
+    entries: Vec<IfdEntry>,
+}
+
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    /// Offset of the entry's value (or the value itself
+    /// if it fits in 4 bytes) from the start of the buffer.
+    value_offset: usize,
+}
+
+impl<'a> Tiff<'a> {
+    pub fn parse(buffer: &'a [u8]) -> Option<Self> {
+        let little_endian = match buffer.get(0..4)? {
+            [b'I', b'I', 42, 0] => true,
+            [b'M', b'M', 0, 42] => false,
+            _ => return None,
+        };
+
+        let mut tiff = Self {
+            buffer,
+            little_endian,
+            ifds: Vec::new(),
+        };
+
+        let mut ifd_offsets = vec![tiff.read_u32(4)? as usize];
+
+        while let Some(ifd_offset) = ifd_offsets.pop() {
+            if tiff.ifds.len() >= MAX_IFDS {
+                break;
+            }
+
+            let (ifd, next_ifd_offset) = match tiff.read_ifd(ifd_offset) {
+                Some(ifd) => ifd,
+                None => continue,
+            };
+
+            if let Some(sub_ifds) = ifd.entry(TAG_SUB_IFDS) {
+                for index in 0..sub_ifds.count as usize {
+                    if let Some(sub_ifd_offset) = tiff.entry_value(sub_ifds, index) {
+                        ifd_offsets.push(sub_ifd_offset as usize);
+                    }
+                }
+            }
+
+            if next_ifd_offset != 0 {
+                ifd_offsets.push(next_ifd_offset);
+            }
+
+            tiff.ifds.push(ifd);
+        }
+
+        Some(tiff)
+    }
+
+    /// Works out which camera raw format this tiff is, returns `None` if it's just a normal tiff.
+    pub fn raw_format(&self) -> Option<ImageFormat> {
+        // cr2s have "CR" right after the tiff header.
+        if self.buffer.get(8..10) == Some(b"CR") {
+            return Some(ImageFormat::Cr2);
+        }
+
+        let first_ifd = self.ifds.first()?;
+
+        if first_ifd.entry(TAG_DNG_VERSION).is_some() {
+            return Some(ImageFormat::Dng);
+        }
+
+        let make = self.entry_string(first_ifd.entry(TAG_MAKE)?)?.to_uppercase();
+
+        match make {
+            make if make.starts_with("NIKON") => Some(ImageFormat::Nef),
+            make if make.starts_with("SONY") => Some(ImageFormat::Arw),
+            make if make.starts_with("CANON") => Some(ImageFormat::Cr2),
+            _ => None,
+        }
+    }
+
+    /// The biggest image dimensions declared in any of the ifds, for
+    /// camera raws that's the sensor data or the full-size preview.
+    pub fn largest_size(&self) -> Option<ImageSize> {
+        self.ifds.iter()
+            .filter_map(|ifd| {
+                let width = self.entry_value(ifd.entry(TAG_IMAGE_WIDTH)?, 0)?;
+                let height = self.entry_value(ifd.entry(TAG_IMAGE_LENGTH)?, 0)?;
+
+                Some((width, height))
+            })
+            .max_by_key(|(width, height)| *width as u64 * *height as u64)
+    }
+
+    /// Returns the byte range and size of the biggest lossy jpeg preview embedded in this tiff.
+    pub fn largest_jpeg_preview(&self) -> Option<(Range<usize>, ImageSize)> {
+        self.jpeg_previews()
+            .max_by_key(|(_, (width, height))| *width as u64 * *height as u64)
+    }
+
+    /// Returns the byte range and size of the smallest lossy jpeg preview embedded in 
+    /// this tiff, for exif chunks that's the thumbnail in the second ifd.
+    pub fn smallest_jpeg_preview(&self) -> Option<(Range<usize>, ImageSize)> {
+        self.jpeg_previews()
+            .min_by_key(|(_, (width, height))| *width as u64 * *height as u64)
+    }
+
+    fn jpeg_previews(&self) -> impl Iterator<Item = (Range<usize>, ImageSize)> {
+        self.ifds.iter()
+            .flat_map(|ifd| [self.jpeg_interchange_range(ifd), self.jpeg_strip_range(ifd)])
+            .flatten()
+            .filter_map(|range| {
+                let jpeg_bytes = &self.buffer[range.clone()];
+
+                // camera raws also store their sensor data as lossless jpegs (e.g. cr2s), we don't want those.
+                if !is_lossy_jpeg(jpeg_bytes) {
+                    return None;
+                }
+
+                let size = imagesize::blob_size(jpeg_bytes).ok()?;
+
+                Some((range, (size.width as u32, size.height as u32)))
+            })
+    }
+
+    fn jpeg_interchange_range(&self, ifd: &Ifd) -> Option<Range<usize>> {
+        let offset = self.entry_value(ifd.entry(TAG_JPEG_INTERCHANGE_FORMAT)?, 0)? as usize;
+        let length = self.entry_value(ifd.entry(TAG_JPEG_INTERCHANGE_FORMAT_LENGTH)?, 0)? as usize;
+
+        self.checked_range(offset, length)
+    }
+
+    fn jpeg_strip_range(&self, ifd: &Ifd) -> Option<Range<usize>> {
+        let compression = self.entry_value(ifd.entry(TAG_COMPRESSION)?, 0)?;
+
+        // 6 is old-style jpeg and 7 is jpeg.
+        if compression != 6 && compression != 7 {
+            return None;
+        }
+
+        let strip_offsets = ifd.entry(TAG_STRIP_OFFSETS)?;
+        let strip_byte_counts = ifd.entry(TAG_STRIP_BYTE_COUNTS)?;
+
+        // a jpeg split across several strips is not something we can hand to a jpeg decoder.
+        if strip_offsets.count != 1 {
+            return None;
+        }
+
+        let offset = self.entry_value(strip_offsets, 0)? as usize;
+        let length = self.entry_value(strip_byte_counts, 0)? as usize;
+
+        self.checked_range(offset, length)
+    }
+
+    fn checked_range(&self, offset: usize, length: usize) -> Option<Range<usize>> {
+        let end = offset.checked_add(length)?;
+
+        match end <= self.buffer.len() && self.buffer.get(offset..offset + 2) == Some(&[0xFF, 0xD8]) {
+            true => Some(offset..end),
+            false => None,
+        }
+    }
+
+    fn read_ifd(&self, offset: usize) -> Option<(Ifd, usize)> {
+        let entry_count = self.read_u16(offset)? as usize;
+        let mut entries = Vec::with_capacity(entry_count);
+
+        for index in 0..entry_count {
+            let entry_offset = offset + 2 + index * 12;
+
+            let field_type = self.read_u16(entry_offset + 2)?;
+            let count = self.read_u32(entry_offset + 4)?;
+
+            let value_size = field_type_size(field_type).checked_mul(count as usize)?;
+
+            let value_offset = match value_size <= 4 {
+                true => entry_offset + 8,
+                false => self.read_u32(entry_offset + 8)? as usize,
+            };
+
+            entries.push(
+                IfdEntry {
+                    tag: self.read_u16(entry_offset)?,
+                    field_type,
+                    count,
+                    value_offset,
+                }
+            );
+        }
+
+        let next_ifd_offset = self.read_u32(offset + 2 + entry_count * 12)? as usize;
+
+        Some((Ifd { entries }, next_ifd_offset))
+    }
+
+    fn entry_value(&self, entry: &IfdEntry, index: usize) -> Option<u32> {
+        if index >= entry.count as usize {
+            return None;
+        }
+
+        match entry.field_type {
+            // byte
+            1 => self.buffer.get(entry.value_offset + index).map(|byte| *byte as u32),
+            // short
+            3 => self.read_u16(entry.value_offset + index * 2).map(|short| short as u32),
+            // long and ifd
+            4 | 13 => self.read_u32(entry.value_offset + index * 4),
+            _ => None,
+        }
+    }
+
+    fn entry_string(&self, entry: &IfdEntry) -> Option<String> {
+        let bytes = self.buffer.get(entry.value_offset..entry.value_offset + entry.count as usize)?;
+
+        Some(
+            String::from_utf8_lossy(bytes)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string()
+        )
+    }
+
+    fn read_u16(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.buffer.get(offset..offset + 2)?.try_into().ok()?;
+
+        Some(
+            match self.little_endian {
+                true => u16::from_le_bytes(bytes),
+                false => u16::from_be_bytes(bytes),
+            }
+        )
+    }
+
+    fn read_u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.buffer.get(offset..offset + 4)?.try_into().ok()?;
+
+        Some(
+            match self.little_endian {
+                true => u32::from_le_bytes(bytes),
+                false => u32::from_be_bytes(bytes),
+            }
+        )
+    }
+}
+
+impl Ifd {
+    fn entry(&self, tag: u16) -> Option<&IfdEntry> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+}
+
+fn field_type_size(field_type: u16) -> usize {
+    match field_type {
+        1 | 2 | 6 | 7 => 1,
+        3 | 8 => 2,
+        4 | 9 | 11 | 13 => 4,
+        5 | 10 | 12 => 8,
+        _ => 1,
+    }
+}
+
+/// Walks the jpeg's markers until it hits the start of frame,
+/// only baseline and progressive (huffman) jpegs count as lossy.
+fn is_lossy_jpeg(jpeg_bytes: &[u8]) -> bool {
+    let mut offset = 2;
+
+    while let Some([0xFF, marker]) = jpeg_bytes.get(offset..offset + 2) {
+        match marker {
+            0xC0..=0xC2 => return true,
+            // every other start of frame marker (0xC4 and 0xCC are huffman and arithmetic tables).
+            0xC3 | 0xC5..=0xCB | 0xCD..=0xCF => return false,
+            // padding
+            0xFF => {
+                offset += 1;
+                continue;
+            },
+            _ => {},
+        }
+
+        let segment_length = match jpeg_bytes.get(offset + 2..offset + 4) {
+            Some(length) => u16::from_be_bytes([length[0], length[1]]) as usize,
+            None => return false,
+        };
+
+        offset += 2 + segment_length;
+    }
+
+    false
+}
+
+/// Determines whether this tiff is actually a camera raw and if so,
+/// which one and it's size (which the tiff header alone can't tell us).
+pub(crate) fn determine_raw_format_and_size(buffer: &[u8]) -> Option<(ImageFormat, ImageSize)> {
+    let tiff = Tiff::parse(buffer)?;
+
+    Some((tiff.raw_format()?, tiff.largest_size()?))
+}
@@ -0,0 +1,144 @@
+use std::sync::{Arc, OnceLock};
+
+use resvg::{tiny_skia::{Pixmap, Transform}, usvg::{Options, Tree, fontdb::Database}};
+
+use crate::{
+    backends::backend::DecodeBackend, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent, ImageSize}, error::{Error, Result}, format::ImageFormat, image_info::metadata::ImageMetadata, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, pixels::Pixels, reader::{ImageReader, ImageReaderData}
+};
+
+/// Loading system fonts is slow and svgs get re-rasterized quite 
+/// often (e.g. when zooming) so we only ever want to do it once.
+static FONT_DATABASE: OnceLock<Arc<Database>> = OnceLock::new();
+
+/// A vector backend that rasterizes svgs with resvg.
+/// 
+/// Unlike the other backends this one cannot be initialized from an already decoded 
+/// image as rasterizing to a different size requires the original svg. `ImageModification::Resize` 
+/// is also not a resize of pixels but the size the svg will be rasterized at.
+pub struct ResvgBackend {
+    tree: Tree,
+    modifications: ModificationPipeline,
+    image_format: ImageFormat,
+    limits: DecodeLimits,
+}
+
+impl DecodeBackend for ResvgBackend {
+    const SUPPORTED_FORMATS: &[ImageFormat] = &[
+        ImageFormat::Svg,
+    ];
+
+    fn from_reader(image_reader: ImageReader) -> Result<Self> {
+        if !Self::SUPPORTED_FORMATS.contains(&image_reader.image_format) {
+            return Err(
+                Error::DecoderImageFormatNotSupported {
+                    image_format: image_reader.image_format.to_string(),
+                    backend: String::from("resvg"),
+                }
+            );
+        }
+
+        match image_reader.data {
+            ImageReaderData::EncodedImage(cursor) => {
+                log::debug!("Initializing resvg backend and parsing svg tree...");
+
+                let font_database = FONT_DATABASE.get_or_init(|| {
+                    log::debug!("Loading system fonts for svg text rendering...");
+
+                    let mut font_database = Database::new();
+                    font_database.load_system_fonts();
+
+                    Arc::new(font_database)
+                });
+
+                let options = Options {
+                    fontdb: font_database.clone(),
+                    ..Default::default()
+                };
+
+                let tree = Tree::from_data(cursor.get_ref(), &options)
+                    .map_err(|error| Error::DecoderInitFailure { error: error.to_string() })?;
+
+                Ok(
+                    Self {
+                        tree,
+                        modifications: ModificationPipeline::new(),
+                        image_format: image_reader.image_format,
+                        limits: image_reader.limits,
+                    }
+                )
+            },
+            ImageReaderData::DecodedImage(_) => Err(
+                Error::DecoderInitFailure {
+                    error: String::from(
+                        "The resvg backend can only rasterize from the encoded svg, not an already decoded image!"
+                    )
+                }
+            ),
+        }
+    }
+
+    fn modify<I>(&mut self, modifications: I)
+    where
+        I: IntoIterator<Item = ImageModification>,
+    {
+        self.modifications.extend(modifications);
+    }
+
+    fn decode(self) -> Result<DecodedImage> {
+        let svg_size = self.tree.size();
+
+        let flattened_modifications = self.modifications.flatten(
+            (svg_size.width().round().max(1.0) as u32, svg_size.height().round().max(1.0) as u32)
+        );
+
+        // the part of the svg we rasterize, all of it unless it's been cropped.
+        let (crop_x, crop_y, crop_width, crop_height) = match flattened_modifications.crop {
+            Some((x, y, width, height)) => (x as f32, y as f32, width as f32, height as f32),
+            None => (0.0, 0.0, svg_size.width(), svg_size.height()),
+        };
+
+        let raster_size: ImageSize = match flattened_modifications.resize {
+            Some((width, height)) => (width.max(1), height.max(1)),
+            None => (crop_width.round().max(1.0) as u32, crop_height.round().max(1.0) as u32),
+        };
+
+        // svgs can be rasterized at any size so zooming into one can ask for a huge pixmap.
+        self.limits.check(raster_size, ImageColourType::Rgba8)?;
+
+        log::debug!("Rasterizing svg at {}x{} with resvg...", raster_size.0, raster_size.1);
+
+        let mut pixmap = Pixmap::new(raster_size.0, raster_size.1).ok_or(
+            Error::DecodingFailure {
+                error: format!(
+                    "Resvg failed to allocate a {}x{} pixmap to rasterize the svg onto!",
+                    raster_size.0,
+                    raster_size.1
+                )
+            }
+        )?;
+
+        let scale_x = raster_size.0 as f32 / crop_width;
+        let scale_y = raster_size.1 as f32 / crop_height;
+
+        let transform = Transform::from_row(scale_x, 0.0, 0.0, scale_y, -crop_x * scale_x, -crop_y * scale_y);
+
+        resvg::render(&self.tree, transform, &mut pixmap.as_mut());
+
+        log::debug!("Resvg successfully rasterized svg to pixels...");
+
+        let mut decoded_image = DecodedImage::new(
+            raster_size,
+            self.image_format,
+            ImageColourType::Rgba8,
+            ImageMetadata::default(),
+            // tiny-skia's pixmaps are premultiplied, the rest of roseate isn't.
+            DecodedImageContent::Static(Pixels::U8(pixmap.take_demultiplied())),
+        );
+
+        // the svg is rasterized upright so it's flipped and rotated after.
+        decoded_image.orient(flattened_modifications.orientation);
+        decoded_image.adjust_colours(&flattened_modifications.adjustments);
+
+        Ok(decoded_image)
+    }
+}
@@ -0,0 +1,2 @@
+mod backend;
+pub use backend::*;
@@ -1 +0,0 @@
-struct ZuneImageBackend {}
\ No newline at end of file
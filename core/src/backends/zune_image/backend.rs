@@ -0,0 +1,848 @@
+use std::ops::Range;
+
+use zune_core::{bit_depth::BitDepth, bytestream::ZReaderTrait, colorspace::ColorSpace, options::DecoderOptions, result::DecodingResult};
+use zune_image::{
+    codecs::{
+        jpeg::JpegDecoder, jpeg_xl::jxl_oxide::{JxlImage, PixelFormat, RenderResult, color::{ColourEncoding, ColourSpace, Primaries, TransferFunction, WhitePoint}}, png::{DisposeOp, PngDecoder, post_process_image}
+    },
+    image::Image,
+};
+
+use crate::{
+    backends::backend::DecodeBackend, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent, ImageSize, Page}, error::{Error, Result}, format::ImageFormat, image_info::{colour_profile::ColourProfile, metadata::ImageMetadata}, limits::DecodeLimits, modifications::{ImageModification, ModificationPipeline}, orientation::{ImageOrientation, orientation_to_decode_with}, pixels::Pixels, reader::{EncodedImageBytes, EncodedImageReader, ImageReader, ImageReaderData}
+};
+
+enum Decoder {
+    // zune decoders are rather large (the jpeg one especially) so we keep them on the heap.
+    Png(Box<PngDecoder<EncodedImageBytes>>),
+    Jpeg(Box<JpegDecoder<EncodedImageBytes>>),
+    JpegXl(Box<JxlImage<EncodedImageReader>>),
+}
+
+enum Buffer {
+    Image(Image),
+    Animation((Vec<(Image, f32)>, ImageSize, ImageColourType)),
+    Pages(Vec<Image>),
+}
+
+enum Source {
+    Decoder(Decoder),
+    Buffer(Buffer),
+}
+
+pub struct ZuneImageBackend {
+    source: Source,
+    modifications: ModificationPipeline,
+    image_exif_chunk: Option<Vec<u8>>,
+    image_icc_profile: Option<Vec<u8>>,
+    image_format: ImageFormat,
+    limits: DecodeLimits,
+    orientation: ImageOrientation,
+}
+
+impl DecodeBackend for ZuneImageBackend {
+    const SUPPORTED_FORMATS: &[ImageFormat] = &[
+        ImageFormat::Png,
+        ImageFormat::Jpeg,
+        ImageFormat::JpegXl,
+    ];
+
+    fn from_reader(image_reader: ImageReader) -> Result<Self> {
+        match image_reader.data {
+            ImageReaderData::EncodedImage(cursor) => {
+                log::debug!("Initializing zune-image backend decoders with buf reader...");
+
+                // zune decoders read straight from a byte buffer so we take ownership of the cursor's.
+                // 
+                // Zune refuses images over 16384x16384 by default, we leave that up to our decode limits instead.
+                let decoder_options = DecoderOptions::default()
+                    .set_max_width(usize::MAX)
+                    .set_max_height(usize::MAX);
+
+                // jxl-oxide doesn't read the exif box of the jxl container so we have to find it ourselves.
+                let jxl_exif_chunk = match image_reader.image_format {
+                    ImageFormat::JpegXl => jxl_exif_box(cursor.get_ref().as_slice()),
+                    _ => None,
+                };
+
+                let mut image_decoder = match image_reader.image_format {
+                    ImageFormat::Png => Decoder::Png(
+                        Box::new(PngDecoder::new_with_options(cursor.into_inner(), decoder_options))
+                    ),
+                    ImageFormat::Jpeg => Decoder::Jpeg(
+                        Box::new(JpegDecoder::new_with_options(cursor.into_inner(), decoder_options))
+                    ),
+                    ImageFormat::JpegXl => Decoder::JpegXl(
+                        Box::new(
+                            JxlImage::from_reader(cursor).map_err(
+                                |error| Error::DecoderInitFailure { error: error.to_string() }
+                            )?
+                        )
+                    ),
+                    unsupported_format => {
+                        return Err(
+                            Error::DecoderImageFormatNotSupported {
+                                image_format: unsupported_format.to_string(),
+                                backend: String::from("zune-image"),
+                            }
+                        );
+                    }
+                };
+
+                // zune decoders are lazy, so we must decode the headers first 
+                // before we are able to get hold of the exif chunk and icc profile.
+                let (exif_chunk, icc_profile) = match &mut image_decoder {
+                    Decoder::Png(png_decoder) => {
+                        png_decoder.decode_headers().map_err(
+                            |error| Error::DecoderInitFailure { error: error.to_string() }
+                        )?;
+
+                        match png_decoder.get_info() {
+                            Some(info) => (info.exif.clone(), info.icc_profile.clone()),
+                            None => (None, None),
+                        }
+                    },
+                    Decoder::Jpeg(jpeg_decoder) => {
+                        jpeg_decoder.decode_headers().map_err(
+                            |error| Error::DecoderInitFailure { error: error.to_string() }
+                        )?;
+
+                        (jpeg_decoder.exif().cloned(), jpeg_decoder.icc_profile())
+                    },
+                    Decoder::JpegXl(jxl_image) => {
+                        // jxl-oxide renders the image in it's own colour space (or linear sRGB if it's xyb 
+                        // encoded with an icc profile), there's no need to convert images that are sRGB already.
+                        let icc_profile = match jxl_renders_srgb(&jxl_image.image_header().metadata.colour_encoding) {
+                            true => None,
+                            false => Some(jxl_image.rendered_icc()),
+                        };
+
+                        (jxl_exif_chunk, icc_profile)
+                    },
+                };
+
+                // jxl-oxide orients jxls from their codestream which jxl says to go by over the exif.
+                let orientation = match image_decoder {
+                    Decoder::JpegXl(_) => ImageOrientation::Normal,
+                    _ => orientation_to_decode_with(exif_chunk.as_deref(), image_reader.auto_orientation),
+                };
+
+                Ok(
+                    Self {
+                        source: Source::Decoder(image_decoder),
+                        modifications: ModificationPipeline::new(),
+                        orientation,
+                        image_exif_chunk: exif_chunk,
+                        image_icc_profile: icc_profile,
+                        image_format: image_reader.image_format,
+                        limits: image_reader.limits,
+                    }
+                )
+            },
+            ImageReaderData::DecodedImage(decoded_image) => {
+                log::debug!("Initializing zune-image backend from already decoded image...");
+
+                match decoded_image.content {
+                    DecodedImageContent::Static(pixels) => {
+                        let image = Self::image_from_pixels(
+                            pixels,
+                            decoded_image.size,
+                            decoded_image.colour_type,
+                        );
+
+                        Ok(
+                            Self {
+                                source: Source::Buffer(Buffer::Image(image)),
+                                modifications: ModificationPipeline::new(),
+                                image_exif_chunk: None, // decoded image should
+                                // contain it so we don't need the chunk no more
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                            }
+                        )
+                    },
+                    DecodedImageContent::Animated(frames) => {
+                        let mut animated_images = Vec::new();
+
+                        for (pixels, delay) in frames {
+                            let image = Self::image_from_pixels(
+                                pixels,
+                                decoded_image.size,
+                                decoded_image.colour_type,
+                            );
+
+                            animated_images.push((image, delay));
+                        }
+
+                        Ok(
+                            Self {
+                                source: Source::Buffer(
+                                    Buffer::Animation((
+                                        animated_images,
+                                        decoded_image.size,
+                                        decoded_image.colour_type
+                                    ))
+                                ),
+                                modifications: ModificationPipeline::new(),
+                                image_exif_chunk: None,
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                            }
+                        )
+                    },
+                    DecodedImageContent::Pages(pages) => {
+                        let images = pages.into_iter()
+                            .map(|page| Self::image_from_pixels(page.pixels, page.size, page.colour_type))
+                            .collect();
+
+                        Ok(
+                            Self {
+                                source: Source::Buffer(Buffer::Pages(images)),
+                                modifications: ModificationPipeline::new(),
+                                image_exif_chunk: None,
+                                image_icc_profile: None,
+                                image_format: image_reader.image_format,
+                                limits: image_reader.limits,
+                                // already decoded images were oriented when they were first decoded.
+                                orientation: ImageOrientation::Normal,
+                            }
+                        )
+                    },
+                    // we can't decode a streamed animation from memory, there's only ever a few of it's frames in memory.
+                    DecodedImageContent::Streamed(_) => Err(Error::StreamedAnimationNotInMemory),
+                }
+            }
+        }
+    }
+
+    fn modify<I>(&mut self, modifications: I)
+    where
+        I: IntoIterator<Item = ImageModification>,
+    {
+        self.modifications.extend(modifications);
+    }
+
+    fn decode(mut self) -> Result<DecodedImage> {
+        // the image is oriented before any of the modifications asked of us are made, zune-image can't
+        // flip or rotate though so that's all done at the end (crops and resizes are adjusted to suit).
+        self.modifications = self.modifications.oriented_first(self.orientation);
+        let orientation = self.modifications.orientation();
+        let adjustments = self.modifications.colour_adjustments();
+        let colour_profile = self.image_icc_profile.take().and_then(ColourProfile::new);
+
+        let mut decoded_image = self.decode_source()?;
+        decoded_image.orient(orientation);
+
+        // the colours are adjusted once they're sRGB like they would be for any other image.
+        if let Some(colour_profile) = colour_profile {
+            decoded_image.convert_to_srgb(colour_profile);
+        }
+
+        decoded_image.adjust_colours(&adjustments);
+
+        Ok(decoded_image)
+    }
+}
+
+impl ZuneImageBackend {
+    /// Decodes the image the way it's stored, orientation is left to `decode`.
+    fn decode_source(self) -> Result<DecodedImage> {
+        match self.source {
+            Source::Decoder(decoder) => match decoder {
+                Decoder::Png(png_decoder) => {
+                    match png_decoder.is_animated() {
+                        true => Self::decode_animated_png(
+                            png_decoder,
+                            self.modifications,
+                            self.image_format,
+                            self.image_exif_chunk,
+                            self.limits
+                        ),
+                        false => Self::decode_png(
+                            png_decoder,
+                            self.modifications,
+                            self.image_format,
+                            self.image_exif_chunk,
+                            self.limits
+                        ),
+                    }
+                },
+                Decoder::Jpeg(jpeg_decoder) => Self::decode_jpeg(
+                    jpeg_decoder,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+                Decoder::JpegXl(jxl_image) => Self::decode_jpeg_xl(
+                    jxl_image,
+                    self.modifications,
+                    self.image_format,
+                    self.image_exif_chunk,
+                    self.limits
+                ),
+            },
+            Source::Buffer(buffer) => {
+                log::debug!(
+                    "Image already decoded and constructed as zune image, applying modifications..."
+                );
+
+                match buffer {
+                    Buffer::Image(mut image) => {
+                        Self::apply_modifications_to_image(self.modifications, &mut image)?;
+
+                        let (pixels, size, colour_type) = Self::image_to_pixels(&image)?;
+
+                        Ok(
+                            DecodedImage::new(
+                                size,
+                                self.image_format,
+                                colour_type,
+                                Self::get_decoded_image_metadata(self.image_exif_chunk),
+                                DecodedImageContent::Static(pixels),
+                            )
+                        )
+                    },
+                    Buffer::Animation((images, mut size, colour_type)) => {
+                        let mut animated_pixels = Vec::new();
+
+                        for (index, (mut image, delay)) in images.into_iter().enumerate() {
+                            log::debug!("Applying modifications to frame {}...", index);
+
+                            Self::apply_modifications_to_image(
+                                self.modifications.clone(),
+                                &mut image,
+                            )?;
+
+                            let (pixels, frame_size, _) = Self::image_to_pixels(&image)?;
+                            size = frame_size;
+
+                            animated_pixels.push((pixels, delay));
+                        }
+
+                        Ok(
+                            DecodedImage::new(
+                                size,
+                                self.image_format,
+                                colour_type,
+                                Self::get_decoded_image_metadata(self.image_exif_chunk),
+                                DecodedImageContent::Animated(animated_pixels),
+                            )
+                        )
+                    },
+                    Buffer::Pages(images) => {
+                        let (first_page_width, first_page_height) = images[0].dimensions();
+                        let first_page_size = (first_page_width as u32, first_page_height as u32);
+
+                        let mut pages = Vec::new();
+
+                        for (index, mut image) in images.into_iter().enumerate() {
+                            log::debug!("Applying modifications to page {}...", index + 1);
+
+                            let (width, height) = image.dimensions();
+
+                            Self::apply_modifications_to_image(
+                                self.modifications.scaled_to_page(
+                                    first_page_size,
+                                    (width as u32, height as u32)
+                                ),
+                                &mut image,
+                            )?;
+
+                            let (pixels, size, colour_type) = Self::image_to_pixels(&image)?;
+
+                            pages.push(Page { size, colour_type, pixels });
+                        }
+
+                        Ok(
+                            DecodedImage::new(
+                                pages[0].size,
+                                self.image_format,
+                                pages[0].colour_type,
+                                Self::get_decoded_image_metadata(self.image_exif_chunk),
+                                DecodedImageContent::Pages(pages),
+                            )
+                        )
+                    },
+                }
+            }
+        }
+    }
+
+    fn decode_png(
+        mut png_decoder: Box<PngDecoder<EncodedImageBytes>>,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        log::debug!("Decoding image with zune-png decoder...");
+
+        let (image_size, image_colour_type) = Self::get_png_size_and_colour_type(&png_decoder)?;
+
+        limits.check(image_size, image_colour_type)?;
+
+        let image_pixels = match png_decoder.decode().map_err(
+            |error| Error::DecodingFailure {
+                error: format!("Zune-png decoder failed to decode image to pixels: {}", error)
+            }
+        )? {
+            DecodingResult::U8(pixels) => Pixels::U8(pixels),
+            DecodingResult::U16(pixels) => Pixels::U16(pixels),
+            DecodingResult::F32(pixels) => Pixels::F32(pixels),
+            _ => return Err(Error::UnsupportedColourType),
+        };
+
+        log::debug!("Zune-png decoder successfully decoded to pixels...");
+
+        Self::decode_image(
+            image_pixels,
+            image_size,
+            image_colour_type,
+            modifications,
+            image_format,
+            image_exif_chunk
+        )
+    }
+
+    fn decode_animated_png(
+        mut png_decoder: Box<PngDecoder<EncodedImageBytes>>,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        log::debug!("Decoding animated image with zune-png decoder...");
+
+        let (image_size, image_colour_type) = Self::get_png_size_and_colour_type(&png_decoder)?;
+
+        let frame_bytes = image_size.0 as u64 * image_size.1 as u64 * image_colour_type.bytes_per_pixel() as u64;
+
+        limits.check_dimensions(image_size)?;
+        limits.check_allocation(frame_bytes)?;
+        let colour_space = ColorSpace::from(image_colour_type);
+
+        let png_info = png_decoder.get_info().cloned().ok_or(
+            Error::DecodingFailure {
+                error: String::from("Zune-png decoder has no image info, were the headers decoded?")
+            }
+        )?;
+
+        let error_func = |error: zune_image::codecs::png::error::PngDecodeErrors| Error::DecodingFailure {
+            error: format!("Zune-png decoder failed to decode animated frame: {}", error),
+        };
+
+        // APNG frames are drawn on top of each other so we keep one canvas
+        // around that every frame gets composited onto before we copy it out.
+        let mut canvas = vec![0; png_info.width * png_info.height * colour_space.num_components()];
+
+        let mut animated_images = Vec::new();
+
+        while png_decoder.more_frames() {
+            png_decoder.decode_headers().map_err(error_func)?;
+
+            let mut frame_info = png_decoder.frame_info().ok_or(
+                Error::DecodingFailure {
+                    error: String::from("Zune-png decoder returned an animated frame with no frame info!")
+                }
+            )?;
+
+            // we never clear the canvas so it always holds the previous frame already.
+            if frame_info.dispose_op == DisposeOp::Previous {
+                frame_info.dispose_op = DisposeOp::None;
+            }
+
+            let frame_pixels = match png_decoder.decode().map_err(error_func)? {
+                DecodingResult::U8(pixels) => pixels,
+                _ => return Err(
+                    Error::DecodingFailure {
+                        error: String::from("Zune-png decoder does not support animated PNGs above 8 bit depth!")
+                    }
+                ),
+            };
+
+            post_process_image(
+                &png_info,
+                colour_space,
+                &frame_info,
+                &frame_pixels,
+                None,
+                &mut canvas,
+                None
+            ).map_err(error_func)?;
+
+            // the APNG spec says a zero denominator should be treated as 100.
+            let delay_denominator = match frame_info.delay_denom {
+                0 => 100,
+                denominator => denominator,
+            };
+
+            let delay_seconds = frame_info.delay_num as f32 / delay_denominator as f32;
+
+            // the canvas counts as a frame too.
+            limits.check_allocation(frame_bytes * (animated_images.len() as u64 + 2))?;
+
+            animated_images.push(
+                (Self::image_from_pixels(Pixels::U8(canvas.clone()), image_size, image_colour_type), delay_seconds)
+            );
+        }
+
+        if animated_images.is_empty() {
+            return Err(Error::AnimatedImageHasNoFrames);
+        }
+
+        Self {
+            source: Source::Buffer(
+                Buffer::Animation((animated_images, image_size, image_colour_type))
+            ),
+            modifications,
+            image_exif_chunk,
+            // the outer decode orients the frames and converts them to sRGB once they're done.
+            image_icc_profile: None,
+            image_format,
+            limits,
+            orientation: ImageOrientation::Normal,
+        }.decode_source()
+    }
+
+    fn decode_jpeg(
+        mut jpeg_decoder: Box<JpegDecoder<EncodedImageBytes>>,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        log::debug!("Decoding image with zune-jpeg decoder...");
+
+        let (width, height) = jpeg_decoder.dimensions().ok_or(
+            Error::DecodingFailure {
+                error: String::from("Zune-jpeg decoder has no image dimensions, were the headers decoded?")
+            }
+        )?;
+
+        let colour_space = jpeg_decoder.get_output_colorspace()
+            .ok_or(Error::UnsupportedColourType)?;
+
+        let image_colour_type = ImageColourType::try_from((colour_space, BitDepth::Eight))?;
+
+        limits.check((width as u32, height as u32), image_colour_type)?;
+
+        let image_pixels = jpeg_decoder.decode().map_err(
+            |error| Error::DecodingFailure {
+                error: format!("Zune-jpeg decoder failed to decode image to pixels: {}", error)
+            }
+        )?;
+
+        log::debug!("Zune-jpeg decoder successfully decoded to pixels...");
+
+        Self::decode_image(
+            Pixels::U8(image_pixels),
+            (width as u32, height as u32),
+            image_colour_type,
+            modifications,
+            image_format,
+            image_exif_chunk
+        )
+    }
+
+    fn decode_jpeg_xl(
+        mut jxl_image: Box<JxlImage<EncodedImageReader>>,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+        limits: DecodeLimits,
+    ) -> Result<DecodedImage> {
+        log::debug!("Decoding image with jxl-oxide decoder...");
+
+        let image_metadata = &jxl_image.image_header().metadata;
+
+        // jxl-oxide renders everything to f32 so we squish 8 bit 
+        // images back down to u8 to not hold 4x the memory we need.
+        let bit_depth = match image_metadata.bit_depth.bits_per_sample() <= 8 {
+            true => BitDepth::Eight,
+            false => BitDepth::Float32,
+        };
+
+        let seconds_per_tick = image_metadata.animation.as_ref().map(
+            |animation| animation.tps_denominator as f32 / animation.tps_numerator as f32
+        );
+
+        let colour_space = match jxl_image.pixel_format() {
+            PixelFormat::Gray => ColorSpace::Luma,
+            PixelFormat::Graya => ColorSpace::LumaA,
+            PixelFormat::Rgb => ColorSpace::RGB,
+            PixelFormat::Rgba => ColorSpace::RGBA,
+            PixelFormat::Cmyk | PixelFormat::Cmyka => return Err(Error::UnsupportedColourType),
+        };
+
+        let image_colour_type = ImageColourType::try_from((colour_space, bit_depth))?;
+
+        // jxl-oxide always renders to f32, so that's what a frame costs us while it's being rendered.
+        let render_size = (jxl_image.width(), jxl_image.height());
+        let render_colour_type = ImageColourType::try_from((colour_space, BitDepth::Float32))?;
+
+        limits.check(render_size, render_colour_type)?;
+
+        let mut frames = Vec::new();
+        let mut allocated_bytes = 0u64;
+
+        // we hand jxl-oxide the whole image so it needing more data means we're done too.
+        while let RenderResult::Done(render) = jxl_image.render_next_frame().map_err(
+            |error| Error::DecodingFailure {
+                error: format!("Jxl-oxide decoder failed to render frame: {}", error)
+            }
+        )? {
+            let frame_buffer = render.image();
+            let frame_size = (frame_buffer.width() as u32, frame_buffer.height() as u32);
+
+            allocated_bytes += frame_size.0 as u64 * frame_size.1 as u64 * image_colour_type.bytes_per_pixel() as u64;
+            limits.check_allocation(allocated_bytes)?;
+
+            let frame_pixels = match bit_depth {
+                BitDepth::Eight => Pixels::U8(
+                    frame_buffer.buf()
+                        .iter()
+                        .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+                        .collect()
+                ),
+                _ => Pixels::F32(frame_buffer.buf().to_vec()),
+            };
+
+            match seconds_per_tick {
+                Some(seconds_per_tick) => {
+                    log::debug!("Rendered animated frame {}...", frames.len());
+
+                    frames.push((frame_pixels, frame_size, render.duration() as f32 * seconds_per_tick));
+                },
+                None => {
+                    log::debug!("Jxl-oxide decoder successfully decoded to pixels...");
+
+                    return Self::decode_image(
+                        frame_pixels,
+                        frame_size,
+                        image_colour_type,
+                        modifications,
+                        image_format,
+                        image_exif_chunk
+                    );
+                }
+            }
+        }
+
+        let image_size = match frames.first() {
+            Some((_, size, _)) => *size,
+            None => return Err(Error::AnimatedImageHasNoFrames),
+        };
+
+        let animated_images = frames.into_iter()
+            .map(|(pixels, size, delay)| (Self::image_from_pixels(pixels, size, image_colour_type), delay))
+            .collect();
+
+        Self {
+            source: Source::Buffer(
+                Buffer::Animation((animated_images, image_size, image_colour_type))
+            ),
+            modifications,
+            image_exif_chunk,
+            // the outer decode orients the frames and converts them to sRGB once they're done.
+            image_icc_profile: None,
+            image_format,
+            limits,
+            orientation: ImageOrientation::Normal,
+        }.decode_source()
+    }
+
+    fn decode_image(
+        image_pixels: Pixels,
+        image_size: ImageSize,
+        image_colour_type: ImageColourType,
+        modifications: ModificationPipeline,
+        image_format: ImageFormat,
+        image_exif_chunk: Option<Vec<u8>>,
+    ) -> Result<DecodedImage> {
+        let metadata = Self::get_decoded_image_metadata(image_exif_chunk);
+
+        if modifications.is_empty() {
+            log::debug!(
+                "No image modifications so we're constructing decoded image directly from image pixels..."
+            );
+
+            return Ok(
+                DecodedImage::new(
+                    image_size,
+                    image_format,
+                    image_colour_type,
+                    metadata,
+                    DecodedImageContent::Static(image_pixels),
+                )
+            );
+        }
+
+        log::debug!(
+            "We have image modifications. Constructing zune image to apply modifications..."
+        );
+
+        let mut image = Self::image_from_pixels(image_pixels, image_size, image_colour_type);
+
+        Self::apply_modifications_to_image(modifications, &mut image)?;
+
+        log::debug!("Converting zune image back to pixels to construct into decoded image...");
+
+        let (image_pixels, image_size, image_colour_type) = Self::image_to_pixels(&image)?;
+
+        Ok(
+            DecodedImage::new(
+                image_size,
+                image_format,
+                image_colour_type,
+                metadata,
+                DecodedImageContent::Static(image_pixels),
+            )
+        )
+    }
+
+    fn get_png_size_and_colour_type(png_decoder: &PngDecoder<EncodedImageBytes>) -> Result<(ImageSize, ImageColourType)> {
+        let headers_not_decoded_error = || Error::DecodingFailure {
+            error: String::from("Zune-png decoder has no image dimensions, were the headers decoded?")
+        };
+
+        let (width, height) = png_decoder.get_dimensions().ok_or_else(headers_not_decoded_error)?;
+        let colour_space = png_decoder.get_colorspace().ok_or_else(headers_not_decoded_error)?;
+        let bit_depth = png_decoder.get_depth().ok_or_else(headers_not_decoded_error)?;
+
+        Ok(
+            (
+                (width as u32, height as u32),
+                ImageColourType::try_from((colour_space, bit_depth))?
+            )
+        )
+    }
+
+    fn image_from_pixels(pixels: Pixels, size: ImageSize, colour_type: ImageColourType) -> Image {
+        let (width, height) = (size.0 as usize, size.1 as usize);
+        let colour_space = ColorSpace::from(colour_type);
+
+        match pixels {
+            Pixels::U8(pixels) => Image::from_u8(&pixels, width, height, colour_space),
+            Pixels::U16(pixels) => Image::from_u16(&pixels, width, height, colour_space),
+            Pixels::F32(pixels) => Image::from_f32(&pixels, width, height, colour_space),
+        }
+    }
+
+    fn image_to_pixels(image: &Image) -> Result<(Pixels, ImageSize, ImageColourType)> {
+        let (width, height) = image.dimensions();
+        let colour_type = ImageColourType::try_from((image.colorspace(), image.depth()))?;
+
+        let no_frames_error = || Error::DecodingFailure {
+            error: String::from("Zune image unexpectedly contained no frames!")
+        };
+
+        let pixels = match image.depth() {
+            BitDepth::Eight => Pixels::U8(
+                image.flatten_frames::<u8>().into_iter().next().ok_or_else(no_frames_error)?
+            ),
+            BitDepth::Sixteen => Pixels::U16(
+                image.flatten_frames::<u16>().into_iter().next().ok_or_else(no_frames_error)?
+            ),
+            BitDepth::Float32 => Pixels::F32(
+                image.flatten_frames::<f32>().into_iter().next().ok_or_else(no_frames_error)?
+            ),
+            _ => return Err(Error::UnsupportedColourType),
+        };
+
+        Ok((pixels, (width as u32, height as u32), colour_type))
+    }
+
+    fn get_decoded_image_metadata(image_exif_chunk: Option<Vec<u8>>) -> ImageMetadata {
+        match image_exif_chunk {
+            Some(exif_chunk) => match ImageMetadata::new(exif_chunk) {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    log::warn!("{}", error);
+
+                    ImageMetadata::default()
+                },
+            },
+            None => ImageMetadata::default(),
+        }
+    }
+}
+
+/// Lets the zune decoders read straight from the (possibly memory-mapped) image bytes.
+impl ZReaderTrait for EncodedImageBytes {
+    #[inline(always)]
+    fn get_byte(&self, index: usize) -> Option<&u8> {
+        self.as_slice().get(index)
+    }
+
+    #[inline(always)]
+    fn get_slice(&self, index: Range<usize>) -> Option<&[u8]> {
+        self.as_slice().get(index)
+    }
+
+    #[inline(always)]
+    fn get_len(&self) -> usize {
+        self.as_slice().len()
+    }
+}
+
+/// Whether jxl-oxide renders the image as sRGB (or greyscale with the sRGB transfer function) already.
+fn jxl_renders_srgb(colour_encoding: &ColourEncoding) -> bool {
+    let is_srgb_gamut = match colour_encoding.colour_space {
+        ColourSpace::Grey => true,
+        ColourSpace::Rgb => colour_encoding.primaries == Primaries::Srgb,
+        _ => false,
+    };
+
+    !colour_encoding.want_icc
+        && is_srgb_gamut
+        && colour_encoding.white_point == WhitePoint::D65
+        && colour_encoding.tf == TransferFunction::Srgb
+}
+
+/// The exif (tiff header onwards) in the "Exif" box of a jxl container, bare jxl codestreams have no exif.
+/// Brotli compressed ("brob") exif boxes are skipped as we have nothing to decompress them with.
+fn jxl_exif_box(buffer: &[u8]) -> Option<Vec<u8>> {
+    const JXL_CONTAINER_SIGNATURE: &[u8] = &[0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', b' ', 0x0D, 0x0A, 0x87, 0x0A];
+
+    if !buffer.starts_with(JXL_CONTAINER_SIGNATURE) {
+        return None;
+    }
+
+    let mut offset = 0;
+
+    while let Some(box_header) = buffer.get(offset..offset + 8) {
+        let box_type = &box_header[4..8];
+
+        let (header_length, box_length) = match u32::from_be_bytes(box_header[0..4].try_into().ok()?) {
+            // the box runs to the end of the file.
+            0 => (8, buffer.len() - offset),
+            // the box's length is in the 8 bytes after it's type.
+            1 => (16, usize::try_from(u64::from_be_bytes(buffer.get(offset + 8..offset + 16)?.try_into().ok()?)).ok()?),
+            length => (8, length as usize),
+        };
+
+        if box_length < header_length {
+            return None;
+        }
+
+        if box_type == b"Exif" {
+            let box_content = buffer.get(offset + header_length..offset.checked_add(box_length)?)?;
+
+            // the exif box starts with the offset to the tiff header.
+            let tiff_header_offset = u32::from_be_bytes(box_content.get(0..4)?.try_into().ok()?) as usize;
+
+            return box_content.get(4usize.checked_add(tiff_header_offset)?..).map(<[u8]>::to_vec);
+        }
+
+        offset = offset.checked_add(box_length)?;
+    }
+
+    None
+}
@@ -0,0 +1,38 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+
+use crate::{error::Error, colour_type::ImageColourType};
+
+impl TryFrom<(ColorSpace, BitDepth)> for ImageColourType {
+    type Error = Error;
+
+    fn try_from(value: (ColorSpace, BitDepth)) -> Result<Self, Self::Error> {
+        let image_colour_type = match value {
+            (ColorSpace::Luma, BitDepth::Eight) => Self::Grey8,
+            (ColorSpace::Luma, BitDepth::Sixteen) => Self::Grey16,
+            (ColorSpace::Luma, BitDepth::Float32) => Self::Grey32F,
+            (ColorSpace::LumaA, BitDepth::Eight) => Self::GreyA8,
+            (ColorSpace::LumaA, BitDepth::Sixteen) => Self::GreyA16,
+            (ColorSpace::LumaA, BitDepth::Float32) => Self::GreyA32F,
+            (ColorSpace::RGB, BitDepth::Eight) => Self::Rgb8,
+            (ColorSpace::RGB, BitDepth::Sixteen) => Self::Rgb16,
+            (ColorSpace::RGB, BitDepth::Float32) => Self::Rgb32F,
+            (ColorSpace::RGBA, BitDepth::Eight) => Self::Rgba8,
+            (ColorSpace::RGBA, BitDepth::Sixteen) => Self::Rgba16,
+            (ColorSpace::RGBA, BitDepth::Float32) => Self::Rgba32F,
+            _ => return Err(Error::UnsupportedColourType)
+        };
+
+        Ok(image_colour_type)
+    }
+}
+
+impl From<ImageColourType> for ColorSpace {
+    fn from(value: ImageColourType) -> Self {
+        match value {
+            ImageColourType::Grey8 | ImageColourType::Grey16 | ImageColourType::Grey32F => Self::Luma,
+            ImageColourType::GreyA8 | ImageColourType::GreyA16 | ImageColourType::GreyA32F => Self::LumaA,
+            ImageColourType::Rgb8 | ImageColourType::Rgb16 | ImageColourType::Rgb32F => Self::RGB,
+            ImageColourType::Rgba8 | ImageColourType::Rgba16 | ImageColourType::Rgba32F => Self::RGBA,
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod backend;
+pub use backend::*;
+
+mod colour;
+mod modifications;
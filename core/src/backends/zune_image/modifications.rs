@@ -0,0 +1,107 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image, traits::OperationsTrait};
+use zune_imageprocs::{crop::Crop, resize::{Resize, ResizeMethod}};
+
+use crate::{backends::zune_image::ZuneImageBackend, error::{Error, Result}, modifications::{ImageModification, ModificationPipeline}, processing::alpha::{premultiply_channel, unpremultiply_channel}};
+
+impl ZuneImageBackend {
+
+    /// Only makes the crop and resize, the flip and rotation are left to the image's orientation
+    /// and the colour adjustments are made to the decoded image.
+    pub(super) fn apply_modifications_to_image(modifications: ModificationPipeline, image: &mut Image) -> Result<()> {
+        let (image_width, image_height) = image.dimensions();
+        let flattened_modifications = modifications.flatten((image_width as u32, image_height as u32));
+
+        for modification in flattened_modifications.to_modifications() {
+
+            match modification {
+                ImageModification::Crop { x, y, width, height } => {
+                    log::debug!("Applying crop modification ({}x{} at {}, {})...", width, height, x, y);
+
+                    Crop::new(width as usize, height as usize, x as usize, y as usize)
+                        .execute(image)
+                        .map_err(|error| Error::DecodingFailure {
+                            error: format!(
+                                "Zune-image failed to apply crop modification: {:?}",
+                                error
+                            ),
+                        })?;
+                },
+                ImageModification::Resize(width, height, _) => {
+                    log::debug!("Applying resize modification ({}x{})...", width, height);
+
+                    let has_alpha = image.colorspace().has_alpha();
+
+                    // resampling averages the colour of transparent pixels into the edges 
+                    // around them (dark fringes) unless the alpha is premultiplied first.
+                    if has_alpha {
+                        premultiply_image_alpha(image, true).map_err(resize_error)?;
+                    }
+
+                    // NOTE: bilinear is the only resize method zune-imageprocs offers right 
+                    // now so whatever resampling filter was asked for, bilinear is what you get.
+                    Resize::new(width as usize, height as usize, ResizeMethod::Bilinear)
+                        .execute(image)
+                        .map_err(resize_error)?;
+
+                    if has_alpha {
+                        premultiply_image_alpha(image, false).map_err(resize_error)?;
+                    }
+                },
+                // zune-image can't flip or rotate so that's done when the decoded image is oriented.
+                _ => {},
+            }
+
+        }
+
+        Ok(())
+    }
+}
+
+/// zune-imageprocs has it's own alpha premultiplication but it overflows on fully opaque 8 bit 
+/// pixels (255 comes out as 0), so we premultiply (or unpremultiply) each colour channel ourselves.
+fn premultiply_image_alpha(image: &mut Image, premultiply: bool) -> std::result::Result<(), ImageErrors> {
+    let colour_space = image.colorspace();
+    let bit_depth = image.depth();
+
+    for frame in image.frames_mut() {
+        let channels = frame.channels_mut(colour_space, false);
+
+        // the alpha is the last channel, apart from in ARGB where it's the first.
+        let (colour_channels, alpha_channel) = match colour_space == ColorSpace::ARGB {
+            true => {
+                let (alpha_channel, colour_channels) = channels.split_at_mut(1);
+                (colour_channels, &alpha_channel[0])
+            },
+            false => {
+                let (colour_channels, alpha_channel) = channels.split_at_mut(colour_space.num_components() - 1);
+                (colour_channels, &alpha_channel[0])
+            },
+        };
+
+        for channel in colour_channels {
+            match (bit_depth, premultiply) {
+                (BitDepth::Eight, true) => premultiply_channel::<u8>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (BitDepth::Eight, false) => unpremultiply_channel::<u8>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (BitDepth::Sixteen, true) => premultiply_channel::<u16>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (BitDepth::Sixteen, false) => unpremultiply_channel::<u16>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (BitDepth::Float32, true) => premultiply_channel::<f32>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (BitDepth::Float32, false) => unpremultiply_channel::<f32>(channel.reinterpret_as_mut()?, alpha_channel.reinterpret_as()?),
+                (bit_depth, _) => return Err(
+                    ImageErrors::GenericString(format!("Can't premultiply the alpha of {:?} images!", bit_depth))
+                ),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn resize_error(error: ImageErrors) -> Error {
+    Error::DecodingFailure {
+        error: format!(
+            "Zune-image failed to apply resize modification: {:?}",
+            error
+        ),
+    }
+}
@@ -16,6 +16,33 @@ pub enum ImageColourType {
     Rgba32F,
 }
 
+impl ImageColourType {
+    /// The number of channels (including alpha) a pixel of this colour type has.
+    pub fn channel_count(&self) -> usize {
+        match self {
+            ImageColourType::Grey8 | ImageColourType::Grey16 | ImageColourType::Grey32F => 1,
+            ImageColourType::GreyA8 | ImageColourType::GreyA16 | ImageColourType::GreyA32F => 2,
+            ImageColourType::Rgb8 | ImageColourType::Rgb16 | ImageColourType::Rgb32F => 3,
+            ImageColourType::Rgba8 | ImageColourType::Rgba16 | ImageColourType::Rgba32F => 4,
+        }
+    }
+
+    /// The number of bytes a single pixel of this colour type takes up in memory.
+    pub fn bytes_per_pixel(&self) -> usize {
+        let bytes_per_channel = match self {
+            ImageColourType::Grey8 | ImageColourType::GreyA8 | ImageColourType::Rgb8 | ImageColourType::Rgba8 => 1,
+            ImageColourType::Grey16 | ImageColourType::GreyA16 | ImageColourType::Rgb16 | ImageColourType::Rgba16 => 2,
+            ImageColourType::Grey32F | ImageColourType::GreyA32F | ImageColourType::Rgb32F | ImageColourType::Rgba32F => 4,
+        };
+
+        self.channel_count() * bytes_per_channel
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        matches!(self.channel_count(), 2 | 4)
+    }
+}
+
 impl Display for ImageColourType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -1,15 +1,36 @@
-use crate::{colour_type::ImageColourType, format::ImageFormat, image_info::{info::ImageInfo, metadata::ImageMetadata}, pixels::Pixels};
+use crate::{animation::AnimationStream, colour_type::ImageColourType, format::ImageFormat, image_info::{info::ImageInfo, metadata::ImageMetadata}, pixels::Pixels};
 
 pub type ImageSize = (u32, u32);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum DecodedImageContent {
     // NOTE: we do not support outputting channels higher than a u8 yet so 
     // there's no point of storing a decoded image in RAM as anything bigger than a u8
     Static(Pixels),
     Animated(Vec<(Pixels, f32)>),
+    /// Images holding more than one image (multi-page tiffs and the entries of an ico), 
+    /// unlike animation frames each page can have it's own size and colour type.
+    Pages(Vec<Page>),
+    /// Animations too big to hold every frame of in memory, their frames 
+    /// are decoded as they're played back (see [`AnimationStream`]).
+    Streamed(AnimationStream),
 }
 
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub size: ImageSize,
+    pub colour_type: ImageColourType,
+    pub pixels: Pixels,
+}
+
+/// The size and colour type of a page before it has been decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageInfo {
+    pub size: ImageSize,
+    pub colour_type: ImageColourType,
+}
+
+#[derive(Clone)]
 pub struct DecodedImage {
     pub info: ImageInfo,
 
@@ -33,6 +54,8 @@ impl DecodedImage {
             format: format,
             colour_type: colour_type.clone(),
             metadata: metadata,
+            colour_profile: None,
+            document: None,
         };
 
         Self {
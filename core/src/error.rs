@@ -1,5 +1,7 @@
 use std::{fmt::Display, io, result::Result as StdResult};
 
+use crate::decoded_image::ImageSize;
+
 pub type Result<T, E = Error> = StdResult<T, E>;
 
 #[derive(Debug)]
@@ -21,6 +23,12 @@ pub enum Error {
     ImageEncodeFailure { reason: String },
 
     AnimatedImageHasNoFrames,
+    StreamedAnimationNotInMemory,
+    PageOutOfRange { index: usize, page_count: usize },
+
+    /// `required` and `limit` are in bytes.
+    MemoryLimitExceeded { required: u64, limit: u64 },
+    ImageDimensionsLimitExceeded { size: ImageSize, limit: ImageSize },
 }
 
 impl Display for Error {
@@ -70,7 +78,42 @@ impl Display for Error {
                 "This animated image looks to be corrupted, it has no frames! \
                 Are you sure this image is sound? Perhaps try another image."
             ),
-            _ => todo!()
+            Error::StreamedAnimationNotInMemory => write!(
+                f,
+                "This animation is streamed so it's frames aren't held in memory, \
+                it has to be decoded again from the image file!"
+            ),
+            Error::PageOutOfRange { index, page_count } => write!(
+                f,
+                "Page {} does not exist, this image only has {} page(s)!",
+                index + 1,
+                page_count
+            ),
+            Error::MemoryLimitExceeded { required, limit } => write!(
+                f,
+                "Decoding this image would need {:.1} MiB of memory but the limit is {:.1} MiB! \
+                You can raise the limit in your config if you trust this image.",
+                *required as f64 / 1024.0 / 1024.0,
+                *limit as f64 / 1024.0 / 1024.0
+            ),
+            Error::ImageDimensionsLimitExceeded { size, limit } => write!(
+                f,
+                "This image is {}x{} but images are limited to {}x{}! \
+                You can raise the limit in your config if you trust this image.",
+                size.0,
+                size.1,
+                limit.0,
+                limit.1
+            ),
+            Error::IOError(error) => write!(
+                f,
+                "Failed to read the image: {}",
+                error
+            ),
+            Error::UnsupportedColourType => write!(
+                f,
+                "This image's colour type isn't supported, we can't display it!"
+            ),
         }
     }
 }
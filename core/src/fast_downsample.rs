@@ -56,13 +56,10 @@ pub fn experimental_fast_downsample(
 
     let kernel_lookup = precomputed_lanczos(window_size, scale_factor);
 
-    // hopefully I'm right ~ goldy
-    let index_times = match colour_type {
-        ImageColourType::Grey8 | ImageColourType::Grey16 | ImageColourType::Grey32F => 2,
-        ImageColourType::GreyA8 | ImageColourType::GreyA16 | ImageColourType::GreyA32F => 3,
-        ImageColourType::Rgb8 | ImageColourType::Rgb16 | ImageColourType::Rgb32F => 3,
-        ImageColourType::Rgba8 | ImageColourType::Rgba16 | ImageColourType::Rgba32F => 4
-    };
+    let index_times = colour_type.channel_count() as u32;
+
+    // the alpha channel is always the last one.
+    let alpha_index = colour_type.has_alpha().then_some(index_times as usize - 1);
 
     let downsampled_pixels_buffer = Arc::new(
         Mutex::new(
@@ -125,8 +122,20 @@ pub fn experimental_fast_downsample(
                             relative_vertical_pos as usize * image_size.0 as usize + relative_horizontal_pos as usize
                         ) * index_times as usize;
 
+                        // colours are summed premultiplied by their alpha, otherwise the colour of 
+                        // fully transparent pixels would bleed into the edges as dark fringes.
+                        let alpha = match alpha_index {
+                            Some(alpha_index) => pixels[index + alpha_index] as f32 / 255.0,
+                            None => 1.0,
+                        };
+
                         for rgb_index in &rgb_index_range {
-                            rgb_sum[*rgb_index] += pixels[index + rgb_index] as f32 * weight;
+                            let premultiplied_weight = match Some(*rgb_index) == alpha_index {
+                                true => weight,
+                                false => weight * alpha,
+                            };
+
+                            rgb_sum[*rgb_index] += pixels[index + rgb_index] as f32 * premultiplied_weight;
                         }
 
                         sum += weight;
@@ -134,6 +143,21 @@ pub fn experimental_fast_downsample(
                     }
                 }
 
+                // and then unpremultiplied again by the alpha they've averaged out to (the sums 
+                // get divided by the sum of the weights below so it's kept out of the alpha).
+                if let Some(alpha_index) = alpha_index {
+                    let alpha = rgb_sum[alpha_index] / 255.0 / sum;
+
+                    for rgb_index in &rgb_index_range {
+                        if *rgb_index != alpha_index {
+                            rgb_sum[*rgb_index] = match alpha > 0.0 {
+                                true => rgb_sum[*rgb_index] / alpha,
+                                false => 0.0,
+                            };
+                        }
+                    }
+                }
+
                 // work out the index of where the new pixels will lie (destination index).
                 let destination_index: usize = (x * index_times) as usize;
 
@@ -1,15 +1,16 @@
-use std::{fmt::Display, io::{Seek, SeekFrom}, path::PathBuf};
+use std::{fmt::Display, io::{Cursor, Seek, SeekFrom}, path::PathBuf};
 
 use imagesize::Compression;
 
 use crate::{
-    decoded_image::ImageSize, error::{Error, Result}, reader::EncodedImageReader,
+    backends::{image_rs::codecs::{open_raster, pfm::PfmHeader}, rawloader::tiff::determine_raw_format_and_size}, decoded_image::ImageSize, error::{Error, Result},
 };
 
 pub const IMAGE_FORMAT_EXTENSIONS: &[&str] = &[
     "png",
     "jpg", "jpeg",
-    // "svg",
+    "jxl",
+    "svg",
     "gif", "gifv",
     "webp",
     "avif",
@@ -17,12 +18,23 @@ pub const IMAGE_FORMAT_EXTENSIONS: &[&str] = &[
     "qoi",
     "bmp",
     "ico",
+    "exr",
+    "hdr",
+    "dng", "cr2", "nef", "arw",
+    "pnm", "pbm", "pgm", "ppm", "pam",
+    "pfm",
+    "tga",
+    "dds",
+    "ff",
+    "ora",
+    "psd", "psb",
 ];
 
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub enum ImageFormat {
     Png,
     Jpeg,
+    JpegXl,
     Svg,
     Gif,
     Webp,
@@ -30,7 +42,21 @@ pub enum ImageFormat {
     Tiff,
     Qoi,
     Bmp,
-    Ico
+    Ico,
+    Exr,
+    Hdr,
+    Dng,
+    Cr2,
+    Nef,
+    Arw,
+    Pnm,
+    Pfm,
+    Tga,
+    Dds,
+    Farbfeld,
+    OpenRaster,
+    Psd,
+    Psb,
 }
 
 impl Display for ImageFormat {
@@ -38,6 +64,7 @@ impl Display for ImageFormat {
         match self {
             ImageFormat::Png => write!(f, "PNG (Portable Network Graphics)"),
             ImageFormat::Jpeg => write!(f, "JPEG (Joint Photographic Experts Group)"),
+            ImageFormat::JpegXl => write!(f, "JPEG XL (Joint Photographic Experts Group XL)"),
             ImageFormat::Svg => write!(f, "SVG (Scalable Vector Graphics)"),
             ImageFormat::Gif => write!(f, "GIF (Graphics Interchange Format)"),
             ImageFormat::Webp => write!(f, "WEBP (Web Picture)"),
@@ -46,23 +73,227 @@ impl Display for ImageFormat {
             ImageFormat::Qoi => write!(f, "QOI (Quite OK Image Format)"),
             ImageFormat::Bmp => write!(f, "BMP (Bitmap)"),
             ImageFormat::Ico => write!(f, "ICO (Microsoft Icon)"),
+            ImageFormat::Exr => write!(f, "EXR (OpenEXR)"),
+            ImageFormat::Hdr => write!(f, "HDR (Radiance RGBE)"),
+            ImageFormat::Dng => write!(f, "DNG (Digital Negative)"),
+            ImageFormat::Cr2 => write!(f, "CR2 (Canon Raw 2)"),
+            ImageFormat::Nef => write!(f, "NEF (Nikon Electronic Format)"),
+            ImageFormat::Arw => write!(f, "ARW (Sony Alpha Raw)"),
+            ImageFormat::Pnm => write!(f, "PNM (Portable Any Map)"),
+            ImageFormat::Pfm => write!(f, "PFM (Portable Float Map)"),
+            ImageFormat::Tga => write!(f, "TGA (Truevision Graphics Adapter)"),
+            ImageFormat::Dds => write!(f, "DDS (DirectDraw Surface)"),
+            ImageFormat::Farbfeld => write!(f, "FF (Farbfeld)"),
+            ImageFormat::OpenRaster => write!(f, "ORA (OpenRaster)"),
+            ImageFormat::Psd => write!(f, "PSD (Photoshop Document)"),
+            ImageFormat::Psb => write!(f, "PSB (Photoshop Big)"),
         }
     }
 }
 
+impl ImageFormat {
+    /// The format a file extension (without the dot) claims an image is.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let image_format = match extension.to_ascii_lowercase().as_str() {
+            "png" => ImageFormat::Png,
+            "jpg" | "jpeg" => ImageFormat::Jpeg,
+            "jxl" => ImageFormat::JpegXl,
+            "svg" => ImageFormat::Svg,
+            "gif" | "gifv" => ImageFormat::Gif,
+            "webp" => ImageFormat::Webp,
+            "avif" => ImageFormat::Avif,
+            "tiff" | "tif" => ImageFormat::Tiff,
+            "qoi" => ImageFormat::Qoi,
+            "bmp" => ImageFormat::Bmp,
+            "ico" => ImageFormat::Ico,
+            "exr" => ImageFormat::Exr,
+            "hdr" => ImageFormat::Hdr,
+            "dng" => ImageFormat::Dng,
+            "cr2" => ImageFormat::Cr2,
+            "nef" => ImageFormat::Nef,
+            "arw" => ImageFormat::Arw,
+            "pnm" | "pbm" | "pgm" | "ppm" | "pam" => ImageFormat::Pnm,
+            "pfm" => ImageFormat::Pfm,
+            "tga" => ImageFormat::Tga,
+            "dds" => ImageFormat::Dds,
+            "ff" => ImageFormat::Farbfeld,
+            "ora" => ImageFormat::OpenRaster,
+            "psd" => ImageFormat::Psd,
+            "psb" => ImageFormat::Psb,
+            _ => return None,
+        };
+
+        Some(image_format)
+    }
+
+    /// Whether this format stores scene-linear high dynamic range pixels
+    /// that need tone-mapping before they can be displayed.
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(self, ImageFormat::Exr | ImageFormat::Hdr | ImageFormat::Pfm)
+    }
+}
+
+/// Signatures (and the offset they sit at) that give away an image's format from it's first 
+/// few bytes. Formats without a signature (like TGA) are left to imagesize or the file extension.
+const MAGIC_BYTES: &[(usize, &[u8], ImageFormat)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", ImageFormat::Png),
+    (0, b"\xFF\xD8\xFF", ImageFormat::Jpeg),
+    (0, b"\xFF\x0A", ImageFormat::JpegXl),
+    (0, b"\x00\x00\x00\x0CJXL \r\n\x87\n", ImageFormat::JpegXl),
+    (0, b"GIF87a", ImageFormat::Gif),
+    (0, b"GIF89a", ImageFormat::Gif),
+    (8, b"WEBP", ImageFormat::Webp),
+    (4, b"ftypavif", ImageFormat::Avif),
+    (4, b"ftypavis", ImageFormat::Avif),
+    (0, b"II*\x00", ImageFormat::Tiff),
+    (0, b"MM\x00*", ImageFormat::Tiff),
+    (0, b"qoif", ImageFormat::Qoi),
+    (0, b"BM", ImageFormat::Bmp),
+    (0, b"\x00\x00\x01\x00", ImageFormat::Ico),
+    (0, b"\x76\x2F\x31\x01", ImageFormat::Exr),
+    (0, b"#?RADIANCE", ImageFormat::Hdr),
+    (0, b"#?RGBE", ImageFormat::Hdr),
+    (0, b"farbfeld", ImageFormat::Farbfeld),
+    (0, b"DDS ", ImageFormat::Dds),
+    (0, b"8BPS\x00\x01", ImageFormat::Psd),
+    (0, b"8BPS\x00\x02", ImageFormat::Psb),
+    (0, b"P1", ImageFormat::Pnm),
+    (0, b"P2", ImageFormat::Pnm),
+    (0, b"P3", ImageFormat::Pnm),
+    (0, b"P4", ImageFormat::Pnm),
+    (0, b"P5", ImageFormat::Pnm),
+    (0, b"P6", ImageFormat::Pnm),
+];
+
+/// The image format and size we detected from an image's contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DetectedImageFormat {
+    pub image_format: ImageFormat,
+    pub image_size: ImageSize,
+    /// The format the file extension claims the image is when it's 
+    /// contents say otherwise. We go with what the contents say.
+    pub extension_mismatch: Option<ImageFormat>,
+}
+
 /// Only reads the header of an image and determines it's image format and size from that.
-pub fn determine_image_format_and_size_from_header(encoded_image_reader: &mut EncodedImageReader) -> Result<(ImageFormat, ImageSize)> {
-    let image_size_image_type = imagesize::reader_type(&mut *encoded_image_reader)
+pub fn determine_image_format_and_size_from_header<T: AsRef<[u8]>>(encoded_image_reader: &mut Cursor<T>) -> Result<(ImageFormat, ImageSize)> {
+    let detected_image_format = determine_image_format_and_size(encoded_image_reader, None)?;
+
+    Ok((detected_image_format.image_format, detected_image_format.image_size))
+}
+
+/// Determines the format and size of an image in layers, each one only tried if the last one came up short:
+/// 
+/// 1. The magic bytes at the start of the image (plus our own parsers for formats imagesize doesn't know).
+/// 2. imagesize, which only reads as much of the header as it needs.
+/// 3. Parsing the whole image with image-rs, for headers imagesize chokes on (some jpegs and most tiffs).
+/// 4. The file `extension`, when nothing in the image itself gives away it's format.
+/// 
+/// The extension never overrides what the contents of the image say, a 
+/// mismatch is logged and handed back in [`DetectedImageFormat::extension_mismatch`].
+pub fn determine_image_format_and_size<T: AsRef<[u8]>>(encoded_image_reader: &mut Cursor<T>, extension: Option<&str>) -> Result<DetectedImageFormat> {
+    let extension_image_format = extension.and_then(ImageFormat::from_extension);
+
+    let (image_format, image_size) = match determine_image_format_and_size_unknown_to_imagesize(encoded_image_reader.get_ref().as_ref()) {
+        Some(image_format_and_size) => image_format_and_size,
+        None => {
+            let content_image_format = match sniff_image_format(encoded_image_reader.get_ref().as_ref()) {
+                Some(image_format) => Some(image_format),
+                None => determine_image_format_with_imagesize(encoded_image_reader)?,
+            };
+
+            match content_image_format {
+                Some(image_format) => {
+                    // camera raws are tiff containers so nothing above can tell them apart from a normal
+                    // tiff, the size imagesize would give us is also usually just the size of the thumbnail.
+                    let raw_format_and_size = match image_format {
+                        ImageFormat::Tiff => determine_raw_format_and_size(encoded_image_reader.get_ref().as_ref()),
+                        _ => None,
+                    };
+
+                    match raw_format_and_size {
+                        Some(raw_format_and_size) => raw_format_and_size,
+                        None => {
+                            let image_size = determine_image_size(encoded_image_reader, &image_format)?;
+
+                            (image_format, image_size)
+                        },
+                    }
+                },
+                None => match extension_image_format.clone() {
+                    Some(image_format) => {
+                        log::debug!(
+                            "Couldn't tell the image format from it's contents, going with the file extension ({})...",
+                            image_format
+                        );
+
+                        let image_size = determine_image_size(encoded_image_reader, &image_format)?;
+
+                        (image_format, image_size)
+                    },
+                    None => return Err(
+                        Error::ImageHeaderReadFailure {
+                            stage: "Failed to determine format of image!".into(),
+                            error: None,
+                        }
+                    ),
+                },
+            }
+        },
+    };
+
+    encoded_image_reader.seek(SeekFrom::Start(0))
         .map_err(|error| {
             Error::ImageHeaderReadFailure {
-                stage: "Failed to determine format of image!".into(),
+                stage: "Failed to seek back to start after image format and size read.".into(),
                 error: Some(error.to_string()),
             }
         })?;
 
+    let extension_mismatch = extension_image_format.filter(
+        |extension_image_format| extension_image_format != &image_format
+    );
+
+    if let Some(extension_image_format) = &extension_mismatch {
+        log::warn!(
+            "The file extension says the image is '{}' but it's contents say it's '{}', going with the contents...",
+            extension_image_format,
+            image_format
+        );
+    }
+
+    Ok(
+        DetectedImageFormat {
+            image_format,
+            image_size,
+            extension_mismatch,
+        }
+    )
+}
+
+fn sniff_image_format(buffer: &[u8]) -> Option<ImageFormat> {
+    MAGIC_BYTES.iter()
+        .find(|(offset, signature, _)| {
+            buffer.get(*offset..offset + signature.len()) == Some(*signature)
+        })
+        .map(|(_, _, image_format)| image_format.clone())
+}
+
+/// `None` means imagesize has no idea what the image is, rather 
+/// than it knowing the format and us not supporting it.
+fn determine_image_format_with_imagesize<T: AsRef<[u8]>>(encoded_image_reader: &mut Cursor<T>) -> Result<Option<ImageFormat>> {
+    let image_size_image_type = match imagesize::reader_type(&mut *encoded_image_reader) {
+        Ok(image_size_image_type) => image_size_image_type,
+        Err(error) => {
+            log::debug!("imagesize failed to determine format of image: {}", error);
+            return Ok(None);
+        }
+    };
+
     let image_format = match image_size_image_type {
         imagesize::ImageType::Gif => ImageFormat::Gif,
         imagesize::ImageType::Jpeg => ImageFormat::Jpeg,
+        imagesize::ImageType::Jxl => ImageFormat::JpegXl,
         imagesize::ImageType::Png => ImageFormat::Png,
         imagesize::ImageType::Webp => ImageFormat::Webp,
         imagesize::ImageType::Heif(Compression::Av1) => ImageFormat::Avif,
@@ -70,6 +301,17 @@ pub fn determine_image_format_and_size_from_header(encoded_image_reader: &mut En
         imagesize::ImageType::Qoi => ImageFormat::Qoi,
         imagesize::ImageType::Bmp => ImageFormat::Bmp,
         imagesize::ImageType::Ico => ImageFormat::Ico,
+        imagesize::ImageType::Exr => ImageFormat::Exr,
+        imagesize::ImageType::Hdr => ImageFormat::Hdr,
+        imagesize::ImageType::Pnm => ImageFormat::Pnm,
+        imagesize::ImageType::Tga => ImageFormat::Tga,
+        imagesize::ImageType::Dds(_) => ImageFormat::Dds,
+        imagesize::ImageType::Farbfeld => ImageFormat::Farbfeld,
+        // PSBs are version 2 of the same format.
+        imagesize::ImageType::Psd => match encoded_image_reader.get_ref().as_ref().get(4..6) {
+            Some([0, 2]) => ImageFormat::Psb,
+            _ => ImageFormat::Psd,
+        },
         unsupported_format => {
             return Err(
                 Error::ImageFormatNotSupported {
@@ -79,34 +321,140 @@ pub fn determine_image_format_and_size_from_header(encoded_image_reader: &mut En
         }
     };
 
-    // TODO: if this fails with a "failed to fill whole buffer" retry again with the whole buf reader.
-    // Some more JPEGs are failing and most TIF also are failing. 
-    let image_size = image_size_image_type.reader_size(&mut *encoded_image_reader)
-        .map_err(|error| Error::ImageHeaderReadFailure {
-            stage: "Failed to retrieve image dimensions!".into(),
-            error: Some(error.to_string()),
-        })?;
+    Ok(Some(image_format))
+}
 
-    encoded_image_reader.seek(SeekFrom::Start(0))
-        .map_err(|error| {
+/// Tries imagesize first as it only reads the header, if it fails (usually with a "failed to fill 
+/// whole buffer" on some jpegs and most tiffs) we retry by having image-rs parse the whole buffer.
+fn determine_image_size<T: AsRef<[u8]>>(encoded_image_reader: &mut Cursor<T>, image_format: &ImageFormat) -> Result<ImageSize> {
+    let imagesize_error = match imagesize::reader_size(&mut *encoded_image_reader) {
+        Ok(image_size) => return Ok((image_size.width as u32, image_size.height as u32)),
+        Err(error) => error,
+    };
+
+    log::debug!(
+        "imagesize failed to retrieve image dimensions ({}), retrying with the whole image buffer...",
+        imagesize_error
+    );
+
+    match determine_image_size_from_whole_buffer(encoded_image_reader.get_ref().as_ref(), image_format) {
+        Some(image_size) => Ok(image_size),
+        None => Err(
             Error::ImageHeaderReadFailure {
-                stage: "Failed to seek back to start after image format and size read.".into(),
-                error: Some(error.to_string()),
+                stage: "Failed to retrieve image dimensions!".into(),
+                error: Some(imagesize_error.to_string()),
             }
-        })?;
+        ),
+    }
+}
+
+fn determine_image_size_from_whole_buffer(buffer: &[u8], image_format: &ImageFormat) -> Option<ImageSize> {
+    let image_rs_format = match image_format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::Gif => image::ImageFormat::Gif,
+        ImageFormat::Webp => image::ImageFormat::WebP,
+        ImageFormat::Avif => image::ImageFormat::Avif,
+        ImageFormat::Tiff => image::ImageFormat::Tiff,
+        ImageFormat::Qoi => image::ImageFormat::Qoi,
+        ImageFormat::Bmp => image::ImageFormat::Bmp,
+        ImageFormat::Ico => image::ImageFormat::Ico,
+        ImageFormat::Exr => image::ImageFormat::OpenExr,
+        ImageFormat::Hdr => image::ImageFormat::Hdr,
+        ImageFormat::Pnm => image::ImageFormat::Pnm,
+        ImageFormat::Tga => image::ImageFormat::Tga,
+        ImageFormat::Dds => image::ImageFormat::Dds,
+        ImageFormat::Farbfeld => image::ImageFormat::Farbfeld,
+        _ => return None,
+    };
+
+    let dimensions = image::ImageReader::with_format(Cursor::new(buffer), image_rs_format)
+        .into_dimensions();
+
+    match dimensions {
+        Ok(image_size) => Some(image_size),
+        Err(error) => {
+            log::debug!("image-rs also failed to retrieve image dimensions: {}", error);
+            None
+        }
+    }
+}
+
+/// imagesize only knows P1 - P6 of the netpbm family (not PAM or PFM) and
+/// has no idea what an OpenRaster file is, so we check for those ourselves.
+fn determine_image_format_and_size_unknown_to_imagesize(buffer: &[u8]) -> Option<(ImageFormat, ImageSize)> {
+    if let Some(pfm_header) = PfmHeader::parse(buffer) {
+        return Some((ImageFormat::Pfm, pfm_header.size));
+    }
+
+    if buffer.starts_with(b"P7") {
+        return determine_pam_size(buffer).map(|image_size| (ImageFormat::Pnm, image_size));
+    }
+
+    if open_raster::is_open_raster(buffer) {
+        let merged_image = open_raster::extract_merged_image(buffer)?;
+        let image_size = imagesize::blob_size(&merged_image).ok()?;
 
-    Ok((
-        image_format,
-        (image_size.width as u32, image_size.height as u32),
-    ))
+        return Some((ImageFormat::OpenRaster, (image_size.width as u32, image_size.height as u32)));
+    }
+
+    None
+}
+
+/// PAM headers are a line per field (e.g. `WIDTH 64`) up until `ENDHDR`.
+fn determine_pam_size(buffer: &[u8]) -> Option<ImageSize> {
+    let header_end = buffer.windows(6).position(|window| window == b"ENDHDR")?;
+    let header = std::str::from_utf8(&buffer[..header_end]).ok()?;
+
+    let (mut width, mut height) = (None, None);
+
+    for line in header.lines() {
+        match line.split_once(char::is_whitespace) {
+            Some(("WIDTH", value)) => width = value.trim().parse::<u32>().ok(),
+            Some(("HEIGHT", value)) => height = value.trim().parse::<u32>().ok(),
+            _ => {},
+        }
+    }
+
+    Some((width?, height?))
 }
 
-pub fn determine_svg_size(path: &PathBuf) -> ImageSize {
-    let metadata = svg_metadata::Metadata::parse_file(&path)
-        .expect("Failed to parse metadata of the svg file!");
+/// Determines the size of an svg from it's `width` and `height` attributes, falling back 
+/// to the `viewBox` for any of them that are missing (or the other's aspect ratio if there's no `viewBox`).
+pub fn determine_svg_size(path: &PathBuf) -> Result<ImageSize> {
+    let metadata = svg_metadata::Metadata::parse_file(path)
+        .map_err(|error| Error::ImageHeaderReadFailure {
+            stage: "Failed to parse metadata of the svg file!".into(),
+            error: Some(error.to_string()),
+        })?;
+
+    let view_box_size = metadata.view_box().map(|view_box| (view_box.width, view_box.height));
+
+    let (width, height) = match (metadata.width(), metadata.height(), view_box_size) {
+        (Some(width), Some(height), _) => (width, height),
+        (Some(width), None, Some((view_box_width, view_box_height))) => {
+            (width, width * (view_box_height / view_box_width))
+        },
+        (None, Some(height), Some((view_box_width, view_box_height))) => {
+            (height * (view_box_width / view_box_height), height)
+        },
+        (_, _, Some(view_box_size)) => view_box_size,
+        _ => return Err(
+            Error::ImageHeaderReadFailure {
+                stage: "Failed to determine svg size, it has no width, height or viewBox!".into(),
+                error: None,
+            }
+        ),
+    };
 
-    let width = metadata.width().expect("Failed to get SVG width!");
-    let height = metadata.height().expect("Failed to get SVG height!");
+    if !(width.is_finite() && height.is_finite()) || width <= 0.0 || height <= 0.0 {
+        return Err(
+            Error::ImageHeaderReadFailure {
+                stage: "Failed to determine svg size, it's dimensions are invalid!".into(),
+                error: Some(format!("{} x {}", width, height)),
+            }
+        );
+    }
 
-    (width as u32, height as u32)
+    Ok((width.round().max(1.0) as u32, height.round().max(1.0) as u32))
 }
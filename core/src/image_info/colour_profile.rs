@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use moxcms::{ColorProfile, DataColorSpace, ProfileText};
+
+/// An ICC colour profile embedded into an image (PNG iCCP chunk, JPEG APP2 segments, 
+/// etc), it describes what colours the image's pixels actually are. Phones shoot in 
+/// Display P3 and cameras in Adobe RGB, both of which look washed out taken as sRGB.
+#[derive(Clone)]
+pub struct ColourProfile {
+    /// The raw ICC profile as it was embedded into the image.
+    pub icc: Arc<[u8]>,
+    /// The name the profile gives itself (e.g. "Display P3").
+    pub description: Option<String>,
+
+    profile: Arc<ColorProfile>,
+}
+
+impl ColourProfile {
+    /// Returns `None` if the ICC profile is broken or not one we can read.
+    pub fn new(icc: Vec<u8>) -> Option<Self> {
+        let profile = match ColorProfile::new_from_slice(&icc) {
+            Ok(profile) => profile,
+            Err(error) => {
+                log::warn!("Failed to read the image's icc colour profile: {}", error);
+                return None;
+            },
+        };
+
+        let description = profile.description.as_ref().and_then(|description| match description {
+            ProfileText::PlainString(string) => Some(string.clone()),
+            ProfileText::Localizable(strings) => strings.iter()
+                .find(|string| string.language == "en")
+                .or(strings.first())
+                .map(|string| string.value.clone()),
+            ProfileText::Description(description) => match description.ascii_string.is_empty() {
+                true => Some(description.unicode_string.clone()),
+                false => Some(description.ascii_string.clone()),
+            },
+        }).map(|description| description.trim_end_matches('\0').trim().to_string());
+
+        Some(
+            Self {
+                icc: icc.into(),
+                description: description.filter(|description| !description.is_empty()),
+                profile: Arc::new(profile),
+            }
+        )
+    }
+
+    /// Profiles of CMYK, LAB and other colour spaces describe pixels we've already 
+    /// been given as RGB by the decoder, so we can't transform from them.
+    pub fn is_rgb(&self) -> bool {
+        self.profile.color_space == DataColorSpace::Rgb
+    }
+
+    /// Pixels are already sRGB if the profile is sRGB, there's no point transforming them.
+    pub fn is_srgb(&self) -> bool {
+        self.description.as_ref()
+            .is_some_and(|description| description.starts_with("sRGB"))
+    }
+
+    pub(crate) fn profile(&self) -> &ColorProfile {
+        &self.profile
+    }
+}
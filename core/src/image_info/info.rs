@@ -1,4 +1,4 @@
-use crate::{colour_type::ImageColourType, decoded_image::ImageSize, format::ImageFormat, image_info::metadata::ImageMetadata};
+use crate::{colour_type::ImageColourType, decoded_image::ImageSize, format::ImageFormat, image_info::{colour_profile::ColourProfile, metadata::ImageMetadata}};
 
 #[derive(Clone)]
 pub struct ImageInfo {
@@ -6,4 +6,18 @@ pub struct ImageInfo {
     pub format: ImageFormat,
     pub colour_type: ImageColourType,
     pub metadata: ImageMetadata,
+    /// The ICC colour profile embedded into the image, the pixels 
+    /// have already been transformed from it to sRGB if there was one.
+    pub colour_profile: Option<ColourProfile>,
+    /// Only set for layered documents (e.g. photoshop files).
+    pub document: Option<DocumentInfo>,
+}
+
+/// Info about a layered document, we only ever display the flattened
+/// composite of these so the layers themselves are only counted.
+#[derive(Clone)]
+pub struct DocumentInfo {
+    pub size: ImageSize,
+    /// Documents that were flattened have no layers at all.
+    pub layer_count: u32,
 }
\ No newline at end of file
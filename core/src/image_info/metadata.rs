@@ -1,7 +1,7 @@
 use exif::{Field, In, Reader, Tag};
 use log::debug;
 
-use crate::error::{Error, Result};
+use crate::{error::{Error, Result}, orientation::ImageOrientation};
 
 #[derive(Default, Clone)]
 pub struct Location {
@@ -18,6 +18,8 @@ pub struct ImageMetadata {
     pub focal_length: Option<String>,
     pub exposure_time: Option<String>,
     pub originally_created: Option<String>,
+    /// The backends orient the image with this when they decode it (unless told not to).
+    pub orientation: ImageOrientation,
 
     pub location: Location,
 }
@@ -78,6 +80,7 @@ impl ImageMetadata {
                 focal_length: exif.get_field(Tag::FocalLength, In::PRIMARY).and_then(to_option_fn),
                 exposure_time: exposure_time,
                 originally_created: exif.get_field(Tag::DateTimeOriginal, In::PRIMARY).and_then(to_option_fn),
+                orientation: ImageOrientation::from_exif(&exif),
 
                 location: location,
             }
@@ -1,2 +1,3 @@
 pub mod info;
-pub mod metadata;
\ No newline at end of file
+pub mod metadata;
+pub mod colour_profile;
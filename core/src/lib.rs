@@ -1,6 +1,8 @@
 pub mod error;
 pub mod format;
 pub mod reader;
+pub mod limits;
+pub mod thumbnail;
 pub mod pixels;
 pub mod backends;
 pub mod processing;
@@ -8,4 +10,6 @@ pub mod image_info;
 pub mod colour_type;
 pub mod decoded_image;
 pub mod modifications;
-pub mod fast_downsample;
\ No newline at end of file
+pub mod orientation;
+pub mod fast_downsample;
+pub mod animation;
\ No newline at end of file
@@ -0,0 +1,57 @@
+use crate::{colour_type::ImageColourType, decoded_image::ImageSize, error::{Error, Result}};
+
+/// Limits backends must stay within when decoding an image so a huge (or malicious)
+/// image can't have us allocating more memory than the system has. `None` means no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DecodeLimits {
+    /// The widest (in pixels) an image is allowed to be.
+    pub max_width: Option<u32>,
+    /// The tallest (in pixels) an image is allowed to be.
+    pub max_height: Option<u32>,
+    /// The most memory (in bytes) decoding an image is allowed to allocate.
+    pub max_alloc: Option<u64>,
+}
+
+impl DecodeLimits {
+    pub fn new(max_width: Option<u32>, max_height: Option<u32>, max_alloc: Option<u64>) -> Self {
+        Self {
+            max_width,
+            max_height,
+            max_alloc,
+        }
+    }
+
+    /// Errors if decoding an image of this size and colour type would go over the limits.
+    pub fn check(&self, size: ImageSize, colour_type: ImageColourType) -> Result<()> {
+        self.check_dimensions(size)?;
+        self.check_allocation(
+            size.0 as u64 * size.1 as u64 * colour_type.bytes_per_pixel() as u64
+        )
+    }
+
+    pub fn check_dimensions(&self, size: ImageSize) -> Result<()> {
+        let exceeds_width = self.max_width.is_some_and(|max_width| size.0 > max_width);
+        let exceeds_height = self.max_height.is_some_and(|max_height| size.1 > max_height);
+
+        match exceeds_width || exceeds_height {
+            true => Err(
+                Error::ImageDimensionsLimitExceeded {
+                    size,
+                    limit: (
+                        self.max_width.unwrap_or(u32::MAX),
+                        self.max_height.unwrap_or(u32::MAX)
+                    ),
+                }
+            ),
+            false => Ok(()),
+        }
+    }
+
+    /// `required` is in bytes.
+    pub fn check_allocation(&self, required: u64) -> Result<()> {
+        match self.max_alloc {
+            Some(limit) if required > limit => Err(Error::MemoryLimitExceeded { required, limit }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,15 +1,411 @@
-use std::{collections::HashSet, hash::{Hash, Hasher}};
+use serde::{Deserialize, Serialize};
 
-pub type ImageModifications = HashSet<ImageModification>;
+use crate::{decoded_image::ImageSize, orientation::ImageOrientation};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A resize or crop is always relative to the image as it is at that point in the
+/// [`ModificationPipeline`], so cropping then rotating isn't the same as rotating then cropping.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ImageModification {
-    Resize(u32, u32),
+    /// Resizes the image to `width` by `height` with the resampling filter.
+    Resize(u32, u32, ResizeFilter),
+    /// Cuts the image down to the `width` by `height` rectangle with it's
+    /// top left corner at `x`, `y`. The rectangle is clamped to the image's bounds.
+    Crop { x: u32, y: u32, width: u32, height: u32 },
+    /// Rotates the image 90 degrees clockwise.
+    Rotate90,
+    Rotate180,
+    /// Rotates the image 270 degrees clockwise (90 degrees anticlockwise).
+    Rotate270,
+    /// Mirrors the image left to right.
+    FlipHorizontal,
+    /// Mirrors the image top to bottom.
+    FlipVertical,
+    /// Brightens (or darkens with a negative value) the image by this many stops, 
+    /// every stop doubles the light. It's done on linear light so colours hold up.
+    Exposure(f32),
+    /// Pushes tones away from (or towards, below 1.0) the midtones, 1.0 leaves the image as it is.
+    Contrast(f32),
+    /// Lifts the shadows and midtones (or sinks them, below 1.0), 1.0 leaves the image as it is.
+    Gamma(f32),
+    /// Scales how colourful the image is, 0.0 is greyscale and 1.0 leaves the image as it is.
+    Saturation(f32),
+    /// Warms (positive `temperature`) or cools the image and tints it magenta (positive
+    /// `tint`) or green, both go from -1.0 to 1.0 with 0.0 leaving the image as it is.
+    WhiteBalance { temperature: f32, tint: f32 },
 }
 
-impl Hash for ImageModification {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        // all Resize variants hash the same value
-        std::mem::discriminant(self).hash(state);
+impl ImageModification {
+    /// Colour adjustments change the colour of each pixel and nothing else (not the size or shape of the image).
+    pub fn is_colour_adjustment(&self) -> bool {
+        matches!(
+            self,
+            Self::Exposure(..) | Self::Contrast(..) | Self::Gamma(..) | Self::Saturation(..) | Self::WhiteBalance { .. }
+        )
     }
-}
\ No newline at end of file
+}
+
+/// The filter the image is resampled with when it's resized, from the fastest to the sharpest.
+/// Backends that don't have a filter fall back on the closest one they do have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeFilter {
+    /// Picks the nearest pixel, blocky but it keeps pixel art crisp.
+    Nearest,
+    Bilinear,
+    CatmullRom,
+    Gaussian,
+    #[default]
+    Lanczos3,
+}
+
+/// The modifications to make to an image, made in the order they were pushed.
+///
+/// Modifications that follow on from one another of the same kind are merged as they're pushed so two
+/// pipelines that do the same thing compare equal: back to back resizes keep only the last, a crop of a
+/// crop becomes the one crop, back to back flips and rotations add up to (at most) one flip and one rotation
+/// and exposures add up (contrasts, gammas and saturations multiply). White balances are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<ImageModification>", into = "Vec<ImageModification>")]
+pub struct ModificationPipeline {
+    modifications: Vec<ImageModification>,
+}
+
+/// A pipeline boiled down to a crop, a resize and an orientation (in that order) for an image of a
+/// certain size, which is all backends need to make any pipeline and lets them resize before rotating.
+/// Colour adjustments don't care where the pixels are so they can be made at any point after the resize.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FlattenedModifications {
+    /// The `(x, y, width, height)` of the image the way it's stored.
+    pub crop: Option<(u32, u32, u32, u32)>,
+    /// The size the (cropped) image is resized to before it's oriented.
+    pub resize: Option<ImageSize>,
+    /// The filter of the last resize in the pipeline.
+    pub resize_filter: ResizeFilter,
+    pub orientation: ImageOrientation,
+    /// The colour adjustments in the order they have to be made.
+    pub adjustments: Vec<ImageModification>,
+}
+
+impl ModificationPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, modification: ImageModification) {
+        if let Some(transform) = ImageOrientation::from_modification(&modification) {
+            // back to back flips and rotations are merged into one orientation.
+            let orientation = self.pop_orientation().then(transform);
+            self.modifications.extend(orientation.to_modifications());
+
+            return;
+        }
+
+        match (self.modifications.last_mut(), modification) {
+            (Some(ImageModification::Resize(..)), ImageModification::Resize(width, height, filter)) => {
+                *self.modifications.last_mut().unwrap() = ImageModification::Resize(width, height, filter);
+            },
+            (
+                Some(ImageModification::Crop { x, y, width, height }),
+                ImageModification::Crop { x: inner_x, y: inner_y, width: inner_width, height: inner_height }
+            ) => {
+                let (inner_x, inner_y, inner_width, inner_height) = clamp_crop_rect(
+                    (inner_x, inner_y, inner_width, inner_height),
+                    (*width, *height)
+                );
+
+                *x += inner_x;
+                *y += inner_y;
+                *width = inner_width;
+                *height = inner_height;
+            },
+            (Some(ImageModification::Exposure(stops)), ImageModification::Exposure(more_stops)) => *stops += more_stops,
+            (Some(ImageModification::Contrast(value)), ImageModification::Contrast(factor))
+            | (Some(ImageModification::Gamma(value)), ImageModification::Gamma(factor))
+            | (Some(ImageModification::Saturation(value)), ImageModification::Saturation(factor)) => *value *= factor,
+            (_, modification) => self.modifications.push(modification),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ImageModification> {
+        self.modifications.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.modifications.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modifications.is_empty()
+    }
+
+    /// The resize the pipeline ends with, if it ends with one.
+    pub fn last_resize(&self) -> Option<ImageSize> {
+        match self.modifications.last() {
+            Some(ImageModification::Resize(width, height, _)) => Some((*width, *height)),
+            _ => None,
+        }
+    }
+
+    /// Takes the resize off the end of the pipeline, if it ends with one.
+    pub fn pop_resize(&mut self) -> Option<ImageSize> {
+        let last_resize = self.last_resize();
+
+        if last_resize.is_some() {
+            self.modifications.pop();
+        }
+
+        last_resize
+    }
+
+    /// Takes the flips and rotations off the end of the pipeline, returning what they add up to.
+    pub fn pop_orientation(&mut self) -> ImageOrientation {
+        let mut orientation = ImageOrientation::Normal;
+
+        while let Some(transform) = self.modifications.last().and_then(ImageOrientation::from_modification) {
+            orientation = transform.then(orientation);
+            self.modifications.pop();
+        }
+
+        orientation
+    }
+
+    /// Keeps only the modifications `keep` returns true for, what's left is merged like it was pushed.
+    pub fn retain<F: FnMut(&ImageModification) -> bool>(&mut self, mut keep: F) {
+        let modifications = std::mem::take(&mut self.modifications);
+
+        self.extend(modifications.into_iter().filter(|modification| keep(modification)));
+    }
+
+    /// The colour adjustments in the pipeline, in the order they're made.
+    pub fn colour_adjustments(&self) -> Vec<ImageModification> {
+        self.modifications.iter()
+            .filter(|modification| modification.is_colour_adjustment())
+            .cloned()
+            .collect()
+    }
+
+    /// What all the flips and rotations in the pipeline add up to.
+    pub fn orientation(&self) -> ImageOrientation {
+        self.modifications.iter()
+            .filter_map(ImageOrientation::from_modification)
+            .fold(ImageOrientation::Normal, ImageOrientation::then)
+    }
+
+    /// The size an image of `size` ends up once it's been through the pipeline.
+    pub fn output_size(&self, size: ImageSize) -> ImageSize {
+        self.modifications.iter().fold(size, |size, modification| match modification {
+            ImageModification::Resize(width, height, _) => (*width, *height),
+            ImageModification::Crop { x, y, width, height } => {
+                let (_, _, width, height) = clamp_crop_rect((*x, *y, *width, *height), size);
+                (width, height)
+            },
+            modification => ImageOrientation::from_modification(modification)
+                .unwrap_or_default()
+                .orient_size(size),
+        })
+    }
+
+    /// The pipeline with `orientation` put in front of it (for the image's exif orientation).
+    pub(crate) fn oriented_first(&self, orientation: ImageOrientation) -> Self {
+        orientation.to_modifications().into_iter()
+            .chain(self.modifications.iter().cloned())
+            .collect()
+    }
+
+    /// Takes every crop and resize back to the image the way it's stored, for an image of `size`.
+    pub(crate) fn flatten(&self, size: ImageSize) -> FlattenedModifications {
+        // the crop so far, of the image the way it's stored.
+        let (mut crop_x, mut crop_y) = (0.0f32, 0.0f32);
+        let (mut crop_width, mut crop_height) = (size.0 as f32, size.1 as f32);
+
+        // the size of the image so far, before it's oriented.
+        let mut current_size = size;
+        let mut orientation = ImageOrientation::Normal;
+        let mut resize_filter = ResizeFilter::default();
+        let mut adjustments = Vec::new();
+
+        for modification in &self.modifications {
+            match modification {
+                ImageModification::Resize(width, height, filter) => {
+                    current_size = orientation.orient_size((*width, *height));
+                    resize_filter = *filter;
+                },
+                ImageModification::Crop { x, y, width, height } => {
+                    let (x, y, width, height) = orientation.unorient_rect((*x, *y, *width, *height), current_size);
+
+                    // the image may have been resized since the last crop.
+                    let scale_x = crop_width / current_size.0 as f32;
+                    let scale_y = crop_height / current_size.1 as f32;
+
+                    crop_x += x as f32 * scale_x;
+                    crop_y += y as f32 * scale_y;
+                    crop_width = width as f32 * scale_x;
+                    crop_height = height as f32 * scale_y;
+
+                    current_size = (width, height);
+                },
+                adjustment if adjustment.is_colour_adjustment() => adjustments.push(adjustment.clone()),
+                transform => {
+                    orientation = orientation.then(
+                        ImageOrientation::from_modification(transform).unwrap_or_default()
+                    );
+                },
+            }
+        }
+
+        let crop = clamp_crop_rect(
+            (
+                crop_x.round() as u32,
+                crop_y.round() as u32,
+                crop_width.round() as u32,
+                crop_height.round() as u32
+            ),
+            size
+        );
+
+        FlattenedModifications {
+            crop: (crop != (0, 0, size.0, size.1)).then_some(crop),
+            resize: (current_size != (crop.2, crop.3)).then_some(current_size),
+            resize_filter,
+            orientation,
+            adjustments,
+        }
+    }
+
+    /// Crops and resizes are always asked for relative to the first page of a multi-page image,
+    /// so this scales them to a page of a different size (e.g. the smaller entries of an ico).
+    pub(crate) fn scaled_to_page(&self, first_page_size: ImageSize, page_size: ImageSize) -> Self {
+        self.scaled(first_page_size, page_size, true)
+    }
+
+    /// Scales the crops to the same image at a different size (e.g. a raw's embedded preview), resizes
+    /// are left as they are and so is everything after the first one as it's the same size either way.
+    pub(crate) fn scaled_to_source(&self, image_size: ImageSize, source_size: ImageSize) -> Self {
+        self.scaled(image_size, source_size, false)
+    }
+
+    fn scaled(&self, from_size: ImageSize, to_size: ImageSize, scale_resizes: bool) -> Self {
+        let scale_x = to_size.0 as f32 / from_size.0 as f32;
+        let scale_y = to_size.1 as f32 / from_size.1 as f32;
+
+        let scale = |value: u32, scale: f32| ((value as f32 * scale).round() as u32).max(1);
+
+        let mut orientation = ImageOrientation::Normal;
+        let mut resized = false;
+
+        self.modifications.iter().map(|modification| {
+            // the image may be on it's side by now.
+            let (scale_x, scale_y) = match orientation.swaps_dimensions() {
+                true => (scale_y, scale_x),
+                false => (scale_x, scale_y),
+            };
+
+            match modification {
+                ImageModification::Resize(width, height, filter) if scale_resizes => {
+                    ImageModification::Resize(scale(*width, scale_x), scale(*height, scale_y), *filter)
+                },
+                ImageModification::Resize(..) => {
+                    resized = true;
+                    modification.clone()
+                },
+                ImageModification::Crop { x, y, width, height } if !resized => ImageModification::Crop {
+                    x: (*x as f32 * scale_x).round() as u32,
+                    y: (*y as f32 * scale_y).round() as u32,
+                    width: scale(*width, scale_x),
+                    height: scale(*height, scale_y),
+                },
+                modification => {
+                    if let Some(transform) = ImageOrientation::from_modification(modification) {
+                        orientation = orientation.then(transform);
+                    }
+
+                    modification.clone()
+                },
+            }
+        }).collect()
+    }
+}
+
+impl FlattenedModifications {
+    /// The crop, the resize then the flip and rotation, in the order they have to be made (colour adjustments aren't included).
+    pub fn to_modifications(&self) -> Vec<ImageModification> {
+        let mut modifications = Vec::new();
+
+        if let Some((x, y, width, height)) = self.crop {
+            modifications.push(ImageModification::Crop { x, y, width, height });
+        }
+
+        if let Some((width, height)) = self.resize {
+            modifications.push(ImageModification::Resize(width, height, self.resize_filter));
+        }
+
+        modifications.extend(self.orientation.to_modifications());
+
+        modifications
+    }
+
+    /// Scales the crop to the same image at a different size (e.g. a jpeg decoded at a reduced scale).
+    pub fn scale_crop(mut self, image_size: ImageSize, scaled_size: ImageSize) -> Self {
+        let scale_x = scaled_size.0 as f32 / image_size.0 as f32;
+        let scale_y = scaled_size.1 as f32 / image_size.1 as f32;
+
+        self.crop = self.crop.map(|(x, y, width, height)| (
+            (x as f32 * scale_x).round() as u32,
+            (y as f32 * scale_y).round() as u32,
+            ((width as f32 * scale_x).round() as u32).max(1),
+            ((height as f32 * scale_y).round() as u32).max(1),
+        ));
+
+        self
+    }
+}
+
+impl Extend<ImageModification> for ModificationPipeline {
+    fn extend<T: IntoIterator<Item = ImageModification>>(&mut self, modifications: T) {
+        for modification in modifications {
+            self.push(modification);
+        }
+    }
+}
+
+impl FromIterator<ImageModification> for ModificationPipeline {
+    fn from_iter<T: IntoIterator<Item = ImageModification>>(modifications: T) -> Self {
+        let mut pipeline = Self::new();
+        pipeline.extend(modifications);
+        pipeline
+    }
+}
+
+impl From<Vec<ImageModification>> for ModificationPipeline {
+    fn from(modifications: Vec<ImageModification>) -> Self {
+        modifications.into_iter().collect()
+    }
+}
+
+impl From<ModificationPipeline> for Vec<ImageModification> {
+    fn from(pipeline: ModificationPipeline) -> Self {
+        pipeline.modifications
+    }
+}
+
+impl IntoIterator for ModificationPipeline {
+    type Item = ImageModification;
+    type IntoIter = std::vec::IntoIter<ImageModification>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.modifications.into_iter()
+    }
+}
+
+/// Clamps a crop's rectangle to an image of `size` so there's always at least a pixel left, even for an empty image.
+pub(crate) fn clamp_crop_rect((x, y, width, height): (u32, u32, u32, u32), size: ImageSize) -> (u32, u32, u32, u32) {
+    let x = x.min(size.0.saturating_sub(1));
+    let y = y.min(size.1.saturating_sub(1));
+
+    (
+        x,
+        y,
+        width.min(size.0.saturating_sub(x)).max(1),
+        height.min(size.1.saturating_sub(y)).max(1),
+    )
+}
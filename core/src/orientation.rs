@@ -0,0 +1,270 @@
+use std::io::Cursor;
+
+use exif::{Exif, In, Reader, Tag};
+
+use crate::{decoded_image::{DecodedImage, DecodedImageContent, ImageSize}, modifications::{ImageModification, clamp_crop_rect}, pixels::Pixels};
+
+/// How an image has to be rotated and flipped to be displayed the right way up, read
+/// from the exif `Orientation` tag. Cameras (phones especially) save photos the way the
+/// sensor sees them and leave it to us to turn them around.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageOrientation {
+    #[default]
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    /// Flipped across the top left to bottom right diagonal.
+    Transpose,
+    /// Rotated 90 degrees clockwise.
+    Rotate90,
+    /// Flipped across the top right to bottom left diagonal.
+    Transverse,
+    /// Rotated 270 degrees clockwise.
+    Rotate270,
+}
+
+impl ImageOrientation {
+    /// Values outside of 1 to 8 are invalid and treated as normal.
+    pub fn from_exif_value(value: u32) -> Self {
+        match value {
+            2 => Self::FlipHorizontal,
+            3 => Self::Rotate180,
+            4 => Self::FlipVertical,
+            5 => Self::Transpose,
+            6 => Self::Rotate90,
+            7 => Self::Transverse,
+            8 => Self::Rotate270,
+            _ => Self::Normal,
+        }
+    }
+
+    pub(crate) fn from_exif(exif: &Exif) -> Self {
+        exif.get_field(Tag::Orientation, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .map(Self::from_exif_value)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn from_exif_chunk(exif_chunk: &[u8]) -> Self {
+        match Reader::new().read_raw(exif_chunk.to_vec()) {
+            Ok(exif) => Self::from_exif(&exif),
+            Err(_) => Self::Normal,
+        }
+    }
+
+    /// The orientation a flip or rotation modification is, `None` if it's neither.
+    pub fn from_modification(modification: &ImageModification) -> Option<Self> {
+        match modification {
+            ImageModification::Rotate90 => Some(Self::Rotate90),
+            ImageModification::Rotate180 => Some(Self::Rotate180),
+            ImageModification::Rotate270 => Some(Self::Rotate270),
+            ImageModification::FlipHorizontal => Some(Self::FlipHorizontal),
+            ImageModification::FlipVertical => Some(Self::FlipVertical),
+            _ => None,
+        }
+    }
+
+    /// The flip and rotation that make up this orientation as modifications.
+    pub fn to_modifications(self) -> Vec<ImageModification> {
+        let (flip, quarter_turns) = self.to_parts();
+
+        let mut modifications = Vec::new();
+
+        if flip {
+            modifications.push(ImageModification::FlipHorizontal);
+        }
+
+        match quarter_turns {
+            1 => modifications.push(ImageModification::Rotate90),
+            2 => modifications.push(ImageModification::Rotate180),
+            3 => modifications.push(ImageModification::Rotate270),
+            _ => {},
+        }
+
+        modifications
+    }
+
+    /// This orientation followed by `next`.
+    pub fn then(self, next: Self) -> Self {
+        let (flip, quarter_turns) = self.to_parts();
+        let (next_flip, next_quarter_turns) = next.to_parts();
+
+        // flipping after a rotation is the same as flipping first then rotating the other way.
+        let quarter_turns = match next_flip {
+            true => next_quarter_turns + 4 - quarter_turns,
+            false => quarter_turns + next_quarter_turns,
+        };
+
+        Self::from_parts(flip ^ next_flip, quarter_turns % 4)
+    }
+
+    /// The orientation that undoes this one.
+    pub fn inverse(self) -> Self {
+        match self.to_parts() {
+            (true, _) => self,
+            (false, quarter_turns) => Self::from_parts(false, (4 - quarter_turns) % 4),
+        }
+    }
+
+    /// Every orientation is a horizontal flip (or not) followed by a number of clockwise quarter turns.
+    fn to_parts(self) -> (bool, u8) {
+        match self {
+            Self::Normal => (false, 0),
+            Self::Rotate90 => (false, 1),
+            Self::Rotate180 => (false, 2),
+            Self::Rotate270 => (false, 3),
+            Self::FlipHorizontal => (true, 0),
+            Self::Transverse => (true, 1),
+            Self::FlipVertical => (true, 2),
+            Self::Transpose => (true, 3),
+        }
+    }
+
+    fn from_parts(flip: bool, quarter_turns: u8) -> Self {
+        match (flip, quarter_turns) {
+            (false, 1) => Self::Rotate90,
+            (false, 2) => Self::Rotate180,
+            (false, 3) => Self::Rotate270,
+            (true, 0) => Self::FlipHorizontal,
+            (true, 1) => Self::Transverse,
+            (true, 2) => Self::FlipVertical,
+            (true, 3) => Self::Transpose,
+            _ => Self::Normal,
+        }
+    }
+
+    /// Whether the width and height of the image trade places once it's oriented.
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(self, Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270)
+    }
+
+    /// The size an image of `size` will be once it's oriented.
+    pub fn orient_size(&self, size: ImageSize) -> ImageSize {
+        match self.swaps_dimensions() {
+            true => (size.1, size.0),
+            false => size,
+        }
+    }
+
+    /// Takes the `(x, y, width, height)` rectangle of the oriented image back to where it 
+    /// is in the image the way it's stored, `size` being the size of the stored image.
+    pub fn unorient_rect(&self, rect: (u32, u32, u32, u32), size: ImageSize) -> (u32, u32, u32, u32) {
+        let (x, y, width, height) = clamp_crop_rect(rect, self.orient_size(size));
+        let source_size = (size.0 as usize, size.1 as usize);
+
+        let (first_x, first_y) = self.source_position(x as usize, y as usize, source_size);
+        let (last_x, last_y) = self.source_position(
+            (x + width - 1) as usize,
+            (y + height - 1) as usize,
+            source_size
+        );
+
+        (
+            first_x.min(last_x) as u32,
+            first_y.min(last_y) as u32,
+            first_x.abs_diff(last_x) as u32 + 1,
+            first_y.abs_diff(last_y) as u32 + 1,
+        )
+    }
+
+    /// Where the pixel at `(x, y)` of the oriented image comes from in an image of `size`.
+    fn source_position(&self, x: usize, y: usize, size: (usize, usize)) -> (usize, usize) {
+        let (width, height) = size;
+
+        match self {
+            Self::Normal => (x, y),
+            Self::FlipHorizontal => (width - 1 - x, y),
+            Self::Rotate180 => (width - 1 - x, height - 1 - y),
+            Self::FlipVertical => (x, height - 1 - y),
+            Self::Transpose => (y, x),
+            Self::Rotate90 => (y, height - 1 - x),
+            Self::Transverse => (width - 1 - y, height - 1 - x),
+            Self::Rotate270 => (width - 1 - y, x),
+        }
+    }
+}
+
+/// The orientation a backend decodes with, images without any exif are left the way they're stored.
+pub(crate) fn orientation_to_decode_with(exif_chunk: Option<&[u8]>, auto_orientation: bool) -> ImageOrientation {
+    match (auto_orientation, exif_chunk) {
+        (true, Some(exif_chunk)) => ImageOrientation::from_exif_chunk(exif_chunk),
+        _ => ImageOrientation::Normal,
+    }
+}
+
+/// Reads the orientation straight out of the exif of an encoded image (jpegs,
+/// tiffs, camera raws, pngs, webps and heifs) without decoding any of it.
+pub fn determine_image_orientation(buffer: &[u8]) -> ImageOrientation {
+    match Reader::new().read_from_container(&mut Cursor::new(buffer)) {
+        Ok(exif) => ImageOrientation::from_exif(&exif),
+        Err(_) => ImageOrientation::Normal,
+    }
+}
+
+impl DecodedImage {
+    /// Rotates and flips the decoded image the right way up. The size of the image
+    /// (and `info.size`) is swapped along with it for orientations that turn it on it's side.
+    pub fn orient(&mut self, orientation: ImageOrientation) {
+        if orientation == ImageOrientation::Normal {
+            return;
+        }
+
+        log::debug!("Orienting decoded image ({:?})...", orientation);
+
+        let channel_count = self.colour_type.channel_count();
+
+        match &mut self.content {
+            DecodedImageContent::Static(pixels) => {
+                *pixels = orient_pixels(pixels, self.size, channel_count, orientation);
+            },
+            DecodedImageContent::Animated(frames) => {
+                for (pixels, _) in frames {
+                    *pixels = orient_pixels(pixels, self.size, channel_count, orientation);
+                }
+            },
+            DecodedImageContent::Pages(pages) => {
+                for page in pages {
+                    page.pixels = orient_pixels(
+                        &page.pixels,
+                        page.size,
+                        page.colour_type.channel_count(),
+                        orientation
+                    );
+                    page.size = orientation.orient_size(page.size);
+                }
+            },
+            // the frames of a streamed animation are oriented by the backend as they're decoded.
+            DecodedImageContent::Streamed(_) => return,
+        }
+
+        self.size = orientation.orient_size(self.size);
+        self.info.size = orientation.orient_size(self.info.size);
+    }
+}
+
+fn orient_pixels(pixels: &Pixels, size: ImageSize, channel_count: usize, orientation: ImageOrientation) -> Pixels {
+    match pixels {
+        Pixels::U8(samples) => Pixels::U8(orient_samples(samples, size, channel_count, orientation)),
+        Pixels::U16(samples) => Pixels::U16(orient_samples(samples, size, channel_count, orientation)),
+        Pixels::F32(samples) => Pixels::F32(orient_samples(samples, size, channel_count, orientation)),
+    }
+}
+
+fn orient_samples<T: Copy>(samples: &[T], size: ImageSize, channel_count: usize, orientation: ImageOrientation) -> Vec<T> {
+    let source_size = (size.0 as usize, size.1 as usize);
+    let (oriented_width, oriented_height) = orientation.orient_size(size);
+
+    let mut oriented_samples = Vec::with_capacity(samples.len());
+
+    for y in 0..oriented_height as usize {
+        for x in 0..oriented_width as usize {
+            let (source_x, source_y) = orientation.source_position(x, y, source_size);
+            let index = (source_y * source_size.0 + source_x) * channel_count;
+
+            oriented_samples.extend_from_slice(&samples[index..index + channel_count]);
+        }
+    }
+
+    oriented_samples
+}
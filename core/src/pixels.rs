@@ -2,7 +2,7 @@ use std::{fmt::Display, ops::{Deref, DerefMut}};
 
 use crate::colour_type::ImageColourType;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Pixels {
     /// A low dynamic range pixel container, 8 bit depth (aka SDR).
     U8(Vec<u8>),
@@ -0,0 +1,181 @@
+use rayon::{iter::ParallelIterator, slice::ParallelSliceMut};
+
+use crate::{colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, modifications::ImageModification, pixels::Pixels, processing::tone_mapping::linear_to_srgb};
+
+/// How far a white balance of 1.0 (or -1.0) pushes each channel.
+const WHITE_BALANCE_STRENGTH: f32 = 0.25;
+
+/// A sample that can be taken to a float (0.0 - 1.0 or beyond for HDR) to be adjusted and back again.
+pub(crate) trait AdjustableSample: Copy + Send + Sync {
+    /// Float samples are scene-linear, integer samples are sRGB encoded.
+    const LINEAR: bool;
+
+    fn to_f32(self) -> f32;
+    fn from_f32(value: f32) -> Self;
+}
+
+impl AdjustableSample for u8 {
+    const LINEAR: bool = false;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / u8::MAX as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+    }
+}
+
+impl AdjustableSample for u16 {
+    const LINEAR: bool = false;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / u16::MAX as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16
+    }
+}
+
+impl AdjustableSample for f32 {
+    const LINEAR: bool = true;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+/// Makes the colour adjustments to interleaved samples, alpha (the last channel if
+/// there is one) is left alone and greyscale images are adjusted as grey rgb pixels.
+pub(crate) fn adjust_samples<T: AdjustableSample>(
+    samples: &mut [T],
+    channel_count: usize,
+    has_alpha: bool,
+    adjustments: &[ImageModification],
+) {
+    if adjustments.is_empty() {
+        return;
+    }
+
+    let colour_channel_count = match has_alpha {
+        true => channel_count - 1,
+        false => channel_count,
+    };
+
+    samples.par_chunks_mut(channel_count).for_each(|pixel| {
+        let mut rgb = match colour_channel_count {
+            1 => [pixel[0].to_f32(); 3],
+            _ => [pixel[0].to_f32(), pixel[1].to_f32(), pixel[2].to_f32()],
+        };
+
+        // contrast, gamma and saturation are perceptual so they're made on sRGB encoded values.
+        if T::LINEAR {
+            rgb = rgb.map(|value| linear_to_srgb(value.max(0.0)));
+        }
+
+        for adjustment in adjustments {
+            rgb = adjust_colour(rgb, adjustment);
+        }
+
+        if T::LINEAR {
+            rgb = rgb.map(srgb_to_linear);
+        }
+
+        match colour_channel_count {
+            1 => pixel[0] = T::from_f32(luminance(rgb)),
+            _ => {
+                for (sample, value) in pixel.iter_mut().zip(rgb) {
+                    *sample = T::from_f32(value);
+                }
+            },
+        }
+    });
+}
+
+/// Makes a colour adjustment to an sRGB encoded colour, anything that isn't one leaves the colour as it is.
+fn adjust_colour(rgb: [f32; 3], adjustment: &ImageModification) -> [f32; 3] {
+    match adjustment {
+        ImageModification::Exposure(stops) => {
+            let gain = 2.0f32.powf(*stops);
+
+            rgb.map(|value| linear_to_srgb(srgb_to_linear(value) * gain))
+        },
+        ImageModification::Contrast(contrast) => rgb.map(|value| (value - 0.5) * contrast + 0.5),
+        ImageModification::Gamma(gamma) => {
+            let exponent = 1.0 / gamma.max(0.01);
+
+            rgb.map(|value| value.max(0.0).powf(exponent))
+        },
+        ImageModification::Saturation(saturation) => {
+            let luminance = luminance(rgb);
+
+            rgb.map(|value| luminance + (value - luminance) * saturation)
+        },
+        ImageModification::WhiteBalance { temperature, tint } => {
+            // warmer is more red and less blue, a magenta tint is less green.
+            let gains = [
+                1.0 + temperature * WHITE_BALANCE_STRENGTH,
+                1.0 - tint * WHITE_BALANCE_STRENGTH,
+                1.0 - temperature * WHITE_BALANCE_STRENGTH,
+            ];
+
+            [0, 1, 2].map(|index| linear_to_srgb((srgb_to_linear(rgb[index]) * gains[index]).max(0.0)))
+        },
+        _ => rgb,
+    }
+}
+
+/// Decodes an sRGB encoded value to linear light.
+fn srgb_to_linear(value: f32) -> f32 {
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+fn luminance([red, green, blue]: [f32; 3]) -> f32 {
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+pub(crate) fn adjust_pixels(pixels: &mut Pixels, colour_type: ImageColourType, adjustments: &[ImageModification]) {
+    let (channel_count, has_alpha) = (colour_type.channel_count(), colour_type.has_alpha());
+
+    match pixels {
+        Pixels::U8(samples) => adjust_samples(samples, channel_count, has_alpha, adjustments),
+        Pixels::U16(samples) => adjust_samples(samples, channel_count, has_alpha, adjustments),
+        Pixels::F32(samples) => adjust_samples(samples, channel_count, has_alpha, adjustments),
+    }
+}
+
+impl DecodedImage {
+    /// Makes the colour adjustments (exposure, contrast, gamma, saturation and white balance)
+    /// to every frame or page of the image, any other modifications are skipped over.
+    pub fn adjust_colours(&mut self, adjustments: &[ImageModification]) {
+        if adjustments.is_empty() {
+            return;
+        }
+
+        log::debug!("Adjusting the colours of decoded image...");
+
+        match &mut self.content {
+            DecodedImageContent::Static(pixels) => adjust_pixels(pixels, self.colour_type, adjustments),
+            DecodedImageContent::Animated(frames) => {
+                for (pixels, _) in frames {
+                    adjust_pixels(pixels, self.colour_type, adjustments);
+                }
+            },
+            DecodedImageContent::Pages(pages) => {
+                for page in pages {
+                    adjust_pixels(&mut page.pixels, page.colour_type, adjustments);
+                }
+            },
+            // the frames of a streamed animation are adjusted by the backend as they're decoded.
+            DecodedImageContent::Streamed(_) => {},
+        }
+    }
+}
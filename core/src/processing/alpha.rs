@@ -0,0 +1,142 @@
+use rayon::{iter::{IndexedParallelIterator, ParallelIterator}, slice::{ParallelSlice, ParallelSliceMut}};
+
+use crate::{colour_type::ImageColourType, pixels::Pixels};
+
+/// How many pixels each thread works through at a time.
+const PIXELS_PER_CHUNK: usize = 4096;
+
+/// A sample (of any bit depth) that can be multiplied by and divided by an alpha of the same depth.
+pub(crate) trait AlphaSample: Copy + Send + Sync {
+    fn premultiply(self, alpha: Self) -> Self;
+    fn unpremultiply(self, alpha: Self) -> Self;
+}
+
+impl AlphaSample for u8 {
+    fn premultiply(self, alpha: Self) -> Self {
+        ((self as u32 * alpha as u32 + 127) / 255) as u8
+    }
+
+    fn unpremultiply(self, alpha: Self) -> Self {
+        match alpha {
+            0 => 0,
+            alpha => ((self as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8,
+        }
+    }
+}
+
+impl AlphaSample for u16 {
+    fn premultiply(self, alpha: Self) -> Self {
+        ((self as u64 * alpha as u64 + 32767) / 65535) as u16
+    }
+
+    fn unpremultiply(self, alpha: Self) -> Self {
+        match alpha {
+            0 => 0,
+            alpha => ((self as u64 * 65535 + alpha as u64 / 2) / alpha as u64).min(65535) as u16,
+        }
+    }
+}
+
+impl AlphaSample for f32 {
+    fn premultiply(self, alpha: Self) -> Self {
+        self * alpha
+    }
+
+    fn unpremultiply(self, alpha: Self) -> Self {
+        match alpha > 0.0 {
+            true => self / alpha,
+            false => 0.0,
+        }
+    }
+}
+
+/// Multiplies the colour channels of every pixel by it's alpha.
+///
+/// Premultiplied pixels are what should be averaged when resampling (and what egui's `Color32`
+/// expects), otherwise the colour of fully transparent pixels bleeds into the edges around them
+/// as dark fringes. Pixels without an alpha channel are left as they are.
+pub fn premultiply_alpha(pixels: &mut Pixels, colour_type: ImageColourType) {
+    if !colour_type.has_alpha() {
+        return;
+    }
+
+    let channel_count = colour_type.channel_count();
+
+    match pixels {
+        Pixels::U8(samples) => premultiply_samples(samples, channel_count),
+        Pixels::U16(samples) => premultiply_samples(samples, channel_count),
+        Pixels::F32(samples) => premultiply_samples(samples, channel_count),
+    }
+}
+
+/// Divides the colour channels of every pixel by it's alpha, undoing [`premultiply_alpha`].
+///
+/// The colour of fully transparent pixels is lost when premultiplying so they come back black.
+pub fn unpremultiply_alpha(pixels: &mut Pixels, colour_type: ImageColourType) {
+    if !colour_type.has_alpha() {
+        return;
+    }
+
+    let channel_count = colour_type.channel_count();
+
+    match pixels {
+        Pixels::U8(samples) => unpremultiply_samples(samples, channel_count),
+        Pixels::U16(samples) => unpremultiply_samples(samples, channel_count),
+        Pixels::F32(samples) => unpremultiply_samples(samples, channel_count),
+    }
+}
+
+/// [`premultiply_alpha`] for samples that aren't in [`Pixels`] (e.g. the backend's own buffers),
+/// the alpha is always taken to be the last of the `channel_count` channels.
+pub(crate) fn premultiply_samples<T: AlphaSample>(samples: &mut [T], channel_count: usize) {
+    for_each_pixel(samples, channel_count, |colour, alpha| {
+        for sample in colour {
+            *sample = sample.premultiply(alpha);
+        }
+    });
+}
+
+/// [`unpremultiply_alpha`] for samples that aren't in [`Pixels`].
+pub(crate) fn unpremultiply_samples<T: AlphaSample>(samples: &mut [T], channel_count: usize) {
+    for_each_pixel(samples, channel_count, |colour, alpha| {
+        for sample in colour {
+            *sample = sample.unpremultiply(alpha);
+        }
+    });
+}
+
+/// [`premultiply_samples`] for a colour channel that's kept apart from the others (zune-image 
+/// stores every channel in it's own buffer), `alpha` being the image's alpha channel.
+pub(crate) fn premultiply_channel<T: AlphaSample>(channel: &mut [T], alpha: &[T]) {
+    for_each_channel_sample(channel, alpha, |sample, alpha| sample.premultiply(alpha));
+}
+
+/// [`unpremultiply_samples`] for a colour channel that's kept apart from the others.
+pub(crate) fn unpremultiply_channel<T: AlphaSample>(channel: &mut [T], alpha: &[T]) {
+    for_each_channel_sample(channel, alpha, |sample, alpha| sample.unpremultiply(alpha));
+}
+
+fn for_each_channel_sample<T: AlphaSample>(channel: &mut [T], alpha: &[T], function: impl Fn(T, T) -> T + Send + Sync) {
+    channel.par_chunks_mut(PIXELS_PER_CHUNK)
+        .zip(alpha.par_chunks(PIXELS_PER_CHUNK))
+        .for_each(|(samples, alpha)| {
+            for (sample, alpha) in samples.iter_mut().zip(alpha) {
+                *sample = function(*sample, *alpha);
+            }
+        });
+}
+
+/// Hands the colour channels and the alpha (always the last channel) of each pixel to `function`.
+fn for_each_pixel<T: Copy + Send + Sync>(
+    samples: &mut [T],
+    channel_count: usize,
+    function: impl Fn(&mut [T], T) + Send + Sync,
+) {
+    samples.par_chunks_mut(channel_count * PIXELS_PER_CHUNK).for_each(|chunk| {
+        for pixel in chunk.chunks_exact_mut(channel_count) {
+            let (colour, alpha) = pixel.split_at_mut(channel_count - 1);
+
+            function(colour, alpha[0]);
+        }
+    });
+}
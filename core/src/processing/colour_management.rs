@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use moxcms::{ColorProfile, Layout, TransformExecutor, TransformOptions};
+use rayon::{iter::ParallelIterator, slice::ParallelSliceMut};
+
+use crate::{colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, image_info::colour_profile::ColourProfile, pixels::Pixels};
+
+impl DecodedImage {
+    /// Transforms the pixels of every frame or page from the colour profile they're in to sRGB (what 
+    /// we display in) and keeps the profile in the image's info. Pixels are left as they are if we 
+    /// can't transform from the profile, as well as greyscale and float (scene-linear) pixels.
+    pub fn convert_to_srgb(&mut self, colour_profile: ColourProfile) {
+        if colour_profile.is_rgb() && !colour_profile.is_srgb() {
+            log::debug!(
+                "Transforming decoded image from it's colour profile ({}) to sRGB...",
+                colour_profile.description.as_deref().unwrap_or("unnamed")
+            );
+
+            let srgb_profile = ColorProfile::new_srgb();
+            let source_profile = colour_profile.profile();
+
+            match &mut self.content {
+                DecodedImageContent::Static(pixels) => {
+                    convert_pixels(pixels, self.colour_type, source_profile, &srgb_profile);
+                },
+                DecodedImageContent::Animated(frames) => {
+                    for (pixels, _) in frames {
+                        convert_pixels(pixels, self.colour_type, source_profile, &srgb_profile);
+                    }
+                },
+                DecodedImageContent::Pages(pages) => {
+                    for page in pages {
+                        convert_pixels(&mut page.pixels, page.colour_type, source_profile, &srgb_profile);
+                    }
+                },
+                // the frames of a streamed animation are converted by the backend as they're decoded.
+                DecodedImageContent::Streamed(_) => {},
+            }
+        }
+
+        self.info.colour_profile = Some(colour_profile);
+    }
+}
+
+fn convert_pixels(pixels: &mut Pixels, colour_type: ImageColourType, source_profile: &ColorProfile, srgb_profile: &ColorProfile) {
+    let layout = match colour_type.channel_count() {
+        3 => Layout::Rgb,
+        4 => Layout::Rgba,
+        _ => return,
+    };
+
+    let options = TransformOptions::default();
+
+    let result = match pixels {
+        Pixels::U8(samples) => source_profile.create_transform_8bit(layout, srgb_profile, layout, options)
+            .map(|transform| transform_samples(samples, colour_type.channel_count(), transform)),
+        Pixels::U16(samples) => source_profile.create_transform_16bit(layout, srgb_profile, layout, options)
+            .map(|transform| transform_samples(samples, colour_type.channel_count(), transform)),
+        Pixels::F32(_) => return,
+    };
+
+    if let Err(error) = result {
+        log::warn!("Failed to transform pixels from their colour profile to sRGB: {}", error);
+    }
+}
+
+/// Transforms 4096 pixels at a time (one chunk per thread) so we never need a second copy of the whole image.
+fn transform_samples<T: Copy + Default + Send + Sync>(
+    samples: &mut [T],
+    channel_count: usize,
+    transform: Arc<dyn TransformExecutor<T> + Send + Sync>,
+) {
+    const PIXELS_PER_CHUNK: usize = 4096;
+
+    samples.par_chunks_mut(PIXELS_PER_CHUNK * channel_count).for_each(|chunk| {
+        let source = chunk.to_vec();
+
+        if let Err(error) = transform.transform(&source, chunk) {
+            log::warn!("Failed to transform pixels from their colour profile to sRGB: {}", error);
+        }
+    });
+}
+
+/// Transforms the pixels of a single animation frame to sRGB, for streamed animations 
+/// which are decoded a frame at a time and never make it into a [`DecodedImage`] together.
+pub(crate) fn convert_frame_to_srgb(pixels: &mut Pixels, colour_type: ImageColourType, colour_profile: &ColourProfile) {
+    if colour_profile.is_rgb() && !colour_profile.is_srgb() {
+        convert_pixels(pixels, colour_type, colour_profile.profile(), &ColorProfile::new_srgb());
+    }
+}
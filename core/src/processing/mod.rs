@@ -1,3 +1,5 @@
-// TODO: add tone-mapping methods for HDR images when zune-image HDR decoder is implemented.
-
-pub mod quantization;
\ No newline at end of file
+pub mod adjustments;
+pub mod quantization;
+pub mod tone_mapping;
+pub mod colour_management;
+pub mod alpha;
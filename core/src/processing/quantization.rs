@@ -1,23 +1,323 @@
+use std::{fmt::Display, sync::OnceLock};
+
 use log::debug;
+use rayon::{iter::{IndexedParallelIterator, ParallelIterator}, slice::ParallelSliceMut};
+
+use crate::{colour_type::ImageColourType, decoded_image::ImageSize, pixels::Pixels};
+
+/// Size of the (tileable) ordered and blue noise threshold maps.
+const THRESHOLD_MAP_SIZE: usize = 64;
 
-use crate::pixels::Pixels;
+/// Floyd–Steinberg is run on bands of this many rows in parallel. The error
+/// isn't carried over between bands but at 8 bit that's impossible to spot.
+const ERROR_DIFFUSION_BAND_HEIGHT: usize = 64;
 
+/// How the quantization error is spread out when going down to 8 bit,
+/// without dithering smooth 16 bit gradients turn into visible bands.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dithering {
+    /// Just round to the nearest 8 bit value.
+    None,
+    /// A tiled bayer matrix, very fast but has a faint cross-hatch pattern.
+    Ordered,
+    /// A tiled blue noise threshold map, as fast as ordered dithering
+    /// but the noise is a lot less noticeable to our eyes.
+    #[default]
+    BlueNoise,
+    /// Error diffusion, spreads each pixel's rounding error onto its neighbours.
+    FloydSteinberg,
+}
+
+impl Display for Dithering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dithering::None => write!(f, "None"),
+            Dithering::Ordered => write!(f, "Ordered"),
+            Dithering::BlueNoise => write!(f, "Blue Noise"),
+            Dithering::FloydSteinberg => write!(f, "Floyd–Steinberg"),
+        }
+    }
+}
+
+/// Quantizes u16 or f32 pixels down to u8 pixels, dithering the colour channels.
+///
+/// Values are scaled in the space they are already encoded in (16 bit pngs and tiffs are
+/// already gamma encoded), so the 8 bit levels end up evenly spaced to our eyes. Alpha is
+/// coverage, not colour, so it's only ever rounded, dithering it would make edges fuzzy.
+///
+/// F32 pixels are clamped to 0.0 - 1.0, use `tone_mapping` for HDR images.
+///
 /// # Panics
 /// This function panics if pixels are already u8.
-pub fn squish_pixels_to_u8(pixels: &Pixels) -> Vec<u8> {
-    debug!("Squishing '{pixels}' to u8 pixels...");
+pub fn quantize_pixels_to_u8(
+    pixels: &Pixels,
+    size: ImageSize,
+    colour_type: ImageColourType,
+    dithering: Dithering,
+) -> Vec<u8> {
+    debug!("Quantizing '{pixels}' to u8 pixels with '{dithering}' dithering...");
 
-    match pixels {
+    // every sample scaled to 0.0 - 255.0.
+    let mut samples: Vec<f32> = match pixels {
         Pixels::U8(_) => panic!(
-            "Squished pixels to u8 that were already \
-                u8! Don't squish pixels to u8 that are already u8."
+            "Quantized pixels to u8 that were already \
+                u8! Don't quantize pixels to u8 that are already u8."
         ),
-        Pixels::U16(pixels) => pixels
-            .iter()
-            .map(|&pixel| (pixel >> 8) as u8)
+        Pixels::U16(pixels) => pixels.iter()
+            .map(|&pixel| pixel as f32 * (255.0 / u16::MAX as f32))
             .collect(),
         Pixels::F32(pixels) => pixels.iter()
-            .map(|&pixel| (pixel.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .map(|&pixel| match pixel.is_nan() {
+                true => 0.0,
+                false => pixel.clamp(0.0, 1.0) * 255.0,
+            })
             .collect(),
+    };
+
+    let channel_count = colour_type.channel_count();
+    let row_length = size.0 as usize * channel_count;
+
+    let is_colour_channel = |channel: usize| !(colour_type.has_alpha() && channel == channel_count - 1);
+
+    if row_length == 0 {
+        return Vec::new();
+    }
+
+    match dithering {
+        Dithering::None => samples.iter().map(|&sample| sample.round() as u8).collect(),
+        Dithering::Ordered | Dithering::BlueNoise => {
+            let threshold_map = match dithering {
+                Dithering::Ordered => bayer_threshold_map(),
+                _ => blue_noise_threshold_map(),
+            };
+
+            let mut quantized = vec![0u8; samples.len()];
+
+            quantized.par_chunks_mut(row_length)
+                .zip(samples.par_chunks_mut(row_length))
+                .enumerate()
+                .for_each(|(y, (quantized_row, row))| {
+                    let threshold_row = &threshold_map[(y % THRESHOLD_MAP_SIZE) * THRESHOLD_MAP_SIZE..][..THRESHOLD_MAP_SIZE];
+
+                    for (index, (quantized_sample, &sample)) in quantized_row.iter_mut().zip(row.iter()).enumerate() {
+                        let channel = index % channel_count;
+
+                        *quantized_sample = match is_colour_channel(channel) {
+                            // thresholds are between 0.0 and 1.0 so flooring the
+                            // sample + threshold rounds it up or down on average just right.
+                            true => {
+                                let threshold = threshold_row[(index / channel_count) % THRESHOLD_MAP_SIZE];
+
+                                (sample + threshold).floor().clamp(0.0, 255.0) as u8
+                            },
+                            false => sample.round() as u8,
+                        };
+                    }
+                });
+
+            quantized
+        },
+        Dithering::FloydSteinberg => {
+            let mut quantized = vec![0u8; samples.len()];
+
+            quantized.par_chunks_mut(row_length * ERROR_DIFFUSION_BAND_HEIGHT)
+                .zip(samples.par_chunks_mut(row_length * ERROR_DIFFUSION_BAND_HEIGHT))
+                .for_each(|(quantized_band, band)| {
+                    floyd_steinberg_band(band, quantized_band, row_length, channel_count, is_colour_channel);
+                });
+
+            quantized
+        },
+    }
+}
+
+fn floyd_steinberg_band(
+    band: &mut [f32],
+    quantized_band: &mut [u8],
+    row_length: usize,
+    channel_count: usize,
+    is_colour_channel: impl Fn(usize) -> bool,
+) {
+    let rows = band.len() / row_length;
+
+    for y in 0..rows {
+        for index in 0..row_length {
+            let position = y * row_length + index;
+            let channel = index % channel_count;
+            let sample = band[position];
+
+            let quantized_sample = sample.round().clamp(0.0, 255.0);
+            quantized_band[position] = quantized_sample as u8;
+
+            if !is_colour_channel(channel) {
+                continue;
+            }
+
+            let error = sample - quantized_sample;
+            let has_right = index + channel_count < row_length;
+            let has_left = index >= channel_count;
+            let has_below = y + 1 < rows;
+
+            if has_right {
+                band[position + channel_count] += error * 7.0 / 16.0;
+            }
+
+            if has_below {
+                let below = position + row_length;
+
+                if has_left {
+                    band[below - channel_count] += error * 3.0 / 16.0;
+                }
+
+                band[below] += error * 5.0 / 16.0;
+
+                if has_right {
+                    band[below + channel_count] += error * 1.0 / 16.0;
+                }
+            }
+        }
+    }
+}
+
+/// An 8x8 bayer matrix tiled out to the threshold map size, normalized to 0.0 - 1.0.
+fn bayer_threshold_map() -> &'static [f32] {
+    static BAYER_THRESHOLD_MAP: OnceLock<Vec<f32>> = OnceLock::new();
+
+    BAYER_THRESHOLD_MAP.get_or_init(|| {
+        (0..THRESHOLD_MAP_SIZE * THRESHOLD_MAP_SIZE)
+            .map(|index| {
+                let (x, y) = (index % THRESHOLD_MAP_SIZE % 8, index / THRESHOLD_MAP_SIZE % 8);
+
+                // bit interleave of x ^ y and y, reversed, gives the bayer index.
+                let xor = x ^ y;
+                let mut value = 0;
+
+                for bit in 0..3 {
+                    value |= ((xor >> bit) & 1) << (5 - bit * 2);
+                    value |= ((y >> bit) & 1) << (4 - bit * 2);
+                }
+
+                (value as f32 + 0.5) / 64.0
+            })
+            .collect()
+    })
+}
+
+/// A blue noise threshold map generated with Ulichney's void-and-cluster method.
+/// It's generated once (it takes a moment) and normalized to 0.0 - 1.0.
+fn blue_noise_threshold_map() -> &'static [f32] {
+    static BLUE_NOISE_THRESHOLD_MAP: OnceLock<Vec<f32>> = OnceLock::new();
+
+    BLUE_NOISE_THRESHOLD_MAP.get_or_init(|| {
+        debug!("Generating {0}x{0} blue noise threshold map...", THRESHOLD_MAP_SIZE);
+
+        let ranks = void_and_cluster(THRESHOLD_MAP_SIZE);
+        let length = ranks.len() as f32;
+
+        ranks.into_iter().map(|rank| (rank as f32 + 0.5) / length).collect()
+    })
+}
+
+fn void_and_cluster(size: usize) -> Vec<usize> {
+    const SIGMA: f32 = 1.5;
+
+    let length = size * size;
+
+    // gaussian energy contributed to every offset (wrapping around, so the map tiles).
+    let gaussian: Vec<f32> = (0..length)
+        .map(|index| {
+            let (x, y) = (index % size, index / size);
+            let (dx, dy) = (x.min(size - x) as f32, y.min(size - y) as f32);
+
+            (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp()
+        })
+        .collect();
+
+    let mut energy = vec![0.0f32; length];
+    let mut points = vec![false; length];
+
+    let toggle = |points: &mut Vec<bool>, energy: &mut Vec<f32>, index: usize| {
+        points[index] = !points[index];
+
+        let sign = match points[index] { true => 1.0, false => -1.0 };
+        let (x, y) = (index % size, index / size);
+
+        for (other_index, other_energy) in energy.iter_mut().enumerate() {
+            let (other_x, other_y) = (other_index % size, other_index / size);
+            let offset = ((other_y + size - y) % size) * size + (other_x + size - x) % size;
+
+            *other_energy += sign * gaussian[offset];
+        }
+    };
+
+    let tightest_cluster = |points: &Vec<bool>, energy: &Vec<f32>| {
+        (0..length)
+            .filter(|&index| points[index])
+            .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+
+    let largest_void = |points: &Vec<bool>, energy: &Vec<f32>| {
+        (0..length)
+            .filter(|&index| !points[index])
+            .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+
+    // a fixed seed so every run gets the same map.
+    let mut seed: u32 = 0x9E3779B9;
+    let initial_point_count = length / 10;
+
+    while points.iter().filter(|point| **point).count() < initial_point_count {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+
+        let index = seed as usize % length;
+
+        if !points[index] {
+            toggle(&mut points, &mut energy, index);
+        }
     }
-}
\ No newline at end of file
+
+    // spread the initial points out evenly by moving the tightest cluster into the largest void.
+    loop {
+        let cluster = tightest_cluster(&points, &energy);
+        toggle(&mut points, &mut energy, cluster);
+
+        let void = largest_void(&points, &energy);
+
+        if void == cluster {
+            toggle(&mut points, &mut energy, void);
+            break;
+        }
+
+        toggle(&mut points, &mut energy, void);
+    }
+
+    let mut ranks = vec![0usize; length];
+
+    let initial_points = points.clone();
+    let initial_energy = energy.clone();
+
+    // rank the initial points, removing the tightest cluster first.
+    for rank in (0..initial_point_count).rev() {
+        let cluster = tightest_cluster(&points, &energy);
+        toggle(&mut points, &mut energy, cluster);
+
+        ranks[cluster] = rank;
+    }
+
+    points = initial_points;
+    energy = initial_energy;
+
+    // then rank the rest by filling the largest void.
+    for rank in initial_point_count..length {
+        let void = largest_void(&points, &energy);
+        toggle(&mut points, &mut energy, void);
+
+        ranks[void] = rank;
+    }
+
+    ranks
+}
@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use log::debug;
+use rayon::{iter::ParallelIterator, slice::ParallelSlice};
+
+use crate::colour_type::ImageColourType;
+
+/// Operators that squeeze the unbounded scene-linear values of HDR
+/// images (OpenEXR, Radiance HDR, PFM) down into a displayable 0.0 - 1.0 range.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapper {
+    /// The classic `x / (1 + x)` curve, it never clips but
+    /// highlights end up looking flat and a little grey.
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve, punchy
+    /// contrast with a soft roll off into the highlights.
+    #[default]
+    AcesFilmic,
+    /// John Hable's filmic curve (from Uncharted 2), softer than ACES
+    /// and better at keeping detail in the shadows.
+    Hable,
+}
+
+impl Display for ToneMapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToneMapper::Reinhard => write!(f, "Reinhard"),
+            ToneMapper::AcesFilmic => write!(f, "ACES Filmic"),
+            ToneMapper::Hable => write!(f, "Hable"),
+        }
+    }
+}
+
+impl ToneMapper {
+    /// Tone-maps a single linear channel value to a linear value between 0.0 and 1.0.
+    pub fn tone_map(&self, value: f32) -> f32 {
+        // NaNs and negative values do turn up in EXRs.
+        let value = match value.is_nan() {
+            true => 0.0,
+            false => value.max(0.0),
+        };
+
+        let mapped = match self {
+            ToneMapper::Reinhard => value / (1.0 + value),
+            ToneMapper::AcesFilmic => {
+                // the fit is of the whole RRT + ODT which brightens
+                // the image up quite a bit, 0.6 brings it back to 1:1.
+                let value = value * 0.6;
+
+                (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+            },
+            ToneMapper::Hable => {
+                const EXPOSURE_BIAS: f32 = 2.0;
+                const LINEAR_WHITE: f32 = 11.2;
+
+                hable_partial(value * EXPOSURE_BIAS) / hable_partial(LINEAR_WHITE)
+            },
+        };
+
+        mapped.clamp(0.0, 1.0)
+    }
+}
+
+fn hable_partial(value: f32) -> f32 {
+    const SHOULDER_STRENGTH: f32 = 0.15;
+    const LINEAR_STRENGTH: f32 = 0.50;
+    const LINEAR_ANGLE: f32 = 0.10;
+    const TOE_STRENGTH: f32 = 0.20;
+    const TOE_NUMERATOR: f32 = 0.02;
+    const TOE_DENOMINATOR: f32 = 0.30;
+
+    ((value * (SHOULDER_STRENGTH * value + LINEAR_ANGLE * LINEAR_STRENGTH) + TOE_STRENGTH * TOE_NUMERATOR)
+        / (value * (SHOULDER_STRENGTH * value + LINEAR_STRENGTH) + TOE_STRENGTH * TOE_DENOMINATOR))
+        - TOE_NUMERATOR / TOE_DENOMINATOR
+}
+
+/// Encodes a linear value with the sRGB transfer function.
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    match value <= 0.0031308 {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+    }
+}
+
+/// Tone-maps scene-linear f32 pixels into sRGB encoded u8 pixels ready for displaying.
+///
+/// Alpha is not tone-mapped, it's only clamped.
+pub fn tone_map_pixels_to_u8(pixels: &[f32], colour_type: ImageColourType, tone_mapper: ToneMapper) -> Vec<u8> {
+    debug!("Tone-mapping f32 pixels to u8 pixels with the '{tone_mapper}' operator...");
+
+    let channel_count = colour_type.channel_count();
+    let alpha_index = match colour_type.has_alpha() {
+        true => Some(channel_count - 1),
+        false => None,
+    };
+
+    pixels.par_chunks(channel_count)
+        .flat_map_iter(|pixel| {
+            pixel.iter().enumerate().map(move |(index, &value)| {
+                let value = match Some(index) == alpha_index {
+                    true => value.clamp(0.0, 1.0),
+                    false => linear_to_srgb(tone_mapper.tone_map(value)),
+                };
+
+                (value * 255.0).round() as u8
+            })
+        })
+        .collect()
+}
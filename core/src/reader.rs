@@ -1,23 +1,100 @@
-use std::io::{Cursor};
+use std::{fs::File, io::{Cursor, Read}, ops::Deref, sync::Arc};
 
-use crate::{format::ImageFormat, decoded_image::DecodedImage};
+use memmap2::Mmap;
 
-pub type EncodedImageReader = Cursor<Vec<u8>>;
+use crate::{format::ImageFormat, decoded_image::DecodedImage, limits::DecodeLimits};
+
+pub type EncodedImageReader = Cursor<EncodedImageBytes>;
+
+/// The bytes of an encoded image. Files are memory-mapped so decoders read straight from the 
+/// page cache rather than us holding a copy of the whole file on the heap. Cloning only clones 
+/// the `Arc` so the same bytes can be handed to as many decoders as we like.
+#[derive(Clone)]
+pub enum EncodedImageBytes {
+    Buffer(Arc<Vec<u8>>),
+    Mapped(Arc<Mmap>),
+}
+
+impl EncodedImageBytes {
+    /// Memory-maps the file, falling back to reading it into memory if it can't be 
+    /// mapped (e.g. it's a pipe or lives on a filesystem that doesn't support it).
+    pub fn from_file(mut file: File) -> std::io::Result<Self> {
+        // SAFETY: the map is read-only, if the file gets truncated while we have it mapped 
+        // reading past the new end will fault but that's the same trade-off every image viewer 
+        // that maps files makes and nothing else in roseate writes to the images it opens.
+        match unsafe { Mmap::map(&file) } {
+            Ok(mmap) => Ok(Self::Mapped(Arc::new(mmap))),
+            Err(error) => {
+                log::debug!("Failed to memory-map image file, reading it into memory instead... Error: {}", error);
+
+                Self::from_reader(&mut file)
+            }
+        }
+    }
+
+    /// Reads any other source of an encoded image into memory.
+    pub fn from_reader<R: Read>(mut reader: R) -> std::io::Result<Self> {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+
+        Ok(Self::Buffer(Arc::new(buffer)))
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Buffer(buffer) => buffer.as_slice(),
+            Self::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+impl Deref for EncodedImageBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for EncodedImageBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for EncodedImageBytes {
+    fn from(buffer: Vec<u8>) -> Self {
+        Self::Buffer(Arc::new(buffer))
+    }
+}
 
 pub enum ImageReaderData {
     EncodedImage(EncodedImageReader),
-    DecodedImage(DecodedImage),
+    // decoded images are a lot larger than the encoded image reader so we keep them on the heap.
+    DecodedImage(Box<DecodedImage>),
 }
 
 impl From<EncodedImageReader> for ImageReaderData {
-    fn from(cursor: Cursor<Vec<u8>>) -> Self {
+    fn from(cursor: EncodedImageReader) -> Self {
         Self::EncodedImage(cursor)
     }
 }
 
+impl From<Cursor<Vec<u8>>> for ImageReaderData {
+    fn from(cursor: Cursor<Vec<u8>>) -> Self {
+        Self::from(EncodedImageBytes::from(cursor.into_inner()))
+    }
+}
+
+impl From<EncodedImageBytes> for ImageReaderData {
+    fn from(encoded_image_bytes: EncodedImageBytes) -> Self {
+        Self::EncodedImage(Cursor::new(encoded_image_bytes))
+    }
+}
+
 impl From<DecodedImage> for ImageReaderData {
     fn from(value: DecodedImage) -> Self {
-        Self::DecodedImage(value)
+        Self::DecodedImage(Box::new(value))
     }
 }
 
@@ -25,13 +102,47 @@ pub struct ImageReader {
     // NOTE: this may become private in the future.
     pub(crate) data: ImageReaderData,
     pub image_format: ImageFormat,
+    /// Every backend is handed these limits to stay within while decoding.
+    pub limits: DecodeLimits,
+    /// Whether the backends rotate and flip the image the right way up with it's exif orientation. 
+    /// Already decoded images were oriented the first time round so this does nothing for them.
+    pub auto_orientation: bool,
 }
 
 impl ImageReader {
     pub fn new<T: Into<ImageReaderData>>(data: T, image_format: ImageFormat) -> Self {
         Self {
             data: data.into(),
-            image_format
+            image_format,
+            limits: DecodeLimits::default(),
+            auto_orientation: true,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_auto_orientation(mut self, auto_orientation: bool) -> Self {
+        self.auto_orientation = auto_orientation;
+        self
+    }
+
+    /// Copies the reader so the image can be handed to another backend if the first one fails. 
+    /// Only encoded images can be copied (which is cheap as the bytes are shared), an 
+    /// already decoded image is far too big to duplicate.
+    pub fn try_clone(&self) -> Option<Self> {
+        match &self.data {
+            ImageReaderData::EncodedImage(cursor) => Some(
+                Self {
+                    data: ImageReaderData::EncodedImage(Cursor::new(cursor.get_ref().clone())),
+                    image_format: self.image_format.clone(),
+                    limits: self.limits,
+                    auto_orientation: self.auto_orientation,
+                }
+            ),
+            ImageReaderData::DecodedImage(_) => None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+use std::io::Cursor;
+
+use crate::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend, rawloader::tiff::Tiff}, decoded_image::DecodedImage, format::ImageFormat, reader::{EncodedImageBytes, ImageReader}};
+
+/// APP1 segments holding exif start with this, the tiff structure follows straight after.
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// Decodes the thumbnail cameras embed into jpegs (in the exif's second ifd) and camera raws, 
+/// without touching the image itself. They're tiny so this is near instant even for huge photos, 
+/// which makes them handy as a placeholder to show while the real image is decoded.
+/// 
+/// Returns `None` if the image has no embedded thumbnail or it couldn't be decoded.
+pub fn decode_embedded_thumbnail(buffer: &[u8], image_format: &ImageFormat) -> Option<DecodedImage> {
+    let tiff_buffer = match image_format {
+        ImageFormat::Jpeg => find_jpeg_exif_chunk(buffer)?,
+        ImageFormat::Tiff | ImageFormat::Dng | ImageFormat::Cr2 | ImageFormat::Nef | ImageFormat::Arw => buffer,
+        _ => return None,
+    };
+
+    let (thumbnail_range, (thumbnail_width, thumbnail_height)) = Tiff::parse(tiff_buffer)?.smallest_jpeg_preview()?;
+
+    log::debug!(
+        "Decoding embedded {}x{} jpeg thumbnail with image-rs...",
+        thumbnail_width,
+        thumbnail_height
+    );
+
+    let thumbnail_reader = ImageReader::new(
+        Cursor::new(EncodedImageBytes::from(tiff_buffer[thumbnail_range].to_vec())),
+        ImageFormat::Jpeg
+    );
+
+    let decoded_thumbnail = ImageRSBackend::from_reader(thumbnail_reader)
+        .and_then(|backend| backend.decode());
+
+    match decoded_thumbnail {
+        Ok(mut decoded_thumbnail) => {
+            decoded_thumbnail.info.format = image_format.clone();
+
+            Some(decoded_thumbnail)
+        },
+        Err(error) => {
+            log::warn!("Failed to decode embedded thumbnail! Error: {:?}", error);
+
+            None
+        },
+    }
+}
+
+/// Walks the jpeg's markers up until the start of scan looking for the exif APP1 segment.
+fn find_jpeg_exif_chunk(buffer: &[u8]) -> Option<&[u8]> {
+    let mut offset = 2;
+
+    while let Some([0xFF, marker]) = buffer.get(offset..offset + 2) {
+        match marker {
+            // start of scan, the exif would've come before this.
+            0xDA => return None,
+            // padding
+            0xFF => {
+                offset += 1;
+                continue;
+            },
+            _ => {},
+        }
+
+        let segment_length = match buffer.get(offset + 2..offset + 4) {
+            Some(length) => u16::from_be_bytes([length[0], length[1]]) as usize,
+            None => return None,
+        };
+
+        let segment = buffer.get(offset + 4..offset + 2 + segment_length)?;
+
+        if *marker == 0xE1 && segment.starts_with(EXIF_HEADER) {
+            return Some(&segment[EXIF_HEADER.len()..]);
+        }
+
+        offset += 2 + segment_length;
+    }
+
+    None
+}
@@ -7,6 +7,10 @@ use roseate_core::{
 };
 
 mod test_image_rs_backend;
+mod test_zune_image_backend;
+mod test_resvg_backend;
+mod test_rawloader_backend;
+mod test_psd_backend;
 
 pub const IMAGE_DUMP_PATH: &str = "./tests-image-dump";
 
@@ -34,6 +38,7 @@ pub fn save_image<P: image::Pixel<Subpixel = u8> + image::PixelWithColorType>(de
                     match decoded_image.info.format {
                         ImageFormat::Png => "png",
                         ImageFormat::Jpeg => "jpeg",
+                        ImageFormat::JpegXl => "jxl",
                         ImageFormat::Svg => "svg",
                         ImageFormat::Gif => "gif",
                         ImageFormat::Webp => "webp",
@@ -42,6 +47,20 @@ pub fn save_image<P: image::Pixel<Subpixel = u8> + image::PixelWithColorType>(de
                         ImageFormat::Qoi => "qoi",
                         ImageFormat::Bmp => "bmp",
                         ImageFormat::Ico => "ico",
+                        ImageFormat::Exr => "exr",
+                        ImageFormat::Hdr => "hdr",
+                        ImageFormat::Dng => "dng",
+                        ImageFormat::Cr2 => "cr2",
+                        ImageFormat::Nef => "nef",
+                        ImageFormat::Arw => "arw",
+                        ImageFormat::Pnm => "pnm",
+                        ImageFormat::Pfm => "pfm",
+                        ImageFormat::Tga => "tga",
+                        ImageFormat::Dds => "dds",
+                        ImageFormat::Farbfeld => "ff",
+                        ImageFormat::OpenRaster => "ora",
+                        ImageFormat::Psd => "psd",
+                        ImageFormat::Psb => "psb",
                     },
                 ));
 
@@ -54,5 +73,24 @@ pub fn save_image<P: image::Pixel<Subpixel = u8> + image::PixelWithColorType>(de
                     .unwrap();
             }
         }
+        DecodedImageContent::Pages(pages) => {
+            let (file_name, extension) = name.split_once(".").unwrap_or((name, "png"));
+
+            let image_path = Path::new(IMAGE_DUMP_PATH).join(file_name);
+
+            let _ = fs::create_dir(&image_path);
+
+            for (index, page) in pages.into_iter().enumerate() {
+                let page_image: ImageBuffer<P, _> = ImageBuffer::from_raw(
+                    page.size.0, page.size.1, page.pixels
+                ).unwrap();
+
+                page_image
+                    .save(image_path.join(format!("page_{}.{}", index + 1, extension)))
+                    .unwrap();
+            }
+        }
+        // streams never end so there's no saving every frame of them.
+        DecodedImageContent::Streamed(_) => unimplemented!("Streamed animations can't be saved!"),
     }
 }
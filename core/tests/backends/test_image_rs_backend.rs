@@ -1,7 +1,7 @@
 use std::io::Cursor;
 
 use image::{Rgb, Rgba};
-use roseate_core::{self, backends::{backend::DecodeBackend, image_rs::ImageRSBackend}, error::Result, format::ImageFormat, colour_type::ImageColourType, modifications::ImageModification, reader::ImageReader};
+use roseate_core::{self, animation::AnimationStreaming, backends::{backend::{DecodeBackend, MultiPageDecodeBackend, ScaledDecodeBackend}, image_rs::ImageRSBackend}, error::{Error, Result}, format::ImageFormat, colour_type::ImageColourType, limits::DecodeLimits, image_info::metadata::ImageMetadata, decoded_image::{DecodedImage, DecodedImageContent, PageInfo}, modifications::{ImageModification, ResizeFilter}, pixels::Pixels, reader::ImageReader};
 
 use crate::backends::{save_image};
 
@@ -54,7 +54,7 @@ fn test_png_modify_and_decode_1() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Png);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(1280, 720)]);
+    backend.modify(vec![ImageModification::Resize(1280, 720, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -74,7 +74,7 @@ fn test_png_modify_and_decode_2() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Png);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(540, 270)]);
+    backend.modify(vec![ImageModification::Resize(540, 270, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -103,7 +103,7 @@ fn test_png_modify_already_decoded_image_1() -> Result<()> {
         ImageReader::new(decoded_image, ImageFormat::Png)
     )?;
 
-    backend.modify(vec![ImageModification::Resize(500, 500)]);
+    backend.modify(vec![ImageModification::Resize(500, 500, ResizeFilter::Lanczos3)]);
     let decoded_image = backend.decode()?;
 
     assert_eq!(decoded_image.size, (500, 500));
@@ -131,7 +131,7 @@ fn test_png_modify_already_decoded_image_2() -> Result<()> {
         ImageReader::new(decoded_image, ImageFormat::Png)
     )?;
 
-    backend.modify(vec![ImageModification::Resize(250, 250)]);
+    backend.modify(vec![ImageModification::Resize(250, 250, ResizeFilter::Lanczos3)]);
     let decoded_image = backend.decode()?;
 
     assert_eq!(decoded_image.size, (250, 250));
@@ -142,6 +142,175 @@ fn test_png_modify_already_decoded_image_2() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_interlaced_png_progressive_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../interlaced_gradient.png");
+
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png)
+    )?;
+
+    let mut snapshots = Vec::new();
+
+    let decoded_image = backend.decode_progressively(
+        |snapshot| snapshots.push(snapshot)
+    )?;
+
+    // a snapshot for every adam7 pass except the last.
+    assert_eq!(snapshots.len(), 6);
+    assert!(snapshots.iter().all(|snapshot| snapshot.size == (40, 30)));
+
+    let expected_image = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png)
+    )?.decode()?;
+
+    assert_eq!(decoded_image.size, (40, 30));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+
+    match (&decoded_image.content, &expected_image.content) {
+        (
+            DecodedImageContent::Static(Pixels::U8(pixels)),
+            DecodedImageContent::Static(Pixels::U8(expected_pixels))
+        ) => assert_eq!(pixels, expected_pixels),
+        _ => panic!("Interlaced png should decode to a static image!"),
+    }
+
+    save_image::<Rgb<u8>>(decoded_image, "interlaced_gradient.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_progressive_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let mut backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png)
+    )?;
+
+    backend.modify(vec![ImageModification::Resize(375, 125, ResizeFilter::Lanczos3)]);
+
+    let mut snapshot_sizes = Vec::new();
+
+    let decoded_image = backend.decode_progressively(
+        |snapshot| snapshot_sizes.push(snapshot.size)
+    )?;
+
+    assert_eq!(snapshot_sizes, vec![(375, 125); 8]);
+
+    assert_eq!(decoded_image.size, (375, 125));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "progressive_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_animated_png_progressive_decode_falls_back() -> Result<()> {
+    let image_bytes = include_bytes!("../animated_png.png");
+
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png)
+    )?;
+
+    let mut snapshot_count = 0;
+
+    let decoded_image = backend.decode_progressively(|_| snapshot_count += 1)?;
+
+    assert_eq!(snapshot_count, 0);
+    assert!(matches!(decoded_image.content, DecodedImageContent::Animated(_)));
+
+    Ok(())
+}
+
+#[test]
+fn test_progressive_jpeg_progressive_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../progressive.jpg");
+
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+
+    let mut snapshots = Vec::new();
+
+    let decoded_image = backend.decode_progressively(
+        |snapshot| snapshots.push(snapshot)
+    )?;
+
+    // a snapshot for every scan except the last.
+    assert_eq!(snapshots.len(), 11);
+    assert!(snapshots.iter().all(|snapshot| snapshot.size == (275, 307)));
+
+    let expected_image = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?.decode()?;
+
+    let pixels_of = |decoded_image: &DecodedImage| match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels.clone(),
+        _ => panic!("Jpeg should decode to a static 8 bit image!"),
+    };
+
+    let expected_pixels = pixels_of(&expected_image);
+
+    assert_eq!(pixels_of(&decoded_image), expected_pixels);
+
+    // each scan should bring the snapshot closer to the final image.
+    let difference_of = |snapshot: &DecodedImage| pixels_of(snapshot).iter()
+        .zip(&expected_pixels)
+        .map(|(&sample, &expected_sample)| sample.abs_diff(expected_sample) as u64)
+        .sum::<u64>();
+
+    let first_difference = difference_of(&snapshots[0]);
+    let last_difference = difference_of(&snapshots[snapshots.len() - 1]);
+
+    assert!(last_difference < first_difference);
+
+    save_image::<Rgb<u8>>(snapshots.swap_remove(0), "progressive_jpeg_first_scan.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_progressive_jpeg_progressive_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../progressive.jpg");
+
+    let mut backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+
+    backend.modify(vec![ImageModification::Resize(60, 67, ResizeFilter::Lanczos3)]);
+
+    let mut snapshot_sizes = Vec::new();
+
+    let decoded_image = backend.decode_progressively(
+        |snapshot| snapshot_sizes.push(snapshot.size)
+    )?;
+
+    assert_eq!(snapshot_sizes, vec![(60, 67); 11]);
+    assert_eq!(decoded_image.size, (60, 67));
+
+    Ok(())
+}
+
+#[test]
+fn test_baseline_jpeg_progressive_decode_has_no_snapshots() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+
+    let mut snapshot_count = 0;
+
+    let decoded_image = backend.decode_progressively(|_| snapshot_count += 1)?;
+
+    assert_eq!(snapshot_count, 0);
+    assert_eq!(decoded_image.size, (275, 307));
+
+    Ok(())
+}
+
 #[test]
 fn test_animated_png_decode_and_modify() -> Result<()> {
     let image_bytes = include_bytes!("../animated_png.png");
@@ -150,7 +319,7 @@ fn test_animated_png_decode_and_modify() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Png);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(50, 50)]);
+    backend.modify(vec![ImageModification::Resize(50, 50, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -162,6 +331,160 @@ fn test_animated_png_decode_and_modify() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_png_rotate_flip_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let decode_with = |modifications: Vec<ImageModification>| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        backend.modify(modifications);
+        backend.decode()
+    };
+
+    let upright_image = decode_with(vec![])?;
+    let rotated_image = decode_with(vec![ImageModification::Rotate90])?;
+    let flipped_image = decode_with(vec![ImageModification::FlipHorizontal, ImageModification::Rotate180])?;
+
+    assert_eq!(rotated_image.size, (250, 750));
+    assert_eq!(flipped_image.size, (750, 250));
+
+    let rgba_at = |decoded_image: &DecodedImage, x: u32, y: u32| match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => {
+            let index = ((y * decoded_image.size.0 + x) * 4) as usize;
+            pixels[index..index + 4].to_vec()
+        },
+        _ => panic!("Expected a static 8 bit image!"),
+    };
+
+    // rotated clockwise, the bottom left corner ends up top left.
+    assert_eq!(rgba_at(&rotated_image, 0, 0), rgba_at(&upright_image, 0, 249));
+    assert_eq!(rgba_at(&rotated_image, 10, 300), rgba_at(&upright_image, 300, 239));
+    // and flipped left to right then turned upside down is the same as flipped top to bottom.
+    assert_eq!(rgba_at(&flipped_image, 300, 10), rgba_at(&upright_image, 300, 239));
+
+    // the resize comes after the rotation so it's relative to the rotated image.
+    let resized_image = decode_with(vec![ImageModification::Rotate270, ImageModification::Resize(125, 375, ResizeFilter::Lanczos3)])?;
+
+    assert_eq!(resized_image.size, (125, 375));
+
+    save_image::<Rgba<u8>>(resized_image, "rotated_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_crop_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let decode_with = |modifications: Vec<ImageModification>| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        backend.modify(modifications);
+        backend.decode()
+    };
+
+    let crop = ImageModification::Crop { x: 100, y: 50, width: 200, height: 100 };
+
+    let uncropped_image = decode_with(vec![])?;
+    let cropped_image = decode_with(vec![crop.clone()])?;
+
+    assert_eq!(cropped_image.size, (200, 100));
+
+    let rgba_at = |decoded_image: &DecodedImage, x: u32, y: u32| match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => {
+            let index = ((y * decoded_image.size.0 + x) * 4) as usize;
+            pixels[index..index + 4].to_vec()
+        },
+        _ => panic!("Expected a static 8 bit image!"),
+    };
+
+    assert_eq!(rgba_at(&cropped_image, 0, 0), rgba_at(&uncropped_image, 100, 50));
+    assert_eq!(rgba_at(&cropped_image, 199, 99), rgba_at(&uncropped_image, 299, 149));
+
+    // the resize is relative to the cropped and rotated image.
+    let resized_image = decode_with(vec![crop, ImageModification::Rotate90, ImageModification::Resize(50, 100, ResizeFilter::Lanczos3)])?;
+
+    assert_eq!(resized_image.size, (50, 100));
+
+    // cropping then rotating isn't the same as rotating then cropping.
+    let crop = ImageModification::Crop { x: 25, y: 50, width: 200, height: 100 };
+
+    let cropped_then_rotated_image = decode_with(vec![crop.clone(), ImageModification::Rotate90])?;
+    let rotated_then_cropped_image = decode_with(vec![ImageModification::Rotate90, crop])?;
+
+    assert_eq!(cropped_then_rotated_image.size, (100, 200));
+    assert_eq!(rotated_then_cropped_image.size, (200, 100));
+    // rotated clockwise, the crop's bottom left corner ends up top left.
+    assert_eq!(rgba_at(&cropped_then_rotated_image, 0, 0), rgba_at(&uncropped_image, 25, 149));
+    // (25, 50) of the rotated image is (50, 224) of the image the way it's stored.
+    assert_eq!(rgba_at(&rotated_then_cropped_image, 0, 0), rgba_at(&uncropped_image, 50, 224));
+
+    // crops hanging off the edge of the image are cut short.
+    let clamped_image = decode_with(vec![ImageModification::Crop { x: 700, y: 200, width: 100, height: 100 }])?;
+
+    assert_eq!(clamped_image.size, (50, 50));
+
+    save_image::<Rgba<u8>>(cropped_image, "cropped_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_animated_png_rotate_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../animated_png.png");
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+    let (width, height) = ImageRSBackend::from_reader(image_reader)?.decode()?.size;
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Rotate90, ImageModification::FlipVertical]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (height, width));
+
+    match decoded_image.content {
+        DecodedImageContent::Animated(frames) => {
+            for (pixels, _) in frames {
+                assert_eq!(pixels.len(), (width * height * 4) as usize);
+            }
+        },
+        _ => panic!("Expected an animated image!"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_animated_png_crop_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../animated_png.png");
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Crop { x: 10, y: 10, width: 40, height: 30 }]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (40, 30));
+
+    match decoded_image.content {
+        DecodedImageContent::Animated(frames) => {
+            for (pixels, _) in frames {
+                assert_eq!(pixels.len(), 40 * 30 * 4);
+            }
+        },
+        _ => panic!("Expected an animated image!"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_gif_decode_and_modify() -> Result<()> {
     let image_bytes = include_bytes!("../sailor_moon.gif");
@@ -170,7 +493,7 @@ fn test_gif_decode_and_modify() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Gif);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(300, 300)]);
+    backend.modify(vec![ImageModification::Resize(300, 300, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -182,6 +505,84 @@ fn test_gif_decode_and_modify() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_gif_streamed_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../sailor_moon.gif");
+
+    let decode_with = |max_cached_bytes: u64| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Gif);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        backend.stream_animations(Some(AnimationStreaming { max_cached_bytes, frames_ahead: 2 }));
+        backend.modify(vec![ImageModification::Resize(300, 300, ResizeFilter::Nearest)]);
+
+        backend.decode()
+    };
+
+    // small enough to be held in memory.
+    let frames = match decode_with(u64::MAX)?.content {
+        DecodedImageContent::Animated(frames) => frames,
+        _ => panic!("Expected every frame of the animation to be decoded!"),
+    };
+
+    let decoded_image = decode_with(0)?;
+
+    assert_eq!(decoded_image.size, (300, 300));
+
+    let animation_stream = match decoded_image.content {
+        DecodedImageContent::Streamed(animation_stream) => animation_stream,
+        _ => panic!("Expected the animation to be streamed!"),
+    };
+
+    // the stream loops back round to the first frame once it's played through.
+    for (frame_pixels, delay) in frames.iter().chain(frames.iter().take(1)) {
+        let (streamed_pixels, streamed_delay) = animation_stream.next_frame()
+            .expect("The animation stream ended early!")?;
+
+        assert_eq!(&*streamed_pixels, &**frame_pixels);
+        assert_eq!(streamed_delay, *delay);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_resize_filters() -> Result<()> {
+    // a 2x2 black and white checkerboard, like the pixel art nearest neighbour is for.
+    let checkerboard = || DecodedImage::new(
+        (2, 2),
+        ImageFormat::Png,
+        ImageColourType::Grey8,
+        ImageMetadata::default(),
+        DecodedImageContent::Static(Pixels::U8(vec![0, 255, 255, 0])),
+    );
+
+    let resize_with = |filter: ResizeFilter| -> Result<Vec<u8>> {
+        let mut backend = ImageRSBackend::from_reader(ImageReader::new(checkerboard(), ImageFormat::Png))?;
+        backend.modify(vec![ImageModification::Resize(8, 8, filter)]);
+
+        match backend.decode()?.content {
+            DecodedImageContent::Static(Pixels::U8(pixels)) => Ok(pixels),
+            _ => panic!("Expected a static 8 bit image!"),
+        }
+    };
+
+    let nearest_pixels = resize_with(ResizeFilter::Nearest)?;
+
+    assert_eq!(nearest_pixels.len(), 64);
+    assert!(nearest_pixels.iter().all(|&sample| sample == 0 || sample == 255));
+    assert_eq!(&nearest_pixels[..8], &[0, 0, 0, 0, 255, 255, 255, 255]);
+
+    // every other filter blends the squares into each other.
+    for filter in [ResizeFilter::Bilinear, ResizeFilter::CatmullRom, ResizeFilter::Gaussian, ResizeFilter::Lanczos3] {
+        let pixels = resize_with(filter)?;
+
+        assert!(pixels.iter().any(|&sample| sample != 0 && sample != 255), "{:?} didn't blend", filter);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_modifying_vertical_png() -> Result<()> {
     let image_bytes = include_bytes!("../example.png");
@@ -190,7 +591,7 @@ fn test_modifying_vertical_png() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Png);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(138, 154)]);
+    backend.modify(vec![ImageModification::Resize(138, 154, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -202,6 +603,66 @@ fn test_modifying_vertical_png() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_jpeg_scaled_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Jpeg);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+    assert_eq!(backend.decode_scales(), &[1, 2, 4, 8]);
+
+    // 275x307 gets decoded at 1/4 scale (69x77) then resized the rest of the way.
+    backend.modify(vec![ImageModification::Resize(60, 67, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (60, 67));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+
+    save_image::<Rgb<u8>>(decoded_image, "scaled_example.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_decode_scale() {
+    // 275x307 down to what the monitor downsampling would ask for on a small screen.
+    assert_eq!(ImageRSBackend::decode_scale(&ImageFormat::Jpeg, (275, 307), (60, 67)), 4);
+    assert_eq!(ImageRSBackend::decode_scale(&ImageFormat::Jpeg, (275, 307), (200, 223)), 1);
+    assert_eq!(ImageRSBackend::decode_scale(&ImageFormat::Png, (275, 307), (60, 67)), 1);
+}
+
+#[test]
+fn test_jpeg_scaled_decode_resize_filter() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let decode_with = |resize_filter: ResizeFilter| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        // decoded at 1/4 scale then resized the rest of the way with the filter we ask for.
+        backend.modify(vec![ImageModification::Resize(60, 67, resize_filter)]);
+        backend.decode()
+    };
+
+    let nearest_image = decode_with(ResizeFilter::Nearest)?;
+    let bilinear_image = decode_with(ResizeFilter::Bilinear)?;
+
+    assert_eq!(nearest_image.size, bilinear_image.size);
+
+    match (nearest_image.content, bilinear_image.content) {
+        (
+            DecodedImageContent::Static(Pixels::U8(nearest_pixels)),
+            DecodedImageContent::Static(Pixels::U8(bilinear_pixels))
+        ) => assert_ne!(nearest_pixels, bilinear_pixels),
+        _ => panic!("Expected static 8 bit images!"),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_tiff_decode_and_modify() -> Result<()> {
     let image_bytes = include_bytes!("../terror_in_resonace_small_backdrop.tiff");
@@ -210,7 +671,7 @@ fn test_tiff_decode_and_modify() -> Result<()> {
     let image_reader = ImageReader::new(cursor, ImageFormat::Tiff);
 
     let mut backend = ImageRSBackend::from_reader(image_reader)?;
-    backend.modify(vec![ImageModification::Resize(200, 190)]);
+    backend.modify(vec![ImageModification::Resize(200, 190, ResizeFilter::Lanczos3)]);
 
     let decoded_image = backend.decode()?;
 
@@ -221,4 +682,392 @@ fn test_tiff_decode_and_modify() -> Result<()> {
     save_image::<Rgba<u8>>(decoded_image, "resized_terror_in_resonace_small_backdrop.tiff");
 
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_exr_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../gradient.exr");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Exr);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(32, 16, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (32, 16));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba32F);
+
+    Ok(())
+}
+
+#[test]
+fn test_hdr_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../gradient.hdr");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Hdr);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (64, 32));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb32F);
+
+    Ok(())
+}
+
+#[test]
+fn test_pnm_tga_and_farbfeld_decode() -> Result<()> {
+    let images: [(&[u8], ImageFormat, ImageColourType); 4] = [
+        (include_bytes!("../gradient.ppm"), ImageFormat::Pnm, ImageColourType::Rgb8),
+        (include_bytes!("../gradient.pam"), ImageFormat::Pnm, ImageColourType::Rgba8),
+        (include_bytes!("../gradient.tga"), ImageFormat::Tga, ImageColourType::Rgba8),
+        (include_bytes!("../gradient.ff"), ImageFormat::Farbfeld, ImageColourType::Rgba16),
+    ];
+
+    for (image_bytes, image_format, colour_type) in images {
+        let cursor = Cursor::new(image_bytes.to_vec());
+        let image_reader = ImageReader::new(cursor, image_format);
+
+        let backend = ImageRSBackend::from_reader(image_reader)?;
+        let decoded_image = backend.decode()?;
+
+        assert_eq!(decoded_image.size, (64, 32));
+        assert_eq!(decoded_image.colour_type, colour_type);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_pfm_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../gradient.pfm");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Pfm);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (64, 32));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb32F);
+
+    let pixels = match decoded_image.content {
+        DecodedImageContent::Static(Pixels::F32(pixels)) => pixels,
+        _ => panic!("PFM should decode to static f32 pixels!"),
+    };
+
+    // pfm rows are stored bottom to top, so the first row in the file is the last row.
+    assert_eq!(&pixels[0..3], &[0.0, 0.0, 0.5]);
+    assert_eq!(&pixels[(31 * 64 + 63) * 3..][..3], &[4.0, 1.0, 0.5]);
+
+    Ok(())
+}
+
+#[test]
+fn test_dds_bc1_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../texture_bc1.dds");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Dds);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (8, 8));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    let pixels = match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels.clone(),
+        _ => panic!("DDS should decode to static u8 pixels!"),
+    };
+
+    let pixel_at = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..][..4];
+
+    assert_eq!(pixel_at(0, 0), &[255, 0, 0, 255]);
+    assert_eq!(pixel_at(7, 3), &[0, 0, 255, 255]);
+    assert_eq!(pixel_at(0, 4), &[170, 0, 85, 255]);
+    // the last block is in the three colour mode with transparent black.
+    assert_eq!(pixel_at(7, 7), &[0, 0, 0, 0]);
+
+    save_image::<Rgba<u8>>(decoded_image, "texture_bc1.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_dds_bc7_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../texture_bc7.dds");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Dds);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (8, 4));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    let pixels = match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels,
+        _ => panic!("DDS should decode to static u8 pixels!"),
+    };
+
+    let pixel_at = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..][..4];
+
+    // first block is a mode 6 block of a single colour.
+    assert_eq!(pixel_at(0, 0), &[201, 41, 121, 255]);
+    assert_eq!(pixel_at(3, 3), &[201, 41, 121, 255]);
+
+    // second block is a mode 1 block split in half by partition 13, the
+    // shared p bits are set so the zeroed end point channels become 2.
+    assert_eq!(pixel_at(4, 0), &[255, 2, 2, 255]);
+    assert_eq!(pixel_at(7, 1), &[255, 2, 2, 255]);
+    assert_eq!(pixel_at(4, 2), &[255, 255, 255, 255]);
+    assert_eq!(pixel_at(5, 2), &[2, 2, 255, 255]);
+    assert_eq!(pixel_at(7, 3), &[109, 109, 255, 255]);
+
+    Ok(())
+}
+
+#[test]
+fn test_open_raster_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../example.ora");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::OpenRaster);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (275, 307));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+
+    save_image::<Rgb<u8>>(decoded_image, "example_ora.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_multi_page_tiff_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../scanned_pages.tiff");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Tiff);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+
+    // the reduced resolution thumbnail after the first page is not a page.
+    assert_eq!(
+        backend.pages(),
+        vec![
+            PageInfo { size: (24, 16), colour_type: ImageColourType::Rgb8 },
+            PageInfo { size: (16, 24), colour_type: ImageColourType::Grey8 },
+            PageInfo { size: (8, 8), colour_type: ImageColourType::Rgba8 },
+        ]
+    );
+
+    backend.modify(vec![ImageModification::Resize(12, 8, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (12, 8));
+
+    match decoded_image.content {
+        DecodedImageContent::Pages(pages) => {
+            let page_sizes: Vec<_> = pages.iter().map(|page| page.size).collect();
+
+            // every page is scaled down by the same amount as the first page.
+            assert_eq!(page_sizes, vec![(12, 8), (8, 12), (4, 4)]);
+            assert_eq!(pages[1].colour_type, ImageColourType::Grey8);
+        },
+        _ => panic!("Multi-page tiff should have been decoded into pages!"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_multi_page_tiff_select_page() -> Result<()> {
+    let image_bytes = include_bytes!("../scanned_pages.tiff");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Tiff);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+
+    assert!(matches!(
+        backend.select_page(3),
+        Err(Error::PageOutOfRange { index: 3, page_count: 3 })
+    ));
+
+    backend.select_page(1)?;
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (16, 24));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Grey8);
+
+    match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => assert_eq!(&pixels[0..4], &[0, 16, 32, 48]),
+        _ => panic!("Selected page should have been decoded to static u8 pixels!"),
+    }
+
+    Ok(())
+}
+
+/// A little endian tiff of 8 bit greyscale pages, each `(width, height, is_broken)`. Broken pages claim 
+/// to be RGB while only having the one sample per pixel so no tiff decoder will take them.
+fn tiff_with_pages(pages: &[(u32, u32, bool)]) -> Vec<u8> {
+    let mut tiff = vec![b'I', b'I', 42, 0, 8, 0, 0, 0];
+
+    for (index, &(width, height, is_broken)) in pages.iter().enumerate() {
+        let strip_offset = tiff.len() as u32;
+        tiff.extend((0..width * height).map(|sample| sample as u8));
+
+        let photometric_interpretation = match is_broken {
+            true => 2,
+            false => 1,
+        };
+
+        // (tag, type, value), where type 3 is a short and 4 is a long.
+        let entries: [(u16, u16, u32); 9] = [
+            (256, 4, width),
+            (257, 4, height),
+            (258, 3, 8),
+            (259, 3, 1),
+            (262, 3, photometric_interpretation),
+            (273, 4, strip_offset),
+            (277, 3, 1),
+            (278, 4, height),
+            (279, 4, width * height),
+        ];
+
+        tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+
+        for (tag, entry_type, value) in entries {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&entry_type.to_le_bytes());
+            tiff.extend_from_slice(&1u32.to_le_bytes());
+            tiff.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // each page's image file directory comes after it's pixels.
+        let next_ifd_offset = match pages.get(index + 1) {
+            Some((next_width, next_height, _)) => tiff.len() as u32 + 4 + next_width * next_height,
+            None => 0,
+        };
+
+        tiff.extend_from_slice(&next_ifd_offset.to_le_bytes());
+    }
+
+    let first_ifd_offset = 8 + pages[0].0 * pages[0].1;
+    tiff[4..8].copy_from_slice(&first_ifd_offset.to_le_bytes());
+
+    tiff
+}
+
+#[test]
+fn test_multi_page_tiff_skips_broken_pages() -> Result<()> {
+    let image_bytes = tiff_with_pages(&[(4, 2, false), (3, 3, true), (2, 4, false)]);
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes), ImageFormat::Tiff);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+
+    assert_eq!(
+        backend.pages(),
+        vec![
+            PageInfo { size: (4, 2), colour_type: ImageColourType::Grey8 },
+            PageInfo { size: (2, 4), colour_type: ImageColourType::Grey8 },
+        ]
+    );
+
+    backend.select_page(1)?;
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (2, 4));
+
+    Ok(())
+}
+
+#[test]
+fn test_multi_page_tiff_with_one_usable_page_decodes_as_single_page() -> Result<()> {
+    let image_bytes = tiff_with_pages(&[(4, 2, false), (3, 3, true)]);
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes), ImageFormat::Tiff);
+
+    let backend = ImageRSBackend::from_reader(image_reader)?;
+
+    assert!(backend.pages().is_empty());
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (4, 2));
+
+    match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => assert_eq!(pixels, vec![0, 1, 2, 3, 4, 5, 6, 7]),
+        _ => panic!("Tiff with one usable page should have been decoded to static u8 pixels!"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_ico_entries_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../multi_size.ico");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Ico);
+
+    let mut backend = ImageRSBackend::from_reader(image_reader)?;
+
+    // entries go from largest to smallest.
+    let page_sizes: Vec<_> = backend.pages().into_iter().map(|page| page.size).collect();
+    assert_eq!(page_sizes, vec![(48, 48), (32, 32), (16, 16)]);
+
+    backend.select_page(2)?;
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (16, 16));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => assert_eq!(&pixels[0..4], &[0, 0, 255, 255]),
+        _ => panic!("Selected ico entry should have been decoded to static u8 pixels!"),
+    }
+
+    save_image::<Rgba<u8>>(decoded_image, "smallest_ico_entry.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_decode_memory_limit_exceeded() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+    let limits = DecodeLimits::new(None, None, Some(512 * 1024));
+
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png).with_limits(limits)
+    )?;
+
+    match backend.decode() {
+        Err(Error::MemoryLimitExceeded { required, limit }) => {
+            assert_eq!(required, 750 * 250 * 4);
+            assert_eq!(limit, 512 * 1024);
+        },
+        result => panic!("Expected the memory limit to be exceeded, got: {:?}", result.map(|image| image.size)),
+    }
+
+    // progressive decoding has to stay within the limits too.
+    let backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png).with_limits(limits)
+    )?;
+
+    assert!(matches!(backend.decode_progressively(|_| {}), Err(Error::MemoryLimitExceeded { .. })));
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::io::Cursor;
+
+use image::Rgba;
+use roseate_core::{self, backends::{backend::DecodeBackend, psd::PsdBackend}, decoded_image::DecodedImageContent, error::Result, format::ImageFormat, colour_type::ImageColourType, modifications::{ImageModification, ResizeFilter}, pixels::Pixels, reader::ImageReader};
+
+use crate::backends::{save_image};
+
+#[test]
+fn test_psd_decode_composite() -> Result<()> {
+    let image_bytes = include_bytes!("../layers.psd");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Psd);
+
+    let backend = PsdBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (32, 16));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    let document = decoded_image.info.document.as_ref().expect("PSD should have document info!");
+
+    assert_eq!(document.size, (32, 16));
+    assert_eq!(document.layer_count, 2);
+
+    let pixels = match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels.clone(),
+        _ => panic!("8 bit PSD should decode to static u8 pixels!"),
+    };
+
+    // the layer count is negative so the fourth channel is the composite's transparency.
+    assert_eq!(&pixels[(32 + 1) * 4..][..4], &[8, 16, 64, 255]);
+    assert_eq!(&pixels[20 * 4..][..4], &[160, 0, 64, 0]);
+
+    save_image::<Rgba<u8>>(decoded_image, "layers_psd.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_psd_cmyk_16_bit_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../cmyk_16_bit.psd");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Psd);
+
+    let backend = PsdBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (8, 4));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb16);
+    assert_eq!(decoded_image.info.metadata.model, Some("Roseate Test Camera".into()));
+    assert_eq!(decoded_image.info.document.as_ref().map(|document| document.layer_count), Some(0));
+
+    let pixels = match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U16(pixels)) => pixels,
+        _ => panic!("16 bit PSD should decode to static u16 pixels!"),
+    };
+
+    // full magenta ink and half black, every other pixel also has full yellow ink.
+    assert_eq!(&pixels[0..3], &[32768, 0, 0]);
+    assert_eq!(&pixels[3..6], &[32768, 0, 32768]);
+
+    Ok(())
+}
+
+#[test]
+fn test_psb_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../grey_16_bit.psb");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Psb);
+
+    let mut backend = PsdBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(8, 4, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (8, 4));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Grey16);
+    assert_eq!(decoded_image.info.format, ImageFormat::Psb);
+
+    // the document info should survive being handed over to image-rs for modifications.
+    let document = decoded_image.info.document.as_ref().expect("PSB should have document info!");
+
+    assert_eq!(document.size, (16, 8));
+    assert_eq!(document.layer_count, 3);
+
+    Ok(())
+}
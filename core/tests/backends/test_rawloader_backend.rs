@@ -0,0 +1,98 @@
+use std::io::Cursor;
+
+use image::Rgb;
+use roseate_core::{self, backends::{backend::DecodeBackend, rawloader::RawloaderBackend}, decoded_image::DecodedImageContent, error::{Error, Result}, format::ImageFormat, colour_type::ImageColourType, limits::DecodeLimits, modifications::{ImageModification, ResizeFilter}, pixels::Pixels, reader::ImageReader};
+
+use crate::backends::{save_image};
+
+#[test]
+fn test_dng_develop() -> Result<()> {
+    let image_bytes = include_bytes!("../example.dng");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Dng);
+
+    let backend = RawloaderBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (64, 48));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb16);
+    assert_eq!(decoded_image.info.format, ImageFormat::Dng);
+    assert_eq!(decoded_image.info.metadata.model, Some("Test Camera (Roseate)".into()));
+    assert_eq!(decoded_image.info.metadata.iso, Some("200".into()));
+
+    let pixels = match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U16(pixels)) => pixels,
+        _ => panic!("Developed raw image should be static 16 bit pixels!"),
+    };
+
+    // the red and blue photosites are half as bright as the green ones but the as shot 
+    // white balance should bring them back in line, so the middle of the image should be grey.
+    let middle = (24 * 64 + 32) * 3;
+    let [red, green, blue] = [pixels[middle], pixels[middle + 1], pixels[middle + 2]];
+
+    assert!(green > 0);
+    assert!(red.abs_diff(green) < 256 && blue.abs_diff(green) < 256);
+
+    Ok(())
+}
+
+#[test]
+fn test_dng_develop_within_limits() -> Result<()> {
+    let image_bytes = include_bytes!("../example.dng");
+
+    // enough for the 16 bit RGB image we hand back but not for developing it.
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Dng)
+        .with_limits(DecodeLimits::new(None, None, Some(30_000)));
+
+    let backend = RawloaderBackend::from_reader(image_reader)?;
+
+    assert!(matches!(
+        backend.decode(),
+        Err(Error::MemoryLimitExceeded { required: 61_440, limit: 30_000 })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_dng_modify_and_develop() -> Result<()> {
+    let image_bytes = include_bytes!("../example.dng");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Dng);
+
+    let mut backend = RawloaderBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(32, 24, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (32, 24));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb16);
+    assert_eq!(decoded_image.info.metadata.model, Some("Test Camera (Roseate)".into()));
+
+    Ok(())
+}
+
+#[test]
+fn test_dng_embedded_preview() -> Result<()> {
+    let image_bytes = include_bytes!("../example.dng");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Dng);
+
+    let mut backend = RawloaderBackend::from_reader(image_reader)?;
+    backend.use_embedded_preview(true);
+
+    let decoded_image = backend.decode()?;
+
+    // the preview embedded into the dng is "example.jpg".
+    assert_eq!(decoded_image.size, (275, 307));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+    assert_eq!(decoded_image.info.format, ImageFormat::Dng);
+    assert_eq!(decoded_image.info.metadata.iso, Some("200".into()));
+
+    save_image::<Rgb<u8>>(decoded_image, "dng_embedded_preview.png");
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::io::Cursor;
+
+use image::Rgba;
+use roseate_core::{self, backends::{backend::DecodeBackend, resvg::ResvgBackend}, error::{Error, Result}, format::ImageFormat, limits::DecodeLimits, colour_type::ImageColourType, modifications::{ImageModification, ResizeFilter}, reader::ImageReader};
+
+use crate::backends::{save_image};
+
+#[test]
+fn test_svg_rasterize() -> Result<()> {
+    let image_bytes = include_bytes!("../rose.svg");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Svg);
+
+    let backend = ResvgBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (240, 160));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "rose.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_svg_rasterize_at_larger_size() -> Result<()> {
+    let image_bytes = include_bytes!("../rose_view_box_only.svg");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Svg);
+
+    let mut backend = ResvgBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(1920, 1280, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (1920, 1280));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "big_rose.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_svg_rasterize_memory_limit_exceeded() -> Result<()> {
+    let image_bytes = include_bytes!("../rose.svg");
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Svg)
+        .with_limits(DecodeLimits::new(None, None, Some(64 * 1024 * 1024)));
+
+    let mut backend = ResvgBackend::from_reader(image_reader)?;
+
+    // zooming far into an svg asks for a giant raster.
+    backend.modify(vec![ImageModification::Resize(24000, 16000, ResizeFilter::Lanczos3)]);
+
+    assert!(matches!(
+        backend.decode(),
+        Err(Error::MemoryLimitExceeded { required, .. }) if required == 24000 * 16000 * 4
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use std::{fs::File, io::Cursor, path::PathBuf};
+
+use image::{Rgb, Rgba};
+use roseate_core::{self, backends::{backend::DecodeBackend, zune_image::ZuneImageBackend}, error::{Error, Result}, format::ImageFormat, limits::DecodeLimits, colour_type::ImageColourType, modifications::{ImageModification, ResizeFilter}, reader::{EncodedImageBytes, ImageReader}};
+
+use crate::backends::{save_image};
+
+#[test]
+fn test_png_decode_1() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let backend = ZuneImageBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (750, 250));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_modify_and_decode_1() -> Result<()> {
+    let image_bytes = include_bytes!("../mia_holding_rust_book.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(1280, 720, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (1280, 720));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_smaller_mia.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_rotate_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Rotate90, ImageModification::Resize(125, 375, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (125, 375));
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_rotated_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_crop_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![
+        ImageModification::Crop { x: 100, y: 50, width: 200, height: 100 },
+        ImageModification::Resize(100, 50, ResizeFilter::Lanczos3)
+    ]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (100, 50));
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_cropped_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_png_modify_already_decoded_image() -> Result<()> {
+    let image_bytes = include_bytes!("../mia_holding_rust_book.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let backend = ZuneImageBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (1920, 1080));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    let mut backend = ZuneImageBackend::from_reader(
+        ImageReader::new(decoded_image, ImageFormat::Png)
+    )?;
+
+    backend.modify(vec![ImageModification::Resize(500, 500, ResizeFilter::Lanczos3)]);
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (500, 500));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_squished_mia.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_animated_png_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../animated_png.png");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Png);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(50, 50, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (50, 50));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_tiny_animated_png");
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_decode_and_read_exif() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Jpeg);
+
+    let backend = ZuneImageBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (275, 307));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+    assert_eq!(
+        decoded_image.info.metadata.model.as_deref(),
+        Some("Test Camera (Roseate)")
+    );
+
+    save_image::<Rgb<u8>>(decoded_image, "zune_example.jpeg");
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_modify_and_decode() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Jpeg);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(138, 154, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (138, 154));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgb8);
+
+    save_image::<Rgb<u8>>(decoded_image, "zune_smaller_example.jpeg");
+
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_format() {
+    let image_bytes = include_bytes!("../sailor_moon.gif");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::Gif);
+
+    assert!(ZuneImageBackend::from_reader(image_reader).is_err());
+}
+
+#[test]
+fn test_jpeg_xl_decode_and_modify() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.jxl");
+
+    let cursor = Cursor::new(image_bytes.to_vec());
+    let image_reader = ImageReader::new(cursor, ImageFormat::JpegXl);
+
+    let mut backend = ZuneImageBackend::from_reader(image_reader)?;
+    backend.modify(vec![ImageModification::Resize(375, 125, ResizeFilter::Lanczos3)]);
+
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(decoded_image.size, (375, 125));
+    assert_eq!(decoded_image.colour_type, ImageColourType::Rgba8);
+
+    save_image::<Rgba<u8>>(decoded_image, "zune_smaller_mov-cli.png");
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_xl_container_read_exif() -> Result<()> {
+    let codestream = include_bytes!("../mov_cli_logo.jxl");
+    let jpeg_bytes = include_bytes!("../oriented.jpg");
+
+    // the exif of the jpeg's APP1 segment, which has a camera model and says to rotate the image.
+    let exif_start = jpeg_bytes.windows(6).position(|window| window == b"Exif\0\0").unwrap();
+    let app1_length = u16::from_be_bytes([jpeg_bytes[exif_start - 2], jpeg_bytes[exif_start - 1]]) as usize;
+    let exif = &jpeg_bytes[exif_start + 6..exif_start - 2 + app1_length];
+
+    let jxl_box = |box_type: &[u8], content: &[u8]| -> Vec<u8> {
+        let mut jxl_box = ((8 + content.len()) as u32).to_be_bytes().to_vec();
+        jxl_box.extend_from_slice(box_type);
+        jxl_box.extend_from_slice(content);
+        jxl_box
+    };
+
+    let mut image_bytes = jxl_box(b"JXL ", &[0x0D, 0x0A, 0x87, 0x0A]);
+    image_bytes.extend(jxl_box(b"ftyp", b"jxl \0\0\0\0jxl "));
+    image_bytes.extend(jxl_box(b"Exif", &[&[0, 0, 0, 0], exif].concat()));
+    image_bytes.extend(jxl_box(b"jxlc", codestream));
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes), ImageFormat::JpegXl);
+
+    let backend = ZuneImageBackend::from_reader(image_reader)?;
+    let decoded_image = backend.decode()?;
+
+    assert_eq!(
+        decoded_image.info.metadata.model.as_deref(),
+        Some("Test Camera (Roseate)")
+    );
+
+    // the codestream's orientation is what counts for jxls, not the exif's.
+    let expected_image = ZuneImageBackend::from_reader(
+        ImageReader::new(Cursor::new(codestream.to_vec()), ImageFormat::JpegXl)
+    )?.decode()?;
+
+    assert_eq!(decoded_image.size, expected_image.size);
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_dimensions_limit_exceeded() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+        .with_limits(DecodeLimits::new(Some(256), None, None));
+
+    let backend = ZuneImageBackend::from_reader(image_reader)?;
+
+    match backend.decode() {
+        Err(Error::ImageDimensionsLimitExceeded { size, limit }) => {
+            assert_eq!(size, (275, 307));
+            assert_eq!(limit, (256, u32::MAX));
+        },
+        result => panic!("Expected the dimensions limit to be exceeded, got: {:?}", result.map(|image| image.size)),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_memory_mapped_jpeg_decode() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/example.jpg");
+
+    let encoded_image_bytes = EncodedImageBytes::from_file(File::open(path).unwrap()).unwrap();
+    assert!(matches!(encoded_image_bytes, EncodedImageBytes::Mapped(_)));
+
+    let image_reader = ImageReader::new(encoded_image_bytes, ImageFormat::Jpeg);
+
+    // the copy we'd fall back with shares the same mapped bytes.
+    let image_reader_copy = image_reader.try_clone().unwrap();
+
+    for image_reader in [image_reader, image_reader_copy] {
+        let backend = ZuneImageBackend::from_reader(image_reader)?;
+        let decoded_image = backend.decode()?;
+
+        assert_eq!(decoded_image.size, (275, 307));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,2 @@
+mod test_header;
+mod test_svg_size;
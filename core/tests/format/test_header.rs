@@ -0,0 +1,149 @@
+use std::io::Cursor;
+
+use roseate_core::{error::Result, format::{ImageFormat, determine_image_format_and_size, determine_image_format_and_size_from_header}};
+
+#[test]
+fn test_jpeg_xl_header() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.jxl");
+
+    let mut cursor = Cursor::new(image_bytes.to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::JpegXl);
+    assert_eq!(image_size, (750, 250));
+
+    Ok(())
+}
+
+#[test]
+fn test_dng_header() -> Result<()> {
+    let image_bytes = include_bytes!("../example.dng");
+
+    let mut cursor = Cursor::new(image_bytes.to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::Dng);
+    assert_eq!(image_size, (64, 48));
+
+    Ok(())
+}
+
+#[test]
+fn test_exr_and_hdr_header() -> Result<()> {
+    let mut cursor = Cursor::new(include_bytes!("../gradient.exr").to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::Exr);
+    assert_eq!(image_size, (64, 32));
+
+    let mut cursor = Cursor::new(include_bytes!("../gradient.hdr").to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::Hdr);
+    assert_eq!(image_size, (64, 32));
+
+    Ok(())
+}
+
+#[test]
+fn test_extra_raster_format_headers() -> Result<()> {
+    let images: [(&[u8], ImageFormat, (u32, u32)); 8] = [
+        (include_bytes!("../gradient.ppm"), ImageFormat::Pnm, (64, 32)),
+        (include_bytes!("../gradient.pam"), ImageFormat::Pnm, (64, 32)),
+        (include_bytes!("../gradient.pfm"), ImageFormat::Pfm, (64, 32)),
+        (include_bytes!("../gradient.tga"), ImageFormat::Tga, (64, 32)),
+        (include_bytes!("../gradient.ff"), ImageFormat::Farbfeld, (64, 32)),
+        (include_bytes!("../texture_bc1.dds"), ImageFormat::Dds, (8, 8)),
+        (include_bytes!("../texture_bc7.dds"), ImageFormat::Dds, (8, 4)),
+        (include_bytes!("../example.ora"), ImageFormat::OpenRaster, (275, 307)),
+    ];
+
+    for (image_bytes, expected_image_format, expected_image_size) in images {
+        let mut cursor = Cursor::new(image_bytes.to_vec());
+
+        let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+        assert_eq!(image_format, expected_image_format);
+        assert_eq!(image_size, expected_image_size);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_psd_and_psb_header() -> Result<()> {
+    let mut cursor = Cursor::new(include_bytes!("../layers.psd").to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::Psd);
+    assert_eq!(image_size, (32, 16));
+
+    let mut cursor = Cursor::new(include_bytes!("../grey_16_bit.psb").to_vec());
+
+    let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+    assert_eq!(image_format, ImageFormat::Psb);
+    assert_eq!(image_size, (16, 8));
+
+    Ok(())
+}
+
+#[test]
+fn test_truncated_headers() -> Result<()> {
+    let images: [(&[u8], ImageFormat, (u32, u32)); 6] = [
+        (include_bytes!("../headers/truncated.png"), ImageFormat::Png, (275, 307)),
+        (include_bytes!("../headers/truncated.gif"), ImageFormat::Gif, (500, 375)),
+        (include_bytes!("../headers/truncated.psd"), ImageFormat::Psd, (32, 16)),
+        (include_bytes!("../headers/truncated.bmp"), ImageFormat::Bmp, (12, 7)),
+        (include_bytes!("../headers/truncated.qoi"), ImageFormat::Qoi, (20, 10)),
+        // imagesize gives up on the fill byte after the SOI marker, so the whole buffer has to be parsed.
+        (include_bytes!("../headers/fill_bytes.jpg"), ImageFormat::Jpeg, (275, 307)),
+    ];
+
+    for (image_bytes, expected_image_format, expected_image_size) in images {
+        let mut cursor = Cursor::new(image_bytes.to_vec());
+
+        let (image_format, image_size) = determine_image_format_and_size_from_header(&mut cursor)?;
+
+        assert_eq!(image_format, expected_image_format);
+        assert_eq!(image_size, expected_image_size);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_header_falls_back_to_extension() -> Result<()> {
+    // the leftover colour map entry size makes imagesize think this isn't a tga.
+    let image_bytes = include_bytes!("../headers/greyscale.tga");
+
+    let mut cursor = Cursor::new(image_bytes.to_vec());
+    assert!(determine_image_format_and_size_from_header(&mut cursor).is_err());
+
+    let mut cursor = Cursor::new(image_bytes.to_vec());
+    let detected_image_format = determine_image_format_and_size(&mut cursor, Some("tga"))?;
+
+    assert_eq!(detected_image_format.image_format, ImageFormat::Tga);
+    assert_eq!(detected_image_format.image_size, (6, 4));
+    assert_eq!(detected_image_format.extension_mismatch, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_header_extension_mismatch() -> Result<()> {
+    let mut cursor = Cursor::new(include_bytes!("../headers/png_named_as.jpg").to_vec());
+
+    let detected_image_format = determine_image_format_and_size(&mut cursor, Some("jpg"))?;
+
+    assert_eq!(detected_image_format.image_format, ImageFormat::Png);
+    assert_eq!(detected_image_format.image_size, (275, 307));
+    assert_eq!(detected_image_format.extension_mismatch, Some(ImageFormat::Jpeg));
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use roseate_core::{error::Result, format::determine_svg_size};
+
+#[test]
+fn test_svg_size_from_attributes() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/rose.svg");
+
+    assert_eq!(determine_svg_size(&path)?, (240, 160));
+
+    Ok(())
+}
+
+#[test]
+fn test_svg_size_from_view_box() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/rose_view_box_only.svg");
+
+    assert_eq!(determine_svg_size(&path)?, (120, 80));
+
+    Ok(())
+}
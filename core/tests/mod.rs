@@ -1 +1,6 @@
-mod backends;
\ No newline at end of file
+mod backends;
+mod format;
+mod modifications;
+mod orientation;
+mod processing;
+mod thumbnail;
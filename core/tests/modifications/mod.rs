@@ -0,0 +1 @@
+mod test_pipeline;
\ No newline at end of file
@@ -0,0 +1,107 @@
+use roseate_core::{modifications::{ImageModification, ModificationPipeline, ResizeFilter}, orientation::ImageOrientation};
+
+#[test]
+fn test_pipeline_merges_back_to_back_modifications() {
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Resize(500, 500, ResizeFilter::Lanczos3),
+        ImageModification::Resize(250, 250, ResizeFilter::Lanczos3),
+    ]);
+
+    assert_eq!(pipeline.iter().collect::<Vec<_>>(), [&ImageModification::Resize(250, 250, ResizeFilter::Lanczos3)]);
+
+    // a crop of a crop is the one crop, offset by the first.
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Crop { x: 100, y: 50, width: 200, height: 100 },
+        ImageModification::Crop { x: 10, y: 20, width: 500, height: 50 },
+    ]);
+
+    assert_eq!(
+        pipeline.iter().collect::<Vec<_>>(),
+        [&ImageModification::Crop { x: 110, y: 70, width: 190, height: 50 }]
+    );
+
+    // flips and rotations add up to a single flip and rotation, or nothing at all.
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Rotate90,
+        ImageModification::FlipHorizontal,
+        ImageModification::Rotate90,
+    ]);
+
+    assert_eq!(pipeline.len(), 1);
+    assert_eq!(pipeline.orientation(), ImageOrientation::FlipHorizontal);
+
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Rotate90,
+        ImageModification::Rotate270,
+    ]);
+
+    assert!(pipeline.is_empty());
+}
+
+#[test]
+fn test_pipeline_order() {
+    let crop = ImageModification::Crop { x: 0, y: 0, width: 200, height: 100 };
+
+    let cropped_then_rotated = ModificationPipeline::from(vec![crop.clone(), ImageModification::Rotate90]);
+    let rotated_then_cropped = ModificationPipeline::from(vec![ImageModification::Rotate90, crop]);
+
+    assert_ne!(cropped_then_rotated, rotated_then_cropped);
+    assert_eq!(cropped_then_rotated.output_size((750, 250)), (100, 200));
+    assert_eq!(rotated_then_cropped.output_size((750, 250)), (200, 100));
+
+    // the same modifications the same way round are the same pipeline.
+    assert_eq!(
+        ModificationPipeline::from(vec![ImageModification::FlipVertical, ImageModification::Resize(10, 10, ResizeFilter::Lanczos3)]),
+        ModificationPipeline::from(vec![
+            ImageModification::FlipHorizontal,
+            ImageModification::Rotate180,
+            ImageModification::Resize(20, 20, ResizeFilter::Lanczos3),
+            ImageModification::Resize(10, 10, ResizeFilter::Lanczos3),
+        ])
+    );
+
+    let mut pipeline = rotated_then_cropped.clone();
+    pipeline.push(ImageModification::Resize(50, 25, ResizeFilter::Lanczos3));
+
+    assert_eq!(pipeline.pop_resize(), Some((50, 25)));
+    assert_eq!(pipeline, rotated_then_cropped);
+    assert_eq!(pipeline.pop_resize(), None);
+}
+
+#[test]
+fn test_pipeline_retain_and_pop_orientation() {
+    let mut pipeline = ModificationPipeline::from(vec![
+        ImageModification::Rotate90,
+        ImageModification::Crop { x: 0, y: 0, width: 200, height: 100 },
+        ImageModification::Rotate90,
+    ]);
+
+    assert_eq!(pipeline.clone().pop_orientation(), ImageOrientation::Rotate90);
+
+    // taking the crop out leaves the rotations back to back so they're merged.
+    pipeline.retain(|modification| !matches!(modification, ImageModification::Crop { .. }));
+
+    assert_eq!(pipeline.iter().collect::<Vec<_>>(), [&ImageModification::Rotate180]);
+    assert_eq!(pipeline.pop_orientation(), ImageOrientation::Rotate180);
+    assert!(pipeline.is_empty());
+}
+
+#[test]
+fn test_pipeline_crop_of_empty_image() {
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Crop { x: 10, y: 10, width: 200, height: 100 },
+    ]);
+
+    assert_eq!(pipeline.output_size((0, 0)), (1, 1));
+
+    // a crop of an empty crop.
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Crop { x: 0, y: 0, width: 0, height: 0 },
+        ImageModification::Crop { x: 10, y: 20, width: 50, height: 50 },
+    ]);
+
+    assert_eq!(
+        pipeline.iter().collect::<Vec<_>>(),
+        [&ImageModification::Crop { x: 0, y: 0, width: 1, height: 1 }]
+    );
+}
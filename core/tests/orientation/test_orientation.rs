@@ -0,0 +1,158 @@
+use std::io::Cursor;
+
+use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend, zune_image::ZuneImageBackend}, decoded_image::{DecodedImage, DecodedImageContent}, error::Result, format::ImageFormat, modifications::{ImageModification, ModificationPipeline, ResizeFilter}, orientation::{ImageOrientation, determine_image_orientation}, pixels::Pixels, reader::ImageReader};
+
+fn decode_oriented_jpeg(auto_orientation: bool) -> Result<DecodedImage> {
+    let image_bytes = include_bytes!("../oriented.jpg");
+
+    let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+        .with_auto_orientation(auto_orientation);
+
+    ImageRSBackend::from_reader(image_reader)?.decode()
+}
+
+fn rgb_at(decoded_image: &DecodedImage, x: u32, y: u32) -> [u8; 3] {
+    match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => {
+            let index = ((y * decoded_image.size.0 + x) * 3) as usize;
+            [pixels[index], pixels[index + 1], pixels[index + 2]]
+        },
+        _ => panic!("Expected a static 8 bit image!"),
+    }
+}
+
+#[test]
+fn test_determine_image_orientation() {
+    assert_eq!(determine_image_orientation(include_bytes!("../oriented.jpg")), ImageOrientation::Rotate90);
+    assert_eq!(determine_image_orientation(include_bytes!("../example.jpg")), ImageOrientation::Normal);
+    assert_eq!(determine_image_orientation(include_bytes!("../example.png")), ImageOrientation::Normal);
+}
+
+#[test]
+fn test_orient_size() {
+    assert_eq!(ImageOrientation::Rotate90.orient_size((275, 307)), (307, 275));
+    assert_eq!(ImageOrientation::Rotate270.orient_size((275, 307)), (307, 275));
+    assert_eq!(ImageOrientation::Rotate180.orient_size((275, 307)), (275, 307));
+    assert_eq!(ImageOrientation::from_exif_value(0), ImageOrientation::Normal);
+}
+
+#[test]
+fn test_orientation_composition() {
+    let orientations = [
+        ImageOrientation::Normal,
+        ImageOrientation::FlipHorizontal,
+        ImageOrientation::Rotate180,
+        ImageOrientation::FlipVertical,
+        ImageOrientation::Transpose,
+        ImageOrientation::Rotate90,
+        ImageOrientation::Transverse,
+        ImageOrientation::Rotate270,
+    ];
+
+    for orientation in orientations {
+        assert_eq!(orientation.then(orientation.inverse()), ImageOrientation::Normal);
+
+        let modifications: ModificationPipeline = orientation.to_modifications().into_iter().collect();
+        assert_eq!(modifications.orientation(), orientation);
+    }
+
+    assert_eq!(ImageOrientation::Rotate90.then(ImageOrientation::Rotate90), ImageOrientation::Rotate180);
+    assert_eq!(ImageOrientation::FlipHorizontal.then(ImageOrientation::Rotate180), ImageOrientation::FlipVertical);
+    assert_eq!(ImageOrientation::Rotate90.then(ImageOrientation::FlipHorizontal), ImageOrientation::Transpose);
+}
+
+#[test]
+fn test_jpeg_auto_orientation() -> Result<()> {
+    let stored_image = decode_oriented_jpeg(false)?;
+    let oriented_image = decode_oriented_jpeg(true)?;
+
+    assert_eq!(stored_image.size, (275, 307));
+    assert_eq!(oriented_image.size, (307, 275));
+    assert_eq!(oriented_image.info.size, (307, 275));
+    assert_eq!(oriented_image.info.metadata.orientation, ImageOrientation::Rotate90);
+
+    // rotated clockwise, the bottom left of the stored image ends up top left.
+    assert_eq!(rgb_at(&oriented_image, 0, 0), rgb_at(&stored_image, 0, 306));
+    assert_eq!(rgb_at(&oriented_image, 306, 0), rgb_at(&stored_image, 0, 0));
+    assert_eq!(rgb_at(&oriented_image, 306, 274), rgb_at(&stored_image, 274, 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_auto_orientation_with_resize() -> Result<()> {
+    let image_bytes = include_bytes!("../oriented.jpg");
+
+    // the resize is asked for relative to the oriented image.
+    let mut backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+    backend.modify(vec![ImageModification::Resize(150, 134, ResizeFilter::Lanczos3)]);
+
+    assert_eq!(backend.decode()?.size, (150, 134));
+
+    let mut backend = ZuneImageBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+    backend.modify(vec![ImageModification::Resize(150, 134, ResizeFilter::Lanczos3)]);
+
+    assert_eq!(backend.decode()?.size, (150, 134));
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_auto_orientation_then_flip() -> Result<()> {
+    let oriented_image = decode_oriented_jpeg(true)?;
+
+    let image_bytes = include_bytes!("../oriented.jpg");
+
+    // the flip is of the image once it's the right way up, not the way it's stored.
+    let mut backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+    backend.modify(vec![ImageModification::FlipHorizontal]);
+
+    let flipped_image = backend.decode()?;
+
+    assert_eq!(flipped_image.size, (307, 275));
+    assert_eq!(rgb_at(&flipped_image, 0, 0), rgb_at(&oriented_image, 306, 0));
+    assert_eq!(rgb_at(&flipped_image, 306, 274), rgb_at(&oriented_image, 0, 274));
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_auto_orientation_and_crop() -> Result<()> {
+    let oriented_image = decode_oriented_jpeg(true)?;
+
+    // the crop is picked on the image the right way up, the backend takes it back to the image the way it's stored.
+    assert_eq!(ImageOrientation::Rotate90.unorient_rect((10, 20, 50, 30), (275, 307)), (20, 247, 30, 50));
+
+    let image_bytes = include_bytes!("../oriented.jpg");
+
+    let mut backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg)
+    )?;
+    backend.modify(vec![ImageModification::Crop { x: 10, y: 20, width: 50, height: 30 }]);
+
+    let cropped_image = backend.decode()?;
+
+    assert_eq!(cropped_image.size, (50, 30));
+    assert_eq!(rgb_at(&cropped_image, 0, 0), rgb_at(&oriented_image, 10, 20));
+    assert_eq!(rgb_at(&cropped_image, 49, 29), rgb_at(&oriented_image, 59, 49));
+
+    Ok(())
+}
+
+#[test]
+fn test_decoded_image_is_not_oriented_twice() -> Result<()> {
+    let oriented_image = decode_oriented_jpeg(true)?;
+
+    let mut backend = ImageRSBackend::from_reader(ImageReader::new(oriented_image, ImageFormat::Jpeg))?;
+    backend.modify(vec![ImageModification::Resize(150, 134, ResizeFilter::Lanczos3)]);
+
+    assert_eq!(backend.decode()?.size, (150, 134));
+
+    Ok(())
+}
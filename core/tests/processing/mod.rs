@@ -0,0 +1,5 @@
+mod test_tone_mapping;
+mod test_quantization;
+mod test_adjustments;
+mod test_colour_management;
+mod test_alpha;
\ No newline at end of file
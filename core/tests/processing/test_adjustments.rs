@@ -0,0 +1,148 @@
+use std::io::Cursor;
+
+use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, error::Result, format::ImageFormat, image_info::metadata::ImageMetadata, modifications::{ImageModification, ModificationPipeline, ResizeFilter}, pixels::Pixels, reader::ImageReader};
+
+fn single_pixel_image(pixels: Pixels, colour_type: ImageColourType) -> DecodedImage {
+    DecodedImage::new(
+        (1, 1),
+        ImageFormat::Png,
+        colour_type,
+        ImageMetadata::default(),
+        DecodedImageContent::Static(pixels),
+    )
+}
+
+fn adjusted(pixels: Pixels, colour_type: ImageColourType, adjustments: &[ImageModification]) -> Pixels {
+    let mut decoded_image = single_pixel_image(pixels, colour_type);
+    decoded_image.adjust_colours(adjustments);
+
+    match decoded_image.content {
+        DecodedImageContent::Static(pixels) => pixels,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_exposure_and_saturation() {
+    // a stop brighter is twice the light, which sRGB encodes as a lot less than twice the value.
+    let brightened = adjusted(Pixels::U8(vec![128, 64, 32, 200]), ImageColourType::Rgba8, &[ImageModification::Exposure(1.0)]);
+
+    match brightened {
+        Pixels::U8(samples) => {
+            assert_eq!(samples[0], 176);
+            assert!(samples[1] > 64 && samples[2] > 32);
+            // alpha is never adjusted.
+            assert_eq!(samples[3], 200);
+        },
+        _ => panic!("Expected 8 bit pixels!"),
+    }
+
+    let desaturated = adjusted(Pixels::U16(vec![60000, 20000, 5000]), ImageColourType::Rgb16, &[ImageModification::Saturation(0.0)]);
+
+    match desaturated {
+        Pixels::U16(samples) => assert!(samples[0] == samples[1] && samples[1] == samples[2]),
+        _ => panic!("Expected 16 bit pixels!"),
+    }
+
+    // float pixels are linear so an exposure is a straight multiply.
+    let brightened = adjusted(Pixels::F32(vec![0.25]), ImageColourType::Grey32F, &[ImageModification::Exposure(1.0)]);
+
+    match brightened {
+        Pixels::F32(samples) => assert!((samples[0] - 0.5).abs() < 0.001),
+        _ => panic!("Expected f32 pixels!"),
+    }
+}
+
+#[test]
+fn test_neutral_adjustments() {
+    let neutral_adjustments = [
+        ImageModification::Contrast(1.0),
+        ImageModification::Gamma(1.0),
+        ImageModification::Saturation(1.0),
+        ImageModification::WhiteBalance { temperature: 0.0, tint: 0.0 },
+    ];
+
+    let samples = vec![0, 1000, 32768, 65535];
+
+    match adjusted(Pixels::U16(samples.clone()), ImageColourType::Rgba16, &neutral_adjustments) {
+        Pixels::U16(adjusted_samples) => {
+            for (sample, adjusted_sample) in samples.iter().zip(adjusted_samples) {
+                assert!(sample.abs_diff(adjusted_sample) <= 1);
+            }
+        },
+        _ => panic!("Expected 16 bit pixels!"),
+    }
+
+    // back to back exposures add up, contrasts multiply.
+    let pipeline = ModificationPipeline::from(vec![
+        ImageModification::Exposure(1.0),
+        ImageModification::Exposure(-0.5),
+        ImageModification::Contrast(2.0),
+        ImageModification::Contrast(0.5),
+    ]);
+
+    assert_eq!(
+        pipeline.iter().collect::<Vec<_>>(),
+        [&ImageModification::Exposure(0.5), &ImageModification::Contrast(1.0)]
+    );
+}
+
+#[test]
+fn test_png_colour_adjustments() -> Result<()> {
+    let image_bytes = include_bytes!("../mov_cli_logo.png");
+
+    let decode_with = |modifications: Vec<ImageModification>| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Png);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        backend.modify(modifications);
+        backend.decode()
+    };
+
+    let sum_of_colours = |decoded_image: &DecodedImage| match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels.chunks(4)
+            .map(|pixel| pixel[..3].iter().map(|&sample| sample as u64).sum::<u64>())
+            .sum::<u64>(),
+        _ => panic!("Expected a static 8 bit image!"),
+    };
+
+    let image = decode_with(vec![])?;
+    let darkened_image = decode_with(vec![ImageModification::Exposure(-1.0)])?;
+    let blacked_out_image = decode_with(vec![ImageModification::Rotate90, ImageModification::Exposure(-20.0)])?;
+
+    assert!(sum_of_colours(&darkened_image) < sum_of_colours(&image));
+    assert_eq!(sum_of_colours(&blacked_out_image), 0);
+    assert_eq!(blacked_out_image.size, (250, 750));
+
+    Ok(())
+}
+
+#[test]
+fn test_scaled_jpeg_colour_adjustments() -> Result<()> {
+    let image_bytes = include_bytes!("../example.jpg");
+
+    let decode_with = |modifications: Vec<ImageModification>| -> Result<DecodedImage> {
+        let image_reader = ImageReader::new(Cursor::new(image_bytes.to_vec()), ImageFormat::Jpeg);
+
+        let mut backend = ImageRSBackend::from_reader(image_reader)?;
+        backend.modify(modifications);
+        backend.decode()
+    };
+
+    let sum_of_samples = |decoded_image: &DecodedImage| match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels.iter().map(|&sample| sample as u64).sum::<u64>(),
+        _ => panic!("Expected a static 8 bit image!"),
+    };
+
+    let (width, height) = decode_with(vec![])?.size;
+    // a quarter of the size is small enough for the jpeg to be decoded at a reduced scale.
+    let resize = ImageModification::Resize(width / 4, height / 4, ResizeFilter::default());
+
+    let image = decode_with(vec![resize.clone()])?;
+    let darkened_image = decode_with(vec![ImageModification::Exposure(-2.0), resize])?;
+
+    assert_eq!(darkened_image.size, image.size);
+    assert!(sum_of_samples(&darkened_image) < sum_of_samples(&image));
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::io::Cursor;
+
+use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend, zune_image::ZuneImageBackend}, colour_type::ImageColourType, decoded_image::DecodedImageContent, error::Result, fast_downsample::experimental_fast_downsample, format::ImageFormat, modifications::{ImageModification, ResizeFilter}, pixels::Pixels, processing::alpha::{premultiply_alpha, unpremultiply_alpha}, reader::ImageReader};
+
+#[test]
+fn test_premultiply_round_trip() {
+    let mut pixels = Pixels::U8(vec![200, 100, 50, 128, 255, 255, 255, 0, 10, 20, 30, 255]);
+
+    premultiply_alpha(&mut pixels, ImageColourType::Rgba8);
+
+    match &pixels {
+        Pixels::U8(samples) => assert_eq!(samples, &[100, 50, 25, 128, 0, 0, 0, 0, 10, 20, 30, 255]),
+        _ => panic!("Expected 8 bit pixels!"),
+    }
+
+    unpremultiply_alpha(&mut pixels, ImageColourType::Rgba8);
+
+    match &pixels {
+        // the colour of fully transparent pixels can't come back.
+        Pixels::U8(samples) => assert_eq!(samples, &[199, 100, 50, 128, 0, 0, 0, 0, 10, 20, 30, 255]),
+        _ => panic!("Expected 8 bit pixels!"),
+    }
+
+    let mut pixels = Pixels::U16(vec![65535, 32768]);
+
+    premultiply_alpha(&mut pixels, ImageColourType::GreyA16);
+
+    match &pixels {
+        Pixels::U16(samples) => assert_eq!(samples, &[32768, 32768]),
+        _ => panic!("Expected 16 bit pixels!"),
+    }
+
+    // pixels without alpha are left alone.
+    let mut pixels = Pixels::F32(vec![0.5, 0.25, 0.125]);
+
+    premultiply_alpha(&mut pixels, ImageColourType::Rgb32F);
+
+    match &pixels {
+        Pixels::F32(samples) => assert_eq!(samples, &[0.5, 0.25, 0.125]),
+        _ => panic!("Expected f32 pixels!"),
+    }
+}
+
+#[test]
+fn test_downsample_has_no_dark_fringes() {
+    // white on the left, transparent black on the right.
+    let pixels = Pixels::U8(
+        (0..16 * 16)
+            .flat_map(|index| match index % 16 < 8 {
+                true => [255, 255, 255, 255],
+                false => [0, 0, 0, 0],
+            })
+            .collect()
+    );
+
+    let (downsampled_pixels, size) = experimental_fast_downsample(
+        &pixels,
+        (4, 4),
+        &(16, 16),
+        &ImageColourType::Rgba8,
+        Some(1)
+    );
+
+    assert_eq!(size, (4, 4));
+
+    // the edge fades out in alpha but the colour stays white.
+    for pixel in downsampled_pixels.chunks(4) {
+        if pixel[3] > 0 {
+            assert!(pixel[..3].iter().all(|&sample| sample >= 250), "{:?} has a dark fringe!", pixel);
+        }
+    }
+}
+
+#[test]
+fn test_backend_resize_has_no_dark_fringes() -> Result<()> {
+    // white on the left, transparent black on the right (split off-center so the edge lands between pixels).
+    let samples: Vec<u8> = (0..16 * 16)
+        .flat_map(|index| match index % 16 < 7 {
+            true => [255, 255, 255, 255],
+            false => [0, 0, 0, 0],
+        })
+        .collect();
+
+    let mut png_bytes = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut png_bytes, 16, 16);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&samples).unwrap();
+    writer.finish().unwrap();
+
+    let resize = ImageModification::Resize(5, 5, ResizeFilter::Bilinear);
+
+    let mut image_rs_backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes.clone()), ImageFormat::Png)
+    )?;
+    image_rs_backend.modify(vec![resize.clone()]);
+
+    let mut zune_backend = ZuneImageBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes), ImageFormat::Png)
+    )?;
+    zune_backend.modify(vec![resize]);
+
+    for decoded_image in [image_rs_backend.decode()?, zune_backend.decode()?] {
+        assert_eq!(decoded_image.size, (5, 5));
+
+        let pixels = match decoded_image.content {
+            DecodedImageContent::Static(Pixels::U8(pixels)) => pixels,
+            _ => panic!("Expected a static 8 bit image!"),
+        };
+
+        assert!(pixels.chunks(4).any(|pixel| pixel[3] > 0 && pixel[3] < 255), "Expected the edge to be partially transparent!");
+
+        for pixel in pixels.chunks(4) {
+            if pixel[3] > 0 {
+                assert!(pixel[..3].iter().all(|&sample| sample >= 250), "{:?} has a dark fringe!", pixel);
+            }
+        }
+    }
+
+    Ok(())
+}
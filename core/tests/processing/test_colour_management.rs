@@ -0,0 +1,137 @@
+use std::{borrow::Cow, io::Cursor};
+
+use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend, zune_image::ZuneImageBackend}, colour_type::ImageColourType, decoded_image::{DecodedImage, DecodedImageContent}, error::Result, format::ImageFormat, image_info::{colour_profile::ColourProfile, metadata::ImageMetadata}, modifications::ImageModification, pixels::Pixels, reader::ImageReader};
+
+const REDDISH_PIXEL: [u8; 3] = [200, 100, 100];
+
+fn display_p3_icc() -> Vec<u8> {
+    moxcms::ColorProfile::new_display_p3().encode().unwrap()
+}
+
+fn static_u8_pixels(decoded_image: DecodedImage) -> Vec<u8> {
+    match decoded_image.content {
+        DecodedImageContent::Static(Pixels::U8(pixels)) => pixels,
+        _ => panic!("Expected a static 8 bit image!"),
+    }
+}
+
+fn encode_png_with_icc(icc: Vec<u8>) -> Vec<u8> {
+    let mut png_bytes = Vec::new();
+
+    let mut info = png::Info::with_size(1, 1);
+    info.color_type = png::ColorType::Rgb;
+    info.bit_depth = png::BitDepth::Eight;
+    info.icc_profile = Some(Cow::Owned(icc));
+
+    let mut writer = png::Encoder::with_info(&mut png_bytes, info).unwrap()
+        .write_header().unwrap();
+
+    writer.write_image_data(&REDDISH_PIXEL).unwrap();
+    writer.finish().unwrap();
+
+    png_bytes
+}
+
+#[test]
+fn test_display_p3_to_srgb() {
+    let colour_profile = ColourProfile::new(display_p3_icc()).unwrap();
+
+    assert_eq!(colour_profile.description.as_deref(), Some("Display P3"));
+    assert!(colour_profile.is_rgb() && !colour_profile.is_srgb());
+
+    let mut decoded_image = DecodedImage::new(
+        (1, 1),
+        ImageFormat::Png,
+        ImageColourType::Rgb8,
+        ImageMetadata::default(),
+        DecodedImageContent::Static(Pixels::U8(REDDISH_PIXEL.to_vec())),
+    );
+
+    decoded_image.convert_to_srgb(colour_profile);
+
+    assert!(decoded_image.info.colour_profile.is_some());
+
+    // display p3's red is more saturated than sRGB's, so the same values land further out.
+    let pixel = static_u8_pixels(decoded_image);
+    assert!(pixel[0] > REDDISH_PIXEL[0] && pixel[1] < REDDISH_PIXEL[1] && pixel[2] < REDDISH_PIXEL[2]);
+}
+
+#[test]
+fn test_srgb_profile_is_left_alone() {
+    let colour_profile = ColourProfile::new(moxcms::ColorProfile::new_srgb().encode().unwrap()).unwrap();
+
+    assert!(colour_profile.is_srgb());
+
+    let mut decoded_image = DecodedImage::new(
+        (1, 1),
+        ImageFormat::Png,
+        ImageColourType::Rgb8,
+        ImageMetadata::default(),
+        DecodedImageContent::Static(Pixels::U8(REDDISH_PIXEL.to_vec())),
+    );
+
+    decoded_image.convert_to_srgb(colour_profile);
+
+    assert!(decoded_image.info.colour_profile.is_some());
+    assert_eq!(static_u8_pixels(decoded_image), REDDISH_PIXEL);
+}
+
+#[test]
+fn test_png_icc_profile_is_extracted() -> Result<()> {
+    let png_bytes = encode_png_with_icc(display_p3_icc());
+
+    let image_rs_image = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes.clone()), ImageFormat::Png)
+    )?.decode()?;
+
+    let zune_image = ZuneImageBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes), ImageFormat::Png)
+    )?.decode()?;
+
+    for decoded_image in [image_rs_image, zune_image] {
+        let colour_profile = decoded_image.info.colour_profile.clone()
+            .expect("Expected the png's iCCP chunk to be extracted!");
+
+        assert_eq!(colour_profile.description.as_deref(), Some("Display P3"));
+
+        let pixel = static_u8_pixels(decoded_image);
+        assert!(pixel[0] > REDDISH_PIXEL[0]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_colour_adjustments_are_made_in_srgb() -> Result<()> {
+    let png_bytes = encode_png_with_icc(display_p3_icc());
+
+    let adjustments = vec![ImageModification::Contrast(1.5), ImageModification::Saturation(1.5)];
+
+    let mut image_rs_backend = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes.clone()), ImageFormat::Png)
+    )?;
+    image_rs_backend.modify(adjustments.clone());
+
+    let mut zune_backend = ZuneImageBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes.clone()), ImageFormat::Png)
+    )?;
+    zune_backend.modify(adjustments.clone());
+
+    // what it should come out as, adjusted after it's been transformed to sRGB.
+    let mut expected_image = ImageRSBackend::from_reader(
+        ImageReader::new(Cursor::new(png_bytes), ImageFormat::Png)
+    )?.decode()?;
+    expected_image.adjust_colours(&adjustments);
+
+    let expected_pixel = static_u8_pixels(expected_image);
+
+    for decoded_image in [image_rs_backend.decode()?, zune_backend.decode()?] {
+        let pixel = static_u8_pixels(decoded_image);
+
+        for (sample, expected_sample) in pixel.iter().zip(&expected_pixel) {
+            assert!(sample.abs_diff(*expected_sample) <= 1, "{:?} != {:?}", pixel, expected_pixel);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use roseate_core::{colour_type::ImageColourType, pixels::Pixels, processing::quantization::{Dithering, quantize_pixels_to_u8}};
+
+const DITHERINGS: [Dithering; 4] = [Dithering::None, Dithering::Ordered, Dithering::BlueNoise, Dithering::FloydSteinberg];
+
+/// A smooth 16 bit grey gradient (with alpha) that bands badly when truncated to 8 bit.
+fn grey_alpha_gradient(width: u32, height: u32) -> Vec<u16> {
+    (0..width * height)
+        .flat_map(|index| {
+            let x = index % width;
+            // only spans a couple of 8 bit levels.
+            let value = 32768 + (x * 512 / width) as u16;
+
+            [value, 40000]
+        })
+        .collect()
+}
+
+#[test]
+fn test_quantize_extremes() {
+    for dithering in DITHERINGS {
+        let pixels = Pixels::U16(vec![0, u16::MAX, 0, u16::MAX]);
+
+        let quantized = quantize_pixels_to_u8(&pixels, (2, 1), ImageColourType::GreyA16, dithering);
+
+        assert_eq!(quantized, vec![0, 255, 0, 255], "{dithering} dithering should not touch black or white");
+    }
+
+    let pixels = Pixels::F32(vec![-1.0, 0.5, 2.0, f32::NAN]);
+    let quantized = quantize_pixels_to_u8(&pixels, (4, 1), ImageColourType::Grey32F, Dithering::None);
+
+    assert_eq!(quantized, vec![0, 128, 255, 0]);
+}
+
+#[test]
+fn test_quantize_rounds_instead_of_truncating() {
+    // 0x00FF >> 8 would be 0 when it's really 0.99 (so 1) and 0xFF00 >> 8 would be 255 when it's really 254.0.
+    let pixels = Pixels::U16(vec![0x00FF, 0xFF00, 0x8080]);
+
+    let quantized = quantize_pixels_to_u8(&pixels, (1, 1), ImageColourType::Rgb16, Dithering::None);
+
+    assert_eq!(quantized, vec![1, 254, 128]);
+}
+
+#[test]
+fn test_dithering_preserves_average_and_alpha() {
+    let (width, height) = (256, 128);
+    let gradient = grey_alpha_gradient(width, height);
+
+    let expected_average = gradient.iter().step_by(2)
+        .map(|&value| value as f64 * 255.0 / 65535.0)
+        .sum::<f64>() / (width * height) as f64;
+
+    let expected_alpha = (40000.0f32 * 255.0 / 65535.0).round() as u8;
+
+    for dithering in [Dithering::Ordered, Dithering::BlueNoise, Dithering::FloydSteinberg] {
+        let quantized = quantize_pixels_to_u8(
+            &Pixels::U16(gradient.clone()),
+            (width, height),
+            ImageColourType::GreyA16,
+            dithering
+        );
+
+        assert_eq!(quantized.len(), gradient.len());
+
+        let average = quantized.iter().step_by(2)
+            .map(|&value| value as f64)
+            .sum::<f64>() / (width * height) as f64;
+
+        assert!(
+            (average - expected_average).abs() < 0.05,
+            "{dithering} dithering should keep the average brightness ({average} != {expected_average})"
+        );
+
+        assert!(
+            quantized.iter().skip(1).step_by(2).all(|&alpha| alpha == expected_alpha),
+            "{dithering} dithering should never dither alpha"
+        );
+
+        // the gradient only covers a few levels so a dithered 
+        // image must mix them rather than having solid bands.
+        let first_column: Vec<u8> = quantized.chunks(width as usize * 2)
+            .map(|row| row[20])
+            .collect();
+
+        assert!(
+            first_column.iter().any(|&value| value != first_column[0]),
+            "{dithering} dithering should break up bands"
+        );
+    }
+}
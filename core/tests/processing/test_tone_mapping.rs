@@ -0,0 +1,55 @@
+use std::io::Cursor;
+
+use roseate_core::{backends::{backend::DecodeBackend, image_rs::ImageRSBackend}, colour_type::ImageColourType, decoded_image::DecodedImageContent, error::Result, format::ImageFormat, pixels::Pixels, processing::tone_mapping::{ToneMapper, tone_map_pixels_to_u8}, reader::ImageReader};
+
+const TONE_MAPPERS: [ToneMapper; 3] = [ToneMapper::Reinhard, ToneMapper::AcesFilmic, ToneMapper::Hable];
+
+#[test]
+fn test_tone_mappers_are_bounded_and_monotonic() {
+    for tone_mapper in TONE_MAPPERS {
+        assert!(tone_mapper.tone_map(0.0) < 0.0001, "{tone_mapper} should keep black, black");
+        assert!(tone_mapper.tone_map(-1.0) < 0.0001);
+        assert!(tone_mapper.tone_map(f32::NAN) < 0.0001);
+
+        let mut last_value = 0.0;
+
+        for step in 1..=1000 {
+            let value = tone_mapper.tone_map(step as f32 * 0.05);
+
+            assert!(value >= last_value, "{tone_mapper} should never get darker as the input gets brighter");
+            assert!(value <= 1.0);
+
+            last_value = value;
+        }
+    }
+}
+
+#[test]
+fn test_tone_map_hdr_pixels_to_u8() -> Result<()> {
+    let cursor = Cursor::new(include_bytes!("../gradient.exr").to_vec());
+
+    let decoded_image = ImageRSBackend::from_reader(ImageReader::new(cursor, ImageFormat::Exr))?.decode()?;
+
+    let pixels = match &decoded_image.content {
+        DecodedImageContent::Static(Pixels::F32(pixels)) => pixels,
+        _ => panic!("EXR images should decode to f32 pixels!"),
+    };
+
+    assert!(decoded_image.info.format.is_high_dynamic_range());
+
+    for tone_mapper in TONE_MAPPERS {
+        let u8_pixels = tone_map_pixels_to_u8(pixels, ImageColourType::Rgba32F, tone_mapper);
+
+        assert_eq!(u8_pixels.len(), pixels.len());
+
+        // the brightest red (16.0) would be clipped by clamping but 
+        // tone-mapping should keep it below the 1/3rd brightness red.
+        let (third_red, brightest_red) = (u8_pixels[21 * 4], u8_pixels[63 * 4]);
+
+        assert!(third_red < brightest_red, "{tone_mapper} should keep highlight detail");
+        // alpha is left alone.
+        assert_eq!(u8_pixels[63 * 4 + 3], 255);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use roseate_core::{colour_type::ImageColourType, format::ImageFormat, thumbnail::decode_embedded_thumbnail};
+
+#[test]
+fn test_jpeg_exif_thumbnail() {
+    let image_bytes = include_bytes!("../exif_thumbnail.jpg");
+
+    let decoded_thumbnail = decode_embedded_thumbnail(image_bytes, &ImageFormat::Jpeg)
+        .expect("Jpeg should have an embedded thumbnail!");
+
+    assert_eq!(decoded_thumbnail.size, (32, 24));
+    assert_eq!(decoded_thumbnail.colour_type, ImageColourType::Rgb8);
+    assert_eq!(decoded_thumbnail.info.format, ImageFormat::Jpeg);
+}
+
+#[test]
+fn test_no_embedded_thumbnail() {
+    // this jpeg's exif chunk only has the one ifd.
+    assert!(decode_embedded_thumbnail(include_bytes!("../example.jpg"), &ImageFormat::Jpeg).is_none());
+    assert!(decode_embedded_thumbnail(include_bytes!("../example.png"), &ImageFormat::Png).is_none());
+}